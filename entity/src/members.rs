@@ -11,15 +11,11 @@ pub struct Model {
     pub first_name: String,
     pub last_name: String,
     pub email: Option<String>,
-    #[sea_orm(unique)]
     pub contact: String,
     pub gender: String,
     pub date_of_birth: Date,
     pub residential_address: String,
     pub date_joined: Option<Date>,
-    pub department: String,
-    pub aux_department: String,
-    pub sub_department: String,
     pub added_by: Option<Uuid>,
     pub alias: Option<String>,
     pub member_type: String,
@@ -27,10 +23,26 @@ pub struct Model {
     pub organization_id: Uuid,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub household_id: Option<Uuid>,
+    pub status: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::households::Entity",
+        from = "Column::HouseholdId",
+        to = "super::households::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Households,
+    #[sea_orm(has_many = "super::member_applications::Entity")]
+    MemberApplications,
+    #[sea_orm(has_many = "super::member_departments::Entity")]
+    MemberDepartments,
+    #[sea_orm(has_many = "super::member_status_history::Entity")]
+    MemberStatusHistory,
     #[sea_orm(
         belongs_to = "super::organization::Entity",
         from = "Column::OrganizationId",
@@ -43,6 +55,30 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::households::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Households.def()
+    }
+}
+
+impl Related<super::member_applications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberApplications.def()
+    }
+}
+
+impl Related<super::member_departments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberDepartments.def()
+    }
+}
+
+impl Related<super::member_status_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberStatusHistory.def()
+    }
+}
+
 impl Related<super::organization::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Organization.def()
@@ -2,7 +2,25 @@
 
 pub mod prelude;
 
+pub mod api_keys;
+pub mod departments;
+pub mod export_jobs;
+pub mod households;
+pub mod identity_providers;
+pub mod invitations;
+pub mod lockout_events;
+pub mod login_history;
 pub mod media;
+pub mod member_applications;
+pub mod member_departments;
+pub mod member_relationships;
+pub mod member_status_history;
 pub mod members;
+pub mod oidc_login_states;
 pub mod organization;
+pub mod organization_settings;
+pub mod password_history;
+pub mod password_reset_codes;
+pub mod recovery_codes;
+pub mod refresh_tokens;
 pub mod users;
@@ -20,8 +20,102 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::api_keys::Entity")]
+    ApiKeys,
+    #[sea_orm(has_many = "super::departments::Entity")]
+    Departments,
+    #[sea_orm(has_many = "super::export_jobs::Entity")]
+    ExportJobs,
+    #[sea_orm(has_many = "super::households::Entity")]
+    Households,
+    #[sea_orm(has_one = "super::identity_providers::Entity")]
+    IdentityProviders,
+    #[sea_orm(has_many = "super::invitations::Entity")]
+    Invitations,
+    #[sea_orm(has_many = "super::lockout_events::Entity")]
+    LockoutEvents,
+    #[sea_orm(has_many = "super::member_applications::Entity")]
+    MemberApplications,
+    #[sea_orm(has_many = "super::member_departments::Entity")]
+    MemberDepartments,
+    #[sea_orm(has_many = "super::member_relationships::Entity")]
+    MemberRelationships,
+    #[sea_orm(has_many = "super::member_status_history::Entity")]
+    MemberStatusHistory,
     #[sea_orm(has_many = "super::members::Entity")]
     Members,
+    #[sea_orm(has_many = "super::oidc_login_states::Entity")]
+    OidcLoginStates,
+    #[sea_orm(has_one = "super::organization_settings::Entity")]
+    OrganizationSettings,
+    #[sea_orm(has_many = "super::users::Entity")]
+    Users,
+}
+
+impl Related<super::api_keys::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiKeys.def()
+    }
+}
+
+impl Related<super::departments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Departments.def()
+    }
+}
+
+impl Related<super::export_jobs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExportJobs.def()
+    }
+}
+
+impl Related<super::households::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Households.def()
+    }
+}
+
+impl Related<super::identity_providers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IdentityProviders.def()
+    }
+}
+
+impl Related<super::invitations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Invitations.def()
+    }
+}
+
+impl Related<super::lockout_events::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LockoutEvents.def()
+    }
+}
+
+impl Related<super::member_applications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberApplications.def()
+    }
+}
+
+impl Related<super::member_departments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberDepartments.def()
+    }
+}
+
+impl Related<super::member_relationships::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberRelationships.def()
+    }
+}
+
+impl Related<super::member_status_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberStatusHistory.def()
+    }
 }
 
 impl Related<super::members::Entity> for Entity {
@@ -30,4 +124,22 @@ impl Related<super::members::Entity> for Entity {
     }
 }
 
+impl Related<super::oidc_login_states::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OidcLoginStates.def()
+    }
+}
+
+impl Related<super::organization_settings::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrganizationSettings.def()
+    }
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
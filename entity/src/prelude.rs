@@ -1,6 +1,24 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.3
 
+pub use super::api_keys::Entity as ApiKeys;
+pub use super::departments::Entity as Departments;
+pub use super::export_jobs::Entity as ExportJobs;
+pub use super::households::Entity as Households;
+pub use super::identity_providers::Entity as IdentityProviders;
+pub use super::invitations::Entity as Invitations;
+pub use super::lockout_events::Entity as LockoutEvents;
+pub use super::login_history::Entity as LoginHistory;
 pub use super::media::Entity as Media;
+pub use super::member_applications::Entity as MemberApplications;
+pub use super::member_departments::Entity as MemberDepartments;
+pub use super::member_relationships::Entity as MemberRelationships;
+pub use super::member_status_history::Entity as MemberStatusHistory;
 pub use super::members::Entity as Members;
+pub use super::oidc_login_states::Entity as OidcLoginStates;
 pub use super::organization::Entity as Organization;
+pub use super::organization_settings::Entity as OrganizationSettings;
+pub use super::password_history::Entity as PasswordHistory;
+pub use super::password_reset_codes::Entity as PasswordResetCodes;
+pub use super::recovery_codes::Entity as RecoveryCodes;
+pub use super::refresh_tokens::Entity as RefreshTokens;
 pub use super::users::Entity as Users;
@@ -10,17 +10,38 @@ pub struct Model {
     pub email: Option<String>,
     pub member_id: Uuid,
     pub session_id: Option<Uuid>,
-    #[sea_orm(unique)]
     pub contact: String,
     pub password: String,
     pub role: String,
     pub is_password_changed: bool,
+    pub totp_secret: Option<String>,
+    pub is_totp_enabled: bool,
+    pub totp_last_step: Option<i64>,
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTimeWithTimeZone>,
+    pub oidc_issuer: Option<String>,
+    pub oidc_subject: Option<String>,
+    pub organization_id: Uuid,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::api_keys::Entity")]
+    ApiKeys,
+    #[sea_orm(has_many = "super::export_jobs::Entity")]
+    ExportJobs,
+    #[sea_orm(has_many = "super::invitations::Entity")]
+    Invitations,
+    #[sea_orm(has_many = "super::lockout_events::Entity")]
+    LockoutEvents,
+    #[sea_orm(has_many = "super::login_history::Entity")]
+    LoginHistory,
+    #[sea_orm(has_many = "super::member_applications::Entity")]
+    MemberApplications,
+    #[sea_orm(has_many = "super::member_status_history::Entity")]
+    MemberStatusHistory,
     #[sea_orm(
         belongs_to = "super::members::Entity",
         from = "Column::MemberId",
@@ -29,6 +50,64 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Members,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Organization,
+    #[sea_orm(has_many = "super::password_history::Entity")]
+    PasswordHistory,
+    #[sea_orm(has_many = "super::password_reset_codes::Entity")]
+    PasswordResetCodes,
+    #[sea_orm(has_many = "super::recovery_codes::Entity")]
+    RecoveryCodes,
+    #[sea_orm(has_many = "super::refresh_tokens::Entity")]
+    RefreshTokens,
+}
+
+impl Related<super::api_keys::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiKeys.def()
+    }
+}
+
+impl Related<super::export_jobs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExportJobs.def()
+    }
+}
+
+impl Related<super::invitations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Invitations.def()
+    }
+}
+
+impl Related<super::lockout_events::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LockoutEvents.def()
+    }
+}
+
+impl Related<super::login_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LoginHistory.def()
+    }
+}
+
+impl Related<super::member_applications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberApplications.def()
+    }
+}
+
+impl Related<super::member_status_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemberStatusHistory.def()
+    }
 }
 
 impl Related<super::members::Entity> for Entity {
@@ -37,4 +116,34 @@ impl Related<super::members::Entity> for Entity {
     }
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl Related<super::password_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PasswordHistory.def()
+    }
+}
+
+impl Related<super::password_reset_codes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PasswordResetCodes.def()
+    }
+}
+
+impl Related<super::recovery_codes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecoveryCodes.def()
+    }
+}
+
+impl Related<super::refresh_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RefreshTokens.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -4,6 +4,27 @@ mod m20250213_211841_create_users;
 mod m20250213_220702_create_members;
 mod m20250214_144741_create_organization;
 mod m20250214_150448_create_media_schema;
+mod m20250310_101500_create_refresh_tokens;
+mod m20250312_090000_scope_tenant_data;
+mod m20250315_120000_create_password_history;
+mod m20250318_100000_create_password_reset_codes;
+mod m20250320_090000_add_two_factor;
+mod m20250322_090000_create_lockout_events;
+mod m20250324_090000_create_login_history;
+mod m20250326_090000_create_api_keys;
+mod m20250328_090000_create_identity_providers;
+mod m20250330_090000_create_invitations;
+mod m20250401_090000_add_member_search;
+mod m20250403_090000_create_export_jobs;
+mod m20250405_090000_create_households;
+mod m20250407_090000_create_member_status_history;
+mod m20250409_090000_create_departments;
+mod m20250411_090000_add_totp_last_step;
+mod m20250413_090000_add_identity_provider_jwks_uri;
+mod m20250415_090000_record_initial_member_status;
+mod m20250417_090000_scope_user_contacts;
+mod m20250419_090000_keep_records_of_deleted_users;
+mod m20250421_090000_restrict_department_delete;
 
 pub struct Migrator;
 
@@ -15,6 +36,27 @@ impl MigratorTrait for Migrator {
             Box::new(m20250213_220702_create_members::Migration),
             Box::new(m20250213_211841_create_users::Migration),
             Box::new(m20250214_150448_create_media_schema::Migration),
+            Box::new(m20250310_101500_create_refresh_tokens::Migration),
+            Box::new(m20250312_090000_scope_tenant_data::Migration),
+            Box::new(m20250315_120000_create_password_history::Migration),
+            Box::new(m20250318_100000_create_password_reset_codes::Migration),
+            Box::new(m20250320_090000_add_two_factor::Migration),
+            Box::new(m20250322_090000_create_lockout_events::Migration),
+            Box::new(m20250324_090000_create_login_history::Migration),
+            Box::new(m20250326_090000_create_api_keys::Migration),
+            Box::new(m20250328_090000_create_identity_providers::Migration),
+            Box::new(m20250330_090000_create_invitations::Migration),
+            Box::new(m20250401_090000_add_member_search::Migration),
+            Box::new(m20250403_090000_create_export_jobs::Migration),
+            Box::new(m20250405_090000_create_households::Migration),
+            Box::new(m20250407_090000_create_member_status_history::Migration),
+            Box::new(m20250409_090000_create_departments::Migration),
+            Box::new(m20250411_090000_add_totp_last_step::Migration),
+            Box::new(m20250413_090000_add_identity_provider_jwks_uri::Migration),
+            Box::new(m20250415_090000_record_initial_member_status::Migration),
+            Box::new(m20250417_090000_scope_user_contacts::Migration),
+            Box::new(m20250419_090000_keep_records_of_deleted_users::Migration),
+            Box::new(m20250421_090000_restrict_department_delete::Migration),
         ]
     }
 }
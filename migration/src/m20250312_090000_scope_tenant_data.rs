@@ -0,0 +1,107 @@
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+use crate::m20250213_220702_create_members::Members;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // member contacts only need to be unique within a church, otherwise the duplicate
+        // check leaks which numbers are registered with other organizations
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "ALTER TABLE members DROP CONSTRAINT IF EXISTS members_contact_key;".to_string(),
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_members_organization_contact")
+                    .table(Members::Table)
+                    .col(Members::OrganizationId)
+                    .col(Members::Contact)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Media::Table)
+                    .add_column(ColumnDef::new(Media::OrganizationId).uuid())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "UPDATE media SET organization_id = owner_id WHERE owner_id IN (SELECT id FROM organization);"
+                    .to_string(),
+            ))
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "UPDATE media SET organization_id = members.organization_id FROM members WHERE media.owner_id = members.id;"
+                    .to_string(),
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_media_organization_id")
+                    .table(Media::Table)
+                    .col(Media::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Media::Table)
+                    .drop_column(Media::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_members_organization_contact")
+                    .table(Members::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "ALTER TABLE members ADD CONSTRAINT members_contact_key UNIQUE (contact);"
+                    .to_string(),
+            ))
+            .await
+            .map(|_| ())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Media {
+    Table,
+    OrganizationId,
+}
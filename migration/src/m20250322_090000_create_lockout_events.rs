@@ -0,0 +1,126 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_211841_create_users::Users, m20250214_144741_create_organization::Organization,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Lockout::FailedLoginAttempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(Lockout::LockedUntil).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LockoutEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LockoutEvents::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(LockoutEvents::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(LockoutEvents::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(LockoutEvents::IpAddress).string().not_null())
+                    .col(ColumnDef::new(LockoutEvents::UserAgent).string().not_null())
+                    .col(
+                        ColumnDef::new(LockoutEvents::LockedUntil)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(LockoutEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(LockoutEvents::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LockoutEvents::Table, LockoutEvents::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LockoutEvents::Table, LockoutEvents::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_lockout_events_organization_id")
+                    .table(LockoutEvents::Table)
+                    .col(LockoutEvents::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LockoutEvents::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Lockout::FailedLoginAttempts)
+                    .drop_column(Lockout::LockedUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Lockout {
+    FailedLoginAttempts,
+    LockedUntil,
+}
+
+#[derive(DeriveIden)]
+pub enum LockoutEvents {
+    Table,
+    Id,
+    UserId,
+    OrganizationId,
+    IpAddress,
+    UserAgent,
+    LockedUntil,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20250213_211841_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LoginHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(LoginHistory::UserId).uuid().not_null())
+                    .col(ColumnDef::new(LoginHistory::IpAddress).string().not_null())
+                    .col(ColumnDef::new(LoginHistory::UserAgent).string().not_null())
+                    .col(
+                        ColumnDef::new(LoginHistory::IsSuccessful)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(LoginHistory::FailureReason).string())
+                    .col(
+                        ColumnDef::new(LoginHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(LoginHistory::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LoginHistory::Table, LoginHistory::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_history_user_id_created_at")
+                    .table(LoginHistory::Table)
+                    .col(LoginHistory::UserId)
+                    .col(LoginHistory::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum LoginHistory {
+    Table,
+    Id,
+    UserId,
+    IpAddress,
+    UserAgent,
+    IsSuccessful,
+    FailureReason,
+    CreatedAt,
+    UpdatedAt,
+}
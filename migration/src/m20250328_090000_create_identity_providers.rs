@@ -0,0 +1,241 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_211841_create_users::Users, m20250214_144741_create_organization::Organization,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdentityProviders::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IdentityProviders::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::OrganizationId)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::Issuer)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::ClientId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(IdentityProviders::ClientSecret).string())
+                    .col(
+                        ColumnDef::new(IdentityProviders::AuthorizationEndpoint)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::TokenEndpoint)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::RedirectUri)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::Scopes)
+                            .string()
+                            .not_null()
+                            .default("openid email profile"),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::IsEnabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviders::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IdentityProviders::Table, IdentityProviders::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OidcLoginStates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OidcLoginStates::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLoginStates::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLoginStates::StateHash)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLoginStates::CodeVerifier)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OidcLoginStates::Nonce).string().not_null())
+                    .col(
+                        ColumnDef::new(OidcLoginStates::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLoginStates::IsUsed)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLoginStates::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLoginStates::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OidcLoginStates::Table, OidcLoginStates::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Oidc::OidcIssuer).string())
+                    .add_column(ColumnDef::new(Oidc::OidcSubject).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_users_oidc_issuer_subject")
+                    .table(Users::Table)
+                    .col(Oidc::OidcIssuer)
+                    .col(Oidc::OidcSubject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_users_oidc_issuer_subject")
+                    .table(Users::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Oidc::OidcIssuer)
+                    .drop_column(Oidc::OidcSubject)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(OidcLoginStates::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(IdentityProviders::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Oidc {
+    OidcIssuer,
+    OidcSubject,
+}
+
+#[derive(DeriveIden)]
+pub enum IdentityProviders {
+    Table,
+    Id,
+    OrganizationId,
+    Issuer,
+    ClientId,
+    ClientSecret,
+    AuthorizationEndpoint,
+    TokenEndpoint,
+    RedirectUri,
+    Scopes,
+    IsEnabled,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum OidcLoginStates {
+    Table,
+    Id,
+    OrganizationId,
+    StateHash,
+    CodeVerifier,
+    Nonce,
+    ExpiresAt,
+    IsUsed,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,267 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_211841_create_users::Users, m20250213_220702_create_members::Members,
+    m20250214_144741_create_organization::Organization,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Invitations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Invitations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(Invitations::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Invitations::CreatedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Invitations::CodeHash)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Invitations::Prefix).string().not_null())
+                    .col(
+                        ColumnDef::new(Invitations::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Invitations::MaxUses)
+                            .integer()
+                            .check(Expr::col(Invitations::MaxUses).gt(0)),
+                    )
+                    .col(
+                        ColumnDef::new(Invitations::UseCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Invitations::IsRevoked)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Invitations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Invitations::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Invitations::Table, Invitations::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Invitations::Table, Invitations::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_invitations_organization_id")
+                    .table(Invitations::Table)
+                    .col(Invitations::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemberApplications::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemberApplications::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(MemberApplications::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MemberApplications::InvitationId).uuid())
+                    .col(
+                        ColumnDef::new(MemberApplications::FirstName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberApplications::LastName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MemberApplications::Email).string())
+                    .col(
+                        ColumnDef::new(MemberApplications::Contact)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberApplications::Gender)
+                            .string()
+                            .not_null()
+                            .check(
+                                Expr::col(MemberApplications::Gender).is_in(vec!["male", "female"]),
+                            ),
+                    )
+                    .col(
+                        ColumnDef::new(MemberApplications::DateOfBirth)
+                            .date()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberApplications::ResidentialAddress)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberApplications::Status)
+                            .string()
+                            .not_null()
+                            .check(
+                                Expr::col(MemberApplications::Status)
+                                    .is_in(vec!["pending", "approved", "rejected"]),
+                            )
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(MemberApplications::ReviewedBy).uuid())
+                    .col(ColumnDef::new(MemberApplications::ReviewedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(MemberApplications::RejectionReason).string())
+                    .col(ColumnDef::new(MemberApplications::MemberId).uuid())
+                    .col(
+                        ColumnDef::new(MemberApplications::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(MemberApplications::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                MemberApplications::Table,
+                                MemberApplications::OrganizationId,
+                            )
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberApplications::Table, MemberApplications::InvitationId)
+                            .to(Invitations::Table, Invitations::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberApplications::Table, MemberApplications::ReviewedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberApplications::Table, MemberApplications::MemberId)
+                            .to(Members::Table, Members::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_member_applications_organization_status")
+                    .table(MemberApplications::Table)
+                    .col(MemberApplications::OrganizationId)
+                    .col(MemberApplications::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemberApplications::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Invitations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Invitations {
+    Table,
+    Id,
+    OrganizationId,
+    CreatedBy,
+    CodeHash,
+    Prefix,
+    ExpiresAt,
+    MaxUses,
+    UseCount,
+    IsRevoked,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum MemberApplications {
+    Table,
+    Id,
+    OrganizationId,
+    InvitationId,
+    FirstName,
+    LastName,
+    Email,
+    Contact,
+    Gender,
+    DateOfBirth,
+    ResidentialAddress,
+    Status,
+    ReviewedBy,
+    ReviewedAt,
+    RejectionReason,
+    MemberId,
+    CreatedAt,
+    UpdatedAt,
+}
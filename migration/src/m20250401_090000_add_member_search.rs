@@ -0,0 +1,44 @@
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+use crate::m20250213_220702_create_members::Members;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "CREATE EXTENSION IF NOT EXISTS pg_trgm;".to_string(),
+            ))
+            .await?;
+
+        // the expression has to match the one member search queries with, otherwise
+        // the planner won't use the index
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "CREATE INDEX IF NOT EXISTS idx_members_search ON members USING gin ((lower(first_name || ' ' || last_name || ' ' || coalesce(alias, '') || ' ' || contact || ' ' || coalesce(email, ''))) gin_trgm_ops);"
+                    .to_string(),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_members_search")
+                    .table(Members::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
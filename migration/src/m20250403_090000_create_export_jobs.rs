@@ -0,0 +1,118 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_211841_create_users::Users, m20250214_144741_create_organization::Organization,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExportJobs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ExportJobs::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(ExportJobs::OrganizationId).uuid().not_null())
+                    .col(ColumnDef::new(ExportJobs::RequestedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(ExportJobs::Format)
+                            .string()
+                            .not_null()
+                            .check(Expr::col(ExportJobs::Format).is_in(vec!["csv", "xlsx", "pdf"])),
+                    )
+                    .col(ColumnDef::new(ExportJobs::Filters).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(ExportJobs::IncludePhotos)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(ExportJobs::Status)
+                            .string()
+                            .not_null()
+                            .check(Expr::col(ExportJobs::Status).is_in(vec![
+                                "pending",
+                                "running",
+                                "completed",
+                                "failed",
+                            ]))
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(ExportJobs::RowCount).integer())
+                    .col(ColumnDef::new(ExportJobs::FileName).string())
+                    .col(ColumnDef::new(ExportJobs::Error).string())
+                    .col(ColumnDef::new(ExportJobs::CompletedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(ExportJobs::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ExportJobs::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ExportJobs::Table, ExportJobs::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ExportJobs::Table, ExportJobs::RequestedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_export_jobs_organization_id")
+                    .table(ExportJobs::Table)
+                    .col(ExportJobs::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ExportJobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ExportJobs {
+    Table,
+    Id,
+    OrganizationId,
+    RequestedBy,
+    Format,
+    Filters,
+    IncludePhotos,
+    Status,
+    RowCount,
+    FileName,
+    Error,
+    CompletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,241 @@
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_220702_create_members::Members, m20250214_144741_create_organization::Organization,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Households::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Households::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(Households::OrganizationId).uuid().not_null())
+                    .col(ColumnDef::new(Households::Name).string().not_null())
+                    .col(ColumnDef::new(Households::Address).string())
+                    .col(
+                        ColumnDef::new(Households::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Households::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Households::Table, Households::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_households_organization_id")
+                    .table(Households::Table)
+                    .col(Households::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Members::Table)
+                    .add_column(ColumnDef::new(HouseholdMembers::HouseholdId).uuid())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_members_household_id")
+                            .from_tbl(Members::Table)
+                            .from_col(HouseholdMembers::HouseholdId)
+                            .to_tbl(Households::Table)
+                            .to_col(Households::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_members_household_id")
+                    .table(Members::Table)
+                    .col(HouseholdMembers::HouseholdId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // every relationship is stored from both sides, so a member's relatives are
+        // always found through member_id alone
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemberRelationships::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemberRelationships::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(MemberRelationships::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberRelationships::MemberId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberRelationships::RelatedMemberId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberRelationships::Relationship)
+                            .string()
+                            .not_null()
+                            .check(Expr::col(MemberRelationships::Relationship).is_in(vec![
+                                "spouse", "parent", "child", "guardian", "ward", "sibling",
+                            ])),
+                    )
+                    .col(
+                        ColumnDef::new(MemberRelationships::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(MemberRelationships::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .check(
+                        Expr::col(MemberRelationships::MemberId)
+                            .ne(Expr::col(MemberRelationships::RelatedMemberId)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                MemberRelationships::Table,
+                                MemberRelationships::OrganizationId,
+                            )
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberRelationships::Table, MemberRelationships::MemberId)
+                            .to(Members::Table, Members::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                MemberRelationships::Table,
+                                MemberRelationships::RelatedMemberId,
+                            )
+                            .to(Members::Table, Members::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_member_relationships_pair")
+                    .table(MemberRelationships::Table)
+                    .col(MemberRelationships::MemberId)
+                    .col(MemberRelationships::RelatedMemberId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_member_relationships_one_spouse ON member_relationships (member_id) WHERE relationship = 'spouse';"
+                    .to_string(),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemberRelationships::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Members::Table)
+                    .drop_foreign_key(Alias::new("fk_members_household_id"))
+                    .drop_column(HouseholdMembers::HouseholdId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Households::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Households {
+    Table,
+    Id,
+    OrganizationId,
+    Name,
+    Address,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum HouseholdMembers {
+    HouseholdId,
+}
+
+#[derive(DeriveIden)]
+pub enum MemberRelationships {
+    Table,
+    Id,
+    OrganizationId,
+    MemberId,
+    RelatedMemberId,
+    Relationship,
+    CreatedAt,
+    UpdatedAt,
+}
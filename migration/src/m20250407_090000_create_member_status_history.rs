@@ -0,0 +1,190 @@
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_211841_create_users::Users, m20250213_220702_create_members::Members,
+    m20250214_144741_create_organization::Organization,
+};
+
+const STATUSES: [&str; 6] = [
+    "visitor",
+    "convert",
+    "member",
+    "inactive",
+    "transferred",
+    "deceased",
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Members::Table)
+                    .add_column(
+                        ColumnDef::new(MemberStatus::Status)
+                            .string()
+                            .not_null()
+                            .check(Expr::col(MemberStatus::Status).is_in(STATUSES))
+                            .default("member"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // blocked members were how "inactive" used to be recorded; blocking itself
+        // stays, but only as a sign-in restriction
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "UPDATE members SET status = 'inactive' WHERE is_blocked;".to_string(),
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_members_organization_id_status")
+                    .table(Members::Table)
+                    .col(Members::OrganizationId)
+                    .col(MemberStatus::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemberStatusHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::MemberId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::FromStatus)
+                            .string()
+                            .not_null()
+                            .check(Expr::col(MemberStatusHistory::FromStatus).is_in(STATUSES)),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::ToStatus)
+                            .string()
+                            .not_null()
+                            .check(Expr::col(MemberStatusHistory::ToStatus).is_in(STATUSES)),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::EffectiveDate)
+                            .date()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::Reason)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MemberStatusHistory::ChangedBy).uuid())
+                    .col(
+                        ColumnDef::new(MemberStatusHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                MemberStatusHistory::Table,
+                                MemberStatusHistory::OrganizationId,
+                            )
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberStatusHistory::Table, MemberStatusHistory::MemberId)
+                            .to(Members::Table, Members::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberStatusHistory::Table, MemberStatusHistory::ChangedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_member_status_history_member_id")
+                    .table(MemberStatusHistory::Table)
+                    .col(MemberStatusHistory::MemberId)
+                    .col(MemberStatusHistory::EffectiveDate)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemberStatusHistory::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_members_organization_id_status")
+                    .table(Members::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Members::Table)
+                    .drop_column(MemberStatus::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemberStatus {
+    Status,
+}
+
+#[derive(DeriveIden)]
+pub enum MemberStatusHistory {
+    Table,
+    Id,
+    OrganizationId,
+    MemberId,
+    FromStatus,
+    ToStatus,
+    EffectiveDate,
+    Reason,
+    ChangedBy,
+    CreatedAt,
+}
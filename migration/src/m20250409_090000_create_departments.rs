@@ -0,0 +1,288 @@
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_220702_create_members::Members, m20250214_144741_create_organization::Organization,
+};
+
+// The values the members CHECK constraints allowed, each now a department of its own.
+const DEPARTMENTS: [&str; 4] = ["men", "women", "youth", "children"];
+const AUX_DEPARTMENTS: [&str; 4] = [
+    "pathfinders",
+    "young_singles",
+    "royal_rangers",
+    "missionettes",
+];
+const SUB_DEPARTMENTS: [&str; 3] = ["music", "ushers", "organizers"];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Departments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Departments::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(Departments::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Departments::ParentId).uuid())
+                    .col(ColumnDef::new(Departments::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Departments::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Departments::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .check(Expr::col(Departments::ParentId).ne(Expr::col(Departments::Id)))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Departments::Table, Departments::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Departments::Table, Departments::ParentId)
+                            .to(Departments::Table, Departments::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // names are unique across the whole organization, not just among siblings, so a
+        // department can be picked by name in imports
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_departments_organization_name ON departments (organization_id, lower(name));"
+                    .to_string(),
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_departments_parent_id")
+                    .table(Departments::Table)
+                    .col(Departments::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemberDepartments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemberDepartments::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(MemberDepartments::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberDepartments::MemberId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberDepartments::DepartmentId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemberDepartments::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberDepartments::Table, MemberDepartments::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberDepartments::Table, MemberDepartments::MemberId)
+                            .to(Members::Table, Members::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MemberDepartments::Table, MemberDepartments::DepartmentId)
+                            .to(Departments::Table, Departments::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_member_departments_pair")
+                    .table(MemberDepartments::Table)
+                    .col(MemberDepartments::MemberId)
+                    .col(MemberDepartments::DepartmentId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_member_departments_department_id")
+                    .table(MemberDepartments::Table)
+                    .col(MemberDepartments::DepartmentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // every existing organization keeps the departments it could choose from, named
+        // the way they were labelled ("young_singles" becomes "Young Singles")
+        let values = [&DEPARTMENTS[..], &AUX_DEPARTMENTS[..], &SUB_DEPARTMENTS[..]]
+            .concat()
+            .iter()
+            .map(|value| format!("('{}')", value))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                format!(
+                    "INSERT INTO departments (organization_id, name) SELECT organization.id, initcap(replace(v.value, '_', ' ')) FROM organization CROSS JOIN (VALUES {}) AS v(value) ON CONFLICT DO NOTHING;",
+                    values
+                ),
+            ))
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "INSERT INTO member_departments (organization_id, member_id, department_id) SELECT members.organization_id, members.id, departments.id FROM members CROSS JOIN LATERAL (VALUES (members.department), (members.aux_department), (members.sub_department)) AS v(value) JOIN departments ON departments.organization_id = members.organization_id AND departments.name = initcap(replace(v.value, '_', ' ')) WHERE v.value <> 'not_selected';"
+                    .to_string(),
+            ))
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Members::Table)
+                    .drop_column(Members::Department)
+                    .drop_column(Members::AuxDepartment)
+                    .drop_column(Members::SubDepartment)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Members::Table)
+                    .add_column(legacy_column("department", &DEPARTMENTS))
+                    .add_column(legacy_column("aux_department", &AUX_DEPARTMENTS))
+                    .add_column(legacy_column("sub_department", &SUB_DEPARTMENTS))
+                    .to_owned(),
+            )
+            .await?;
+
+        // members in several departments of one kind keep only one of them
+        for (column, values) in [
+            ("department", &DEPARTMENTS[..]),
+            ("aux_department", &AUX_DEPARTMENTS[..]),
+            ("sub_department", &SUB_DEPARTMENTS[..]),
+        ] {
+            manager
+                .get_connection()
+                .execute(Statement::from_string(
+                    manager.get_database_backend(),
+                    format!(
+                        "UPDATE members SET {column} = v.value FROM member_departments JOIN departments ON departments.id = member_departments.department_id CROSS JOIN (VALUES {values}) AS v(value) WHERE member_departments.member_id = members.id AND departments.name = initcap(replace(v.value, '_', ' '));",
+                        column = column,
+                        values = values
+                            .iter()
+                            .map(|value| format!("('{}')", value))
+                            .collect::<Vec<String>>()
+                            .join(", "),
+                    ),
+                ))
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(MemberDepartments::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Departments::Table).to_owned())
+            .await
+    }
+}
+
+// The members column as it was before departments had their own table.
+fn legacy_column(name: &str, values: &[&str]) -> ColumnDef {
+    let mut allowed = values.to_vec();
+    allowed.push("not_selected");
+
+    ColumnDef::new(Alias::new(name))
+        .string()
+        .not_null()
+        .check(Expr::col(Alias::new(name)).is_in(allowed))
+        .default("not_selected")
+        .to_owned()
+}
+
+#[derive(DeriveIden)]
+pub enum Departments {
+    Table,
+    Id,
+    OrganizationId,
+    ParentId,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum MemberDepartments {
+    Table,
+    Id,
+    OrganizationId,
+    MemberId,
+    DepartmentId,
+    CreatedAt,
+}
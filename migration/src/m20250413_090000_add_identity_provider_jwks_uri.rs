@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20250328_090000_create_identity_providers::IdentityProviders;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IdentityProviders::Table)
+                    .add_column(ColumnDef::new(JwksUri::JwksUri).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IdentityProviders::Table)
+                    .drop_column(JwksUri::JwksUri)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JwksUri {
+    JwksUri,
+}
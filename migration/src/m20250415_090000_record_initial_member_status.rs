@@ -0,0 +1,62 @@
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+use crate::m20250407_090000_create_member_status_history::MemberStatusHistory;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // a member's first row records the status they were created with, which has
+        // nothing to move from
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MemberStatusHistory::Table)
+                    .modify_column(
+                        ColumnDef::new(MemberStatusHistory::FromStatus)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // members with changes already start their trail at the first from_status, so only
+        // those without any history get their current status recorded
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "INSERT INTO member_status_history (organization_id, member_id, to_status, effective_date, reason) SELECT organization_id, id, status, COALESCE(date_joined, created_at::date), 'Initial status' FROM members WHERE NOT EXISTS (SELECT 1 FROM member_status_history WHERE member_status_history.member_id = members.id);".to_string(),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "DELETE FROM member_status_history WHERE from_status IS NULL;".to_string(),
+            ))
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MemberStatusHistory::Table)
+                    .modify_column(
+                        ColumnDef::new(MemberStatusHistory::FromStatus)
+                            .string()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
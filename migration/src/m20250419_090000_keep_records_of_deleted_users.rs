@@ -0,0 +1,113 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250213_211841_create_users::Users, m20250326_090000_create_api_keys::ApiKeys,
+    m20250330_090000_create_invitations::Invitations,
+    m20250403_090000_create_export_jobs::ExportJobs,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // API keys, invitation links and exports belong to the organization, so deleting
+        // the member who made them only forgets who that was
+        for (name, table, column) in references() {
+            relink(
+                manager,
+                name,
+                table.clone(),
+                column.clone(),
+                ForeignKeyAction::SetNull,
+            )
+            .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .modify_column(ColumnDef::new(column).uuid().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table, column) in references() {
+            manager
+                .exec_stmt(
+                    Query::delete()
+                        .from_table(table.clone())
+                        .and_where(Expr::col(column.clone()).is_null())
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .modify_column(ColumnDef::new(column.clone()).uuid().not_null())
+                        .to_owned(),
+                )
+                .await?;
+
+            relink(manager, name, table, column, ForeignKeyAction::Cascade).await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn references() -> [(&'static str, DynIden, DynIden); 3] {
+    [
+        (
+            "api_keys_created_by_fkey",
+            ApiKeys::Table.into_iden(),
+            ApiKeys::CreatedBy.into_iden(),
+        ),
+        (
+            "invitations_created_by_fkey",
+            Invitations::Table.into_iden(),
+            Invitations::CreatedBy.into_iden(),
+        ),
+        (
+            "export_jobs_requested_by_fkey",
+            ExportJobs::Table.into_iden(),
+            ExportJobs::RequestedBy.into_iden(),
+        ),
+    ]
+}
+
+async fn relink(
+    manager: &SchemaManager<'_>,
+    name: &str,
+    table: DynIden,
+    column: DynIden,
+    on_delete: ForeignKeyAction,
+) -> Result<(), DbErr> {
+    manager
+        .drop_foreign_key(
+            ForeignKey::drop()
+                .name(name)
+                .table(table.clone())
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_foreign_key(
+            ForeignKey::create()
+                .name(name)
+                .from(table, column)
+                .to(Users::Table, Users::Id)
+                .on_delete(on_delete)
+                .to_owned(),
+        )
+        .await
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20250409_090000_create_departments::Departments;
+
+const PARENT_FOREIGN_KEY: &str = "departments_parent_id_fkey";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // a department with sub-departments is refused by the database as well, so one
+        // added while the parent is being deleted isn't silently taken along with it
+        relink_parent(manager, ForeignKeyAction::Restrict).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        relink_parent(manager, ForeignKeyAction::Cascade).await
+    }
+}
+
+async fn relink_parent(
+    manager: &SchemaManager<'_>,
+    on_delete: ForeignKeyAction,
+) -> Result<(), DbErr> {
+    manager
+        .drop_foreign_key(
+            ForeignKey::drop()
+                .name(PARENT_FOREIGN_KEY)
+                .table(Departments::Table)
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_foreign_key(
+            ForeignKey::create()
+                .name(PARENT_FOREIGN_KEY)
+                .from(Departments::Table, Departments::ParentId)
+                .to(Departments::Table, Departments::Id)
+                .on_delete(on_delete)
+                .to_owned(),
+        )
+        .await
+}
@@ -0,0 +1,2282 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+
+use crate::{
+    app::{
+        auth::{
+            dto::dtos::{
+                change_user_password, claim_totp_step, count_reset_codes_since,
+                count_successful_logins, get_active_reset_code, get_active_sessions, get_api_keys,
+                get_lockout_events, get_login_history, get_password_history,
+                get_refresh_token_by_hash, get_user_by_email_in_organization, get_user_by_id,
+                get_user_by_oidc_subject, get_user_by_username, increment_failed_logins,
+                link_oidc_subject, mark_refresh_token_used, replace_recovery_codes, revoke_api_key,
+                revoke_token_family, revoke_user_sessions, save_api_key, save_lockout_event,
+                save_login_attempt, save_login_state, save_refresh_token, save_reset_code,
+                take_login_state, update_reset_code, update_user_lock, update_user_password,
+                update_user_session, update_user_totp, use_recovery_code,
+            },
+            models::model::{
+                ApiKeyResponseModel, ChangePasswordModel, CreateApiKeyModel, CreatedApiKeyModel,
+                ForgotPasswordModel, LoginHistoryQueryModel, LoginModel, LoginResponseModel,
+                OidcAuthorizeResponseModel, OidcCallbackModel, RefreshTokenModel,
+                ResetPasswordModel, SaveApiKeyDto, SaveLockoutEventDto, SaveLoginAttemptDto,
+                SaveLoginStateDto, SaveRefreshTokenDto, SessionResponseModel,
+                TwoFactorActivatedModel, TwoFactorCodeModel, TwoFactorEnrollmentModel,
+                VerifyTwoFactorModel,
+            },
+        },
+        members::dto::dtos::get_member_by_id,
+        organization::dto::dtos::{
+            find_organization_by_id, get_identity_provider, get_organization_settings,
+        },
+    },
+    libs::{
+        api_key::generate_api_key,
+        code::gen_numeric_code,
+        error,
+        ip::{get_ip_info, IpInfo},
+        jwt::{
+            create_jwt, create_refresh_token, gen_string, hash_token, JwtDto, SCOPE_FULL,
+            SCOPE_MFA, SCOPE_MFA_ENROLL, SCOPE_PASSWORD_CHANGE,
+        },
+        oidc,
+        pword::{check_password_policy, hash_password, verify_password},
+        totp, validator,
+    },
+    middlewares::{
+        auth::{AuthContext, API_KEY_SCOPES},
+        role::{ADMIN, SUPER_ADMIN, TWO_FACTOR_ROLES},
+    },
+    utils::models::HttpClientResponse,
+    AppState,
+};
+
+pub async fn login(
+    req: HttpRequest,
+    payload: web::Json<LoginModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let username = validator::required_str(&payload.username, "Username")?;
+    let password = validator::required_str(&payload.password, "Password")?;
+    let organization_id = payload
+        .organization_id
+        .as_deref()
+        .map(|id| validator::uuid(id, "Organization ID"))
+        .transpose()?;
+
+    let client = get_ip_info(&state, &req, &req.connection_info());
+
+    if let Some(wait) = state.throttle.retry_after_ip(&client.addr) {
+        return Err(too_many_attempts(wait));
+    }
+
+    let user = match get_user_by_username(&username, organization_id, &state).await {
+        Ok(user) => user,
+        Err(_) => {
+            state.throttle.record_ip_failure(&client.addr);
+            return Err(error::new_error(1001, "Invalid Credentials", 401));
+        }
+    };
+
+    // keyed on the user id, so signing in by email or by contact shares one window
+    let account = user.id.to_string();
+
+    if let Some(wait) = state.throttle.retry_after(&client.addr, &account) {
+        return Err(too_many_attempts(wait));
+    }
+
+    // checked before the password so a locked account gives nothing away about it
+    if user
+        .locked_until
+        .is_some_and(|until| until > chrono::Utc::now())
+    {
+        record_login(&user, &client, Some("Account Locked"), &state).await;
+        return Err(error::new_error(1003, "Account is Temporarily Locked", 423));
+    }
+
+    // legacy hashes were salted with the user id, so it doubles as their salt
+    let check = verify_password(&password, &user.id, &user.password, &state.config);
+
+    if !check.valid {
+        state.throttle.record_failure(&client.addr, &account);
+        record_login(&user, &client, Some("Invalid Password"), &state).await;
+        register_failed_login(&user, &client, &state).await?;
+
+        return Err(error::new_error(1001, "Invalid Credentials", 401));
+    }
+
+    if user.failed_login_attempts > 0 || user.locked_until.is_some() {
+        update_user_lock(user.id, None, &state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+    }
+
+    if check.needs_rehash {
+        let upgraded = match hash_password(&password, &state.config) {
+            Ok(hash) => update_user_password(user.id, hash, &state)
+                .await
+                .map_err(error::Error::from_db_err),
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = upgraded {
+            log::warn!(
+                "Failed to upgrade password hash for {}: {}",
+                user.id,
+                e.message
+            );
+        }
+    }
+
+    let member = get_member_by_id(user.member_id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if account_blocked(&member, &state).await? {
+        record_login(&user, &client, Some("Account Blocked"), &state).await;
+        return Err(error::new_error(1003, "Account is Blocked", 403));
+    }
+
+    let session_id = uuid::Uuid::new_v4();
+
+    if let Err(e) = update_user_session(user.id, Some(session_id), &state).await {
+        return Err(error::Error::from_db_err(e));
+    };
+
+    let scope = session_scope(&user, &member, &state).await?;
+
+    // a 2FA sign-in is only complete, recorded and its failures forgiven, once the code
+    // has been verified
+    if scope != SCOPE_MFA {
+        state.throttle.reset_account(&account);
+        record_login(&user, &client, None, &state).await;
+    }
+
+    let tokens = issue_tokens(user, member, session_id, scope, &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Login Successful".to_string(),
+        data: json!(tokens),
+    }))
+}
+
+pub async fn refresh(
+    _req: HttpRequest,
+    payload: web::Json<RefreshTokenModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let refresh_token = validator::required_str(&payload.refresh_token, "Refresh Token")?;
+
+    let stored = match get_refresh_token_by_hash(&hash_token(&refresh_token), &state).await {
+        Ok(token) => token,
+        Err(_) => return Err(error::new_error(1001, "Invalid Refresh Token", 401)),
+    };
+
+    if stored.is_revoked || stored.expires_at < chrono::Utc::now() {
+        return Err(error::new_error(1001, "Invalid Refresh Token", 401));
+    }
+
+    // a token that was already rotated out is being replayed, so the whole session is burnt
+    if stored.is_used {
+        revoke_token_family(stored.family_id, &state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        return Err(error::new_error(1001, "Refresh Token Reuse Detected", 401));
+    }
+
+    let user = get_user_by_id(stored.user_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let member = get_member_by_id(user.member_id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if account_blocked(&member, &state).await? {
+        revoke_token_family(stored.family_id, &state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        return Err(error::new_error(1003, "Account is Blocked", 403));
+    }
+
+    // checked before the token is spent, so a retry gets the same answer rather than
+    // looking like a replay
+    if !user.is_password_changed {
+        return Err(error::new_error(1003, "Password Change Required", 403));
+    }
+
+    // the organization may have made 2FA mandatory after this session was opened
+    if !user.is_totp_enabled && two_factor_required(&user, &member, &state).await? {
+        return Err(error::new_error(
+            1003,
+            "Two-Factor Enrollment Required",
+            403,
+        ));
+    }
+
+    // a concurrent refresh with the same token got there first
+    if !mark_refresh_token_used(stored.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        revoke_token_family(stored.family_id, &state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        return Err(error::new_error(1001, "Refresh Token Reuse Detected", 401));
+    }
+
+    let tokens = issue_tokens(user, member, stored.family_id, SCOPE_FULL, &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Token Refreshed Successfully".to_string(),
+        data: json!(tokens),
+    }))
+}
+
+pub async fn logout(
+    _req: HttpRequest,
+    payload: web::Json<RefreshTokenModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let refresh_token = validator::required_str(&payload.refresh_token, "Refresh Token")?;
+
+    let stored = match get_refresh_token_by_hash(&hash_token(&refresh_token), &state).await {
+        Ok(token) => token,
+        Err(_) => return Err(error::new_error(1001, "Invalid Refresh Token", 401)),
+    };
+
+    revoke_token_family(stored.family_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Logout Successful".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn change_password(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<ChangePasswordModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let current_password = validator::required_str(&payload.current_password, "Current Password")?;
+    let new_password = validator::required_str(&payload.new_password, "New Password")?;
+
+    check_password_policy(&new_password, &state)?;
+
+    let user = get_user_by_id(auth.user_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !verify_password(&current_password, &user.id, &user.password, &state.config).valid {
+        return Err(error::new_error(1002, "Current Password is Incorrect", 422));
+    }
+
+    ensure_password_not_reused(&user, &new_password, &state).await?;
+
+    let hash = hash_password(&new_password, &state.config)?;
+
+    change_user_password(user.id, hash, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    // every existing session, including the restricted one used here, is ended
+    revoke_user_sessions(user.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let user = get_user_by_id(user.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let member = get_member_by_id(user.member_id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let session_id = uuid::Uuid::new_v4();
+
+    if let Err(e) = update_user_session(user.id, Some(session_id), &state).await {
+        return Err(error::Error::from_db_err(e));
+    };
+
+    let scope = session_scope(&user, &member, &state).await?;
+
+    let tokens = issue_tokens(user, member, session_id, scope, &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Password Changed Successfully".to_string(),
+        data: json!(tokens),
+    }))
+}
+
+pub async fn forgot_password(
+    req: HttpRequest,
+    payload: web::Json<ForgotPasswordModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let username = validator::required_str(&payload.username, "Username")?;
+    let organization_id = payload
+        .organization_id
+        .as_deref()
+        .map(|id| validator::uuid(id, "Organization ID"))
+        .transpose()?;
+
+    let client = get_ip_info(&state, &req, &req.connection_info());
+
+    if let Some(wait) = state.throttle.retry_after_reset_ip(&client.addr) {
+        return Err(too_many_attempts(wait));
+    }
+
+    // every request counts, so one address cannot sweep contacts looking for accounts;
+    // only against the address, or anyone could lock a user out by naming them here
+    state.throttle.record_reset_ip_failure(&client.addr);
+
+    // the response is identical whether or not the account exists
+    let response = HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "If the account exists, a reset code has been sent".to_string(),
+        data: json!({}),
+    });
+
+    let user = match get_user_by_username(&username, organization_id, &state).await {
+        Ok(user) => user,
+        Err(_) => return Ok(response),
+    };
+
+    let max_requests = state
+        .config
+        .get::<u64>("password_reset.max_requests")
+        .unwrap();
+    let window = state
+        .config
+        .get::<i64>("password_reset.request_window")
+        .unwrap();
+    let expire = state
+        .config
+        .get::<i64>("password_reset.code_expire")
+        .unwrap();
+
+    let recent = count_reset_codes_since(
+        user.id,
+        chrono::Utc::now() - chrono::Duration::seconds(window),
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    // past the limit nothing more is sent, but the caller cannot tell that apart
+    // from an unknown account either
+    if recent >= max_requests {
+        return Ok(response);
+    }
+
+    let code = gen_numeric_code(6);
+
+    save_reset_code(
+        user.id,
+        hash_token(&code),
+        chrono::Utc::now() + chrono::Duration::seconds(expire),
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    let recipient = user.email.clone().unwrap_or(user.contact.clone());
+
+    if let Err(e) = state.notifier.send(
+        &recipient,
+        "Password Reset",
+        &format!(
+            "Your password reset code is {}. It expires in {} minutes.",
+            code,
+            expire / 60
+        ),
+    ) {
+        log::error!("Failed to deliver reset code for {}: {}", user.id, e);
+    }
+
+    Ok(response)
+}
+
+pub async fn reset_password(
+    req: HttpRequest,
+    payload: web::Json<ResetPasswordModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let username = validator::required_str(&payload.username, "Username")?;
+    let code = validator::required_str(&payload.code, "Code")?;
+    let new_password = validator::required_str(&payload.new_password, "New Password")?;
+    let organization_id = payload
+        .organization_id
+        .as_deref()
+        .map(|id| validator::uuid(id, "Organization ID"))
+        .transpose()?;
+
+    let client = get_ip_info(&state, &req, &req.connection_info());
+
+    if let Some(wait) = state.throttle.retry_after_reset_ip(&client.addr) {
+        return Err(too_many_attempts(wait));
+    }
+
+    let user = get_user_by_username(&username, organization_id, &state)
+        .await
+        .map_err(|_| {
+            state.throttle.record_reset_ip_failure(&client.addr);
+            error::new_error(1002, "Invalid or Expired Code", 422)
+        })?;
+
+    let account = user.id.to_string();
+
+    if let Some(wait) = state.throttle.retry_after_reset(&client.addr, &account) {
+        return Err(too_many_attempts(wait));
+    }
+
+    let invalid = || {
+        state.throttle.record_reset_failure(&client.addr, &account);
+        error::new_error(1002, "Invalid or Expired Code", 422)
+    };
+
+    let stored = get_active_reset_code(user.id, &state)
+        .await
+        .map_err(|_| invalid())?;
+
+    let max_attempts = state
+        .config
+        .get::<i32>("password_reset.max_attempts")
+        .unwrap();
+    let attempts = stored.attempts + 1;
+
+    if stored.code_hash != hash_token(&code) {
+        update_reset_code(stored.id, attempts, attempts >= max_attempts, &state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        return Err(invalid());
+    }
+
+    check_password_policy(&new_password, &state)?;
+    ensure_password_not_reused(&user, &new_password, &state).await?;
+
+    let hash = hash_password(&new_password, &state.config)?;
+
+    update_reset_code(stored.id, attempts, true, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    change_user_password(user.id, hash, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    revoke_user_sessions(user.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    // proving control of the contact is enough to lift a lockout
+    state.throttle.reset_account(&account);
+
+    update_user_lock(user.id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Password Reset Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn list_sessions(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "User ID")?;
+
+    let user = get_organization_user(&auth, id, &state).await?;
+
+    let sessions = get_active_sessions(user.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let sessions: Vec<SessionResponseModel> = sessions
+        .into_iter()
+        .map(|s| SessionResponseModel {
+            session_id: s.family_id.to_string(),
+            last_active_at: s.created_at.to_rfc3339(),
+            expires_at: s.expires_at.to_rfc3339(),
+            current: user.session_id == Some(s.family_id),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Sessions Retrieved Successfully".to_string(),
+        data: json!(sessions),
+    }))
+}
+
+pub async fn revoke_session(
+    _req: HttpRequest,
+    auth: AuthContext,
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let (id, session_id) = path.into_inner();
+    let id = validator::uuid(&id, "User ID")?;
+    let session_id = validator::uuid(&session_id, "Session ID")?;
+
+    let user = get_organization_user(&auth, id, &state).await?;
+
+    let sessions = get_active_sessions(user.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !sessions.iter().any(|s| s.family_id == session_id) {
+        return Ok(HttpResponse::NotFound().json(HttpClientResponse {
+            code: 2001,
+            status: false,
+            message: "Session Not Found".to_string(),
+            data: json!({}),
+        }));
+    }
+
+    revoke_token_family(session_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Session Revoked Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn revoke_all_sessions(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "User ID")?;
+
+    let user = get_organization_user(&auth, id, &state).await?;
+
+    revoke_user_sessions(user.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Sessions Revoked Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn enroll_two_factor(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let user = get_user_by_id(auth.user_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if user.is_totp_enabled {
+        return Err(error::new_error(1006, "Two-Factor Already Enabled", 409));
+    }
+
+    // the secret stays inactive until a code generated from it has been confirmed
+    let secret = totp::generate_secret();
+
+    update_user_totp(user.id, Some(secret.clone()), false, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let issuer = state.config.get::<String>("app.name").unwrap();
+    let account = user.email.clone().unwrap_or(user.contact.clone());
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Two-Factor Enrollment Started".to_string(),
+        data: json!(TwoFactorEnrollmentModel {
+            otpauth_uri: totp::otpauth_uri(&issuer, &account, &secret),
+            secret,
+        }),
+    }))
+}
+
+pub async fn activate_two_factor(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<TwoFactorCodeModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let code = validator::required_str(&payload.code, "Code")?;
+
+    let user = get_user_by_id(auth.user_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if user.is_totp_enabled {
+        return Err(error::new_error(1006, "Two-Factor Already Enabled", 409));
+    }
+
+    let secret = match &user.totp_secret {
+        Some(secret) => secret.clone(),
+        None => {
+            return Err(error::new_error(
+                1002,
+                "Two-Factor Enrollment Not Started",
+                422,
+            ))
+        }
+    };
+
+    if !accept_totp_code(&user, &secret, &code, &state).await? {
+        return Err(error::new_error(1002, "Invalid Code", 422));
+    }
+
+    update_user_totp(user.id, Some(secret), true, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let recovery_codes: Vec<String> = (0..10).map(|_| gen_string(10)).collect();
+
+    replace_recovery_codes(
+        user.id,
+        recovery_codes.iter().map(|c| hash_token(c)).collect(),
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    // a login held back for mandatory enrollment is upgraded in place
+    let tokens = if auth.scope == SCOPE_MFA_ENROLL {
+        let member = get_member_by_id(user.member_id, None, &state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        Some(issue_tokens(user, member, auth.session_id, SCOPE_FULL, &state).await?)
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Two-Factor Enabled Successfully".to_string(),
+        data: json!(TwoFactorActivatedModel {
+            recovery_codes,
+            tokens,
+        }),
+    }))
+}
+
+pub async fn verify_two_factor(
+    req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<VerifyTwoFactorModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let user = get_user_by_id(auth.user_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let client = get_ip_info(&state, &req, &req.connection_info());
+    let account = user.id.to_string();
+
+    if let Some(wait) = state.throttle.retry_after(&client.addr, &account) {
+        return Err(too_many_attempts(wait));
+    }
+
+    if user
+        .locked_until
+        .is_some_and(|until| until > chrono::Utc::now())
+    {
+        return Err(error::new_error(1003, "Account is Temporarily Locked", 423));
+    }
+
+    let secret = match (&user.totp_secret, user.is_totp_enabled) {
+        (Some(secret), true) => secret.clone(),
+        _ => return Err(error::new_error(1002, "Two-Factor Not Enabled", 422)),
+    };
+
+    let verified = match (&payload.code, &payload.recovery_code) {
+        (Some(code), _) => accept_totp_code(&user, &secret, code, &state).await?,
+        (None, Some(recovery_code)) => {
+            use_recovery_code(user.id, hash_token(recovery_code.trim()), &state)
+                .await
+                .map_err(error::Error::from_db_err)?
+        }
+        (None, None) => return Err(error::new_error(1002, "Code is required", 422)),
+    };
+
+    if !verified {
+        state.throttle.record_failure(&client.addr, &account);
+        record_login(&user, &client, Some("Invalid Two-Factor Code"), &state).await;
+        register_failed_login(&user, &client, &state).await?;
+
+        return Err(error::new_error(1001, "Invalid Code", 401));
+    }
+
+    let member = get_member_by_id(user.member_id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if account_blocked(&member, &state).await? {
+        return Err(error::new_error(1003, "Account is Blocked", 403));
+    }
+
+    state.throttle.reset_account(&account);
+    record_login(&user, &client, None, &state).await;
+
+    let tokens = issue_tokens(user, member, auth.session_id, SCOPE_FULL, &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Login Successful".to_string(),
+        data: json!(tokens),
+    }))
+}
+
+pub async fn disable_two_factor(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<TwoFactorCodeModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let code = validator::required_str(&payload.code, "Code")?;
+
+    let user = get_user_by_id(auth.user_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let secret = match (&user.totp_secret, user.is_totp_enabled) {
+        (Some(secret), true) => secret.clone(),
+        _ => return Err(error::new_error(1002, "Two-Factor Not Enabled", 422)),
+    };
+
+    let member = get_member_by_id(user.member_id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if two_factor_required(&user, &member, &state).await? {
+        return Err(error::new_error(
+            1003,
+            "Two-Factor is Required by Your Organization",
+            403,
+        ));
+    }
+
+    if !accept_totp_code(&user, &secret, &code, &state).await? {
+        return Err(error::new_error(1002, "Invalid Code", 422));
+    }
+
+    update_user_totp(user.id, None, false, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    replace_recovery_codes(user.id, vec![], &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Two-Factor Disabled Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn login_history(
+    _req: HttpRequest,
+    auth: AuthContext,
+    query: web::Query<LoginHistoryQueryModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let user_id = match &query.user_id {
+        Some(id) => validator::uuid(id, "User ID")?,
+        None => auth.user_id,
+    };
+
+    // anyone may read their own history, only admins may read someone else's
+    if user_id != auth.user_id {
+        if ![ADMIN, SUPER_ADMIN].contains(&auth.role.as_str()) {
+            return Err(error::new_error(1003, "Forbidden", 403));
+        }
+
+        get_organization_user(&auth, user_id, &state).await?;
+    }
+
+    let limit = state.config.get::<u64>("login_history.limit").unwrap();
+
+    let history = get_login_history(user_id, limit, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Login History Retrieved Successfully".to_string(),
+        data: json!(history),
+    }))
+}
+
+pub async fn list_lockouts(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let events = get_lockout_events(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Lockouts Retrieved Successfully".to_string(),
+        data: json!(events),
+    }))
+}
+
+pub async fn unlock_user(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "User ID")?;
+
+    let user = get_organization_user(&auth, id, &state).await?;
+
+    state.throttle.reset_account(&user.id.to_string());
+
+    update_user_lock(user.id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "User Unlocked Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn create_api_key(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<CreateApiKeyModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let name = validator::required_str(&payload.name, "Name")?;
+
+    if payload.scopes.is_empty() {
+        return Err(error::new_error(1002, "Scopes is required", 422));
+    }
+
+    let scopes = payload
+        .scopes
+        .iter()
+        .map(|scope| validator::one_of(scope, &API_KEY_SCOPES, "Scope"))
+        .collect::<Result<Vec<String>, error::Error>>()?;
+
+    if payload
+        .expires_at
+        .is_some_and(|at| at <= chrono::Utc::now())
+    {
+        return Err(error::new_error(1002, "Expiry must be in the future", 422));
+    }
+
+    let generated = generate_api_key(&state);
+
+    let api_key = save_api_key(
+        SaveApiKeyDto {
+            organization_id: auth.organization_id,
+            created_by: auth.user_id,
+            name,
+            prefix: generated.prefix,
+            key_hash: generated.hash,
+            scopes,
+            expires_at: payload.expires_at,
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    // the key itself is only ever shown in this response
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "API Key Created Successfully".to_string(),
+        data: json!(CreatedApiKeyModel {
+            key: generated.key,
+            api_key: api_key_response(api_key),
+        }),
+    }))
+}
+
+pub async fn list_api_keys(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let api_keys = get_api_keys(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let api_keys: Vec<ApiKeyResponseModel> = api_keys.into_iter().map(api_key_response).collect();
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "API Keys Retrieved Successfully".to_string(),
+        data: json!(api_keys),
+    }))
+}
+
+pub async fn delete_api_key(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "API Key ID")?;
+
+    let revoked = revoke_api_key(id, auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !revoked {
+        return Err(error::new_error(1004, "API Key Not Found", 404));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "API Key Revoked Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn oidc_authorize(
+    _req: HttpRequest,
+    organization_id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let organization_id = validator::uuid(&organization_id, "Organization ID")?;
+
+    let provider = match get_identity_provider(organization_id, &state).await {
+        Ok(provider) if provider.is_enabled => provider,
+        _ => return Err(error::new_error(1004, "Single Sign-On Not Available", 404)),
+    };
+
+    let state_expire = state.config.get::<i64>("oidc.state_expire").unwrap();
+
+    let login_state = gen_string(32);
+    let nonce = gen_string(32);
+    let pkce = oidc::pkce_pair();
+
+    let authorization_url =
+        oidc::authorization_url(&provider, &login_state, &nonce, &pkce.challenge)?;
+
+    save_login_state(
+        SaveLoginStateDto {
+            organization_id,
+            state_hash: hash_token(&login_state),
+            code_verifier: pkce.verifier,
+            nonce,
+            expires_at: chrono::Utc::now() + chrono::Duration::seconds(state_expire),
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Authorization URL Created Successfully".to_string(),
+        data: json!(OidcAuthorizeResponseModel { authorization_url }),
+    }))
+}
+
+// Completes a sign-in started by oidc_authorize. An identity is matched on its
+// issuer and subject, or on first use by a verified email within the organization,
+// after which the subject is linked to the account. The provider is trusted with
+// the second factor, so the session starts with full scope.
+pub async fn oidc_callback(
+    req: HttpRequest,
+    payload: web::Json<OidcCallbackModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let login_state = validator::required_str(&payload.state, "State")?;
+    let code = validator::required_str(&payload.code, "Code")?;
+
+    let login_state = take_login_state(hash_token(&login_state), &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .ok_or_else(|| error::new_error(1001, "Invalid or Expired State", 401))?;
+
+    let provider = match get_identity_provider(login_state.organization_id, &state).await {
+        Ok(provider) if provider.is_enabled => provider,
+        _ => return Err(error::new_error(1004, "Single Sign-On Not Available", 404)),
+    };
+
+    let claims = oidc::exchange_code(&provider, &code, &login_state.code_verifier, &state).await?;
+
+    if claims.nonce.as_deref() != Some(login_state.nonce.as_str()) {
+        return Err(error::new_error(1001, "Invalid ID Token", 401));
+    }
+
+    let user = match get_user_by_oidc_subject(&provider.issuer, &claims.sub, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        Some(user) => Some(user),
+        None => match &claims.email {
+            // an email the provider has not vouched for could belong to anyone
+            Some(email) if claims.email_verified == Some(true) => {
+                let user =
+                    get_user_by_email_in_organization(email, provider.organization_id, &state)
+                        .await
+                        .map_err(error::Error::from_db_err)?;
+
+                // an account already linked to another identity is never re-linked by email
+                match user {
+                    Some(user) if user.oidc_subject.is_none() => {
+                        link_oidc_subject(user.id, &provider.issuer, &claims.sub, &state)
+                            .await
+                            .map_err(error::Error::from_db_err)?;
+                        Some(user)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+    };
+
+    let user = user.ok_or_else(|| error::new_error(1003, "No Account for This Identity", 403))?;
+
+    let member = get_member_by_id(user.member_id, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if member.organization_id != provider.organization_id {
+        return Err(error::new_error(1003, "No Account for This Identity", 403));
+    }
+
+    let client = get_ip_info(&state, &req, &req.connection_info());
+
+    // the identity provider vouches only for who is signing in, so a lockout still holds
+    if user
+        .locked_until
+        .is_some_and(|until| until > chrono::Utc::now())
+    {
+        record_login(&user, &client, Some("Account Locked"), &state).await;
+        return Err(error::new_error(1003, "Account is Temporarily Locked", 423));
+    }
+
+    if account_blocked(&member, &state).await? {
+        record_login(&user, &client, Some("Account Blocked"), &state).await;
+        return Err(error::new_error(1003, "Account is Blocked", 403));
+    }
+
+    let session_id = uuid::Uuid::new_v4();
+
+    if let Err(e) = update_user_session(user.id, Some(session_id), &state).await {
+        return Err(error::Error::from_db_err(e));
+    };
+
+    // single sign-on stands in for the password only, so 2FA and a pending password
+    // change still apply as they do for password logins
+    let scope = session_scope(&user, &member, &state).await?;
+
+    if scope != SCOPE_MFA {
+        record_login(&user, &client, None, &state).await;
+    }
+
+    let tokens = issue_tokens(user, member, session_id, scope, &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Login Successful".to_string(),
+        data: json!(tokens),
+    }))
+}
+
+fn api_key_response(api_key: entity::api_keys::Model) -> ApiKeyResponseModel {
+    ApiKeyResponseModel {
+        id: api_key.id.to_string(),
+        name: api_key.name,
+        prefix: api_key.prefix,
+        scopes: api_key
+            .scopes
+            .split_whitespace()
+            .map(|s| s.to_string())
+            .collect(),
+        expires_at: api_key.expires_at.map(|at| at.to_rfc3339()),
+        last_used_at: api_key.last_used_at.map(|at| at.to_rfc3339()),
+        is_revoked: api_key.is_revoked,
+        created_at: api_key.created_at.to_rfc3339(),
+    }
+}
+
+// Counts a failed sign-in against the account and locks it once the limit is hit.
+// The lock is recorded for the organization's admins and the user is told about it.
+async fn register_failed_login(
+    user: &entity::users::Model,
+    client: &IpInfo,
+    state: &web::Data<AppState>,
+) -> Result<(), error::Error> {
+    let max_failures = state.config.get::<i32>("lockout.max_failures").unwrap();
+    let duration = state.config.get::<i64>("lockout.duration").unwrap();
+
+    let attempts = increment_failed_logins(user.id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if attempts < max_failures {
+        return Ok(());
+    }
+
+    let locked_until = chrono::Utc::now() + chrono::Duration::seconds(duration);
+
+    update_user_lock(user.id, Some(locked_until), state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let member = get_member_by_id(user.member_id, None, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    save_lockout_event(
+        SaveLockoutEventDto {
+            user_id: user.id,
+            organization_id: member.organization_id,
+            ip_address: client.addr.clone(),
+            user_agent: client.user_agent.clone(),
+            locked_until,
+        },
+        state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    let recipient = user.email.clone().unwrap_or(user.contact.clone());
+
+    if let Err(e) = state.notifier.send(
+        &recipient,
+        "Account Locked",
+        &format!(
+            "Your account was locked for {} minutes after {} failed sign-in attempts from {}.",
+            duration / 60,
+            attempts,
+            client.addr
+        ),
+    ) {
+        log::error!("Failed to deliver lockout notice for {}: {}", user.id, e);
+    }
+
+    Ok(())
+}
+
+// Sign-ins are audited on a best-effort basis, a failed write never blocks the login.
+// The first successful sign-in from an unseen user agent is reported to the user.
+async fn record_login(
+    user: &entity::users::Model,
+    client: &IpInfo,
+    failure_reason: Option<&str>,
+    state: &web::Data<AppState>,
+) {
+    if failure_reason.is_none() {
+        let previous = count_successful_logins(user.id, None, state).await;
+        let from_device =
+            count_successful_logins(user.id, Some(client.user_agent.clone()), state).await;
+
+        // an account's very first sign-in has no known devices to compare against
+        if let (Ok(previous), Ok(0)) = (previous, from_device) {
+            if previous > 0 {
+                notify_new_device(user, client, state);
+            }
+        }
+    }
+
+    let saved = save_login_attempt(
+        SaveLoginAttemptDto {
+            user_id: user.id,
+            ip_address: client.addr.clone(),
+            user_agent: client.user_agent.clone(),
+            failure_reason: failure_reason.map(|r| r.to_string()),
+        },
+        state,
+    )
+    .await;
+
+    if let Err(e) = saved {
+        log::error!("Failed to record login attempt for {}: {}", user.id, e);
+    }
+}
+
+fn notify_new_device(user: &entity::users::Model, client: &IpInfo, state: &web::Data<AppState>) {
+    let recipient = user.email.clone().unwrap_or(user.contact.clone());
+
+    if let Err(e) = state.notifier.send(
+        &recipient,
+        "New Sign-In",
+        &format!(
+            "Your account was signed in to from a new device ({}) at {} on {}. If this was not you, reset your password.",
+            client.user_agent,
+            client.addr,
+            chrono::Utc::now().to_rfc2822()
+        ),
+    ) {
+        log::error!("Failed to deliver new device notice for {}: {}", user.id, e);
+    }
+}
+
+fn too_many_attempts(wait: u64) -> error::Error {
+    error::new_error(
+        1005,
+        &format!("Too Many Attempts, Try Again in {} Seconds", wait),
+        429,
+    )
+}
+
+// A member is shut out when either they or their whole organization is blocked.
+async fn account_blocked(
+    member: &entity::members::Model,
+    state: &web::Data<AppState>,
+) -> Result<bool, error::Error> {
+    if member.is_blocked {
+        return Ok(true);
+    }
+
+    let organization = find_organization_by_id(member.organization_id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(organization.is_none_or(|organization| organization.is_blocked))
+}
+
+// The scope a fresh session starts with: provisioned accounts must first change their
+// password, then users with 2FA must present a code, and users the organization
+// requires 2FA from must enroll before they get full access.
+async fn session_scope(
+    user: &entity::users::Model,
+    member: &entity::members::Model,
+    state: &web::Data<AppState>,
+) -> Result<&'static str, error::Error> {
+    if !user.is_password_changed {
+        return Ok(SCOPE_PASSWORD_CHANGE);
+    }
+
+    if user.is_totp_enabled {
+        return Ok(SCOPE_MFA);
+    }
+
+    if two_factor_required(user, member, state).await? {
+        return Ok(SCOPE_MFA_ENROLL);
+    }
+
+    Ok(SCOPE_FULL)
+}
+
+async fn two_factor_required(
+    user: &entity::users::Model,
+    member: &entity::members::Model,
+    state: &web::Data<AppState>,
+) -> Result<bool, error::Error> {
+    if !TWO_FACTOR_ROLES.contains(&user.role.as_str()) {
+        return Ok(false);
+    }
+
+    let settings = get_organization_settings(member.organization_id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(settings.map(|s| s.require_two_factor).unwrap_or(false))
+}
+
+async fn ensure_password_not_reused(
+    user: &entity::users::Model,
+    password: &str,
+    state: &web::Data<AppState>,
+) -> Result<(), error::Error> {
+    let history_size = state.config.get::<u64>("password.history_size").unwrap();
+
+    let history = get_password_history(user.id, history_size, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let reused = verify_password(password, &user.id, &user.password, &state.config).valid
+        || history
+            .iter()
+            .any(|h| verify_password(password, &user.id, &h.password, &state.config).valid);
+
+    if reused {
+        return Err(error::new_error(
+            1002,
+            &format!(
+                "Password cannot match any of the last {} passwords",
+                history_size
+            ),
+            422,
+        ));
+    }
+
+    Ok(())
+}
+
+// The matched step is claimed before the code counts, so a replay within the drift
+// window, concurrent or not, is rejected.
+async fn accept_totp_code(
+    user: &entity::users::Model,
+    secret: &str,
+    code: &str,
+    state: &web::Data<AppState>,
+) -> Result<bool, error::Error> {
+    let step = match totp::verify_code(secret, code, user.totp_last_step) {
+        Some(step) => step,
+        None => return Ok(false),
+    };
+
+    claim_totp_step(user.id, step, state)
+        .await
+        .map_err(error::Error::from_db_err)
+}
+
+async fn get_organization_user(
+    auth: &AuthContext,
+    user_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<entity::users::Model, error::Error> {
+    let user = get_user_by_id(user_id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let member = get_member_by_id(user.member_id, auth.scope(), state).await;
+
+    if member.is_err() {
+        return Err(error::new_error(1004, "User Not Found", 404));
+    }
+
+    Ok(user)
+}
+
+async fn issue_tokens(
+    user: entity::users::Model,
+    member: entity::members::Model,
+    session_id: uuid::Uuid,
+    scope: &str,
+    state: &web::Data<AppState>,
+) -> Result<LoginResponseModel, error::Error> {
+    let refresh_token = create_refresh_token(state);
+
+    // the session is tracked through its refresh token family even when the token
+    // itself is withheld from a restricted login
+    let saved = save_refresh_token(
+        SaveRefreshTokenDto {
+            user_id: user.id,
+            family_id: session_id,
+            token_hash: refresh_token.hash,
+            expires_at: refresh_token.expires_at,
+        },
+        state,
+    )
+    .await;
+
+    if let Err(e) = saved {
+        return Err(error::Error::from_db_err(e));
+    };
+
+    let token = create_jwt(
+        JwtDto {
+            user_id: user.id,
+            member_id: member.id,
+            organization_id: member.organization_id,
+            role: user.role,
+            session_id,
+            scope: scope.to_string(),
+        },
+        state,
+    )
+    .await;
+
+    let full_access = scope == SCOPE_FULL;
+
+    Ok(LoginResponseModel {
+        access_token: token.token,
+        token_type: "Bearer".to_string(),
+        expires_in: token.expires_in,
+        scope: scope.to_string(),
+        refresh_token: full_access.then_some(refresh_token.token),
+        refresh_expires_in: full_access.then_some(refresh_token.expires_in),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+
+    use super::{
+        claim_totp_step, get_user_by_id, save_api_key, save_reset_code, update_user_lock,
+        update_user_password, update_user_totp, SCOPE_MFA,
+    };
+    use crate::{
+        app::auth::models::model::SaveApiKeyDto,
+        app::members::{dto::dtos::save_member, models::model::AddMemberDto},
+        app::organization::{
+            dto::dtos::{save_identity_provider, save_organization_settings},
+            models::model::{SaveIdentityProviderDto, UpdateOrganizationSettingsDto},
+        },
+        app::users::controllers::controller::provision_account,
+        libs::{
+            api_key::generate_api_key,
+            jwt::hash_token,
+            pword::{legacy_hash, verify_password},
+            session::is_session_active,
+        },
+        middlewares::role::ADMIN,
+        utils::testing::{
+            bearer, random_phone, seed_tenant, sign_in, test_app, test_state, MockIdentityProvider,
+            Tenant, MOCK_IDP_CLIENT_ID, MOCK_IDP_CODE, MOCK_IDP_KEY_ID, MOCK_IDP_RSA_KEY,
+        },
+        AppState,
+    };
+
+    #[actix_web::test]
+    async fn admin_cannot_list_other_organization_user_sessions() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/auth/users/{}/sessions", org_b.user.id))
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_revoke_other_organization_user_sessions() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/auth/users/{}/sessions", org_b.user.id))
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let user = get_user_by_id(org_b.user.id, &state).await.unwrap();
+        let session_id = user.session_id.unwrap();
+
+        assert!(is_session_active(session_id, &state).await.unwrap());
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_unlock_other_organization_user() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/auth/users/{}/unlock", org_b.user.id))
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn forgot_password_hides_the_reset_limit() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let max_requests = state
+            .config
+            .get::<u64>("password_reset.max_requests")
+            .unwrap();
+
+        for _ in 0..max_requests {
+            save_reset_code(
+                org_a.user.id,
+                hash_token("000000"),
+                chrono::Utc::now() + chrono::Duration::minutes(5),
+                &state,
+            )
+            .await
+            .unwrap();
+        }
+
+        let forgot = |username: &str| {
+            test::TestRequest::post()
+                .uri("/api/v1/auth/forgot")
+                .set_json(serde_json::json!({ "username": username }))
+                .to_request()
+        };
+
+        let limited = test::call_service(&app, forgot(&org_a.user.contact)).await;
+        assert_eq!(limited.status(), StatusCode::OK);
+        let limited = test::read_body(limited).await;
+
+        let unknown = test::call_service(&app, forgot("0200000000")).await;
+        assert_eq!(unknown.status(), StatusCode::OK);
+        let unknown = test::read_body(unknown).await;
+
+        assert_eq!(limited, unknown);
+    }
+
+    #[actix_web::test]
+    async fn totp_step_is_claimed_once() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+
+        assert!(claim_totp_step(org_a.user.id, 100, &state).await.unwrap());
+        assert!(!claim_totp_step(org_a.user.id, 100, &state).await.unwrap());
+        assert!(!claim_totp_step(org_a.user.id, 99, &state).await.unwrap());
+        assert!(claim_totp_step(org_a.user.id, 101, &state).await.unwrap());
+    }
+
+    fn refresh_request(refresh_token: &str) -> actix_http::Request {
+        test::TestRequest::post()
+            .uri("/api/v1/auth/refresh")
+            .set_json(serde_json::json!({ "refresh_token": refresh_token }))
+            .to_request()
+    }
+
+    #[actix_web::test]
+    async fn refresh_rotates_the_refresh_token() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let resp = test::call_service(&app, refresh_request(&tenant.refresh_token)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let rotated = body["data"]["refresh_token"].as_str().unwrap();
+
+        assert_ne!(rotated, tenant.refresh_token);
+        assert!(!body["data"]["access_token"].as_str().unwrap().is_empty());
+
+        let resp = test::call_service(&app, refresh_request(rotated)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn reused_refresh_token_revokes_the_whole_session() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let session_id = get_user_by_id(tenant.user.id, &state)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+
+        let resp = test::call_service(&app, refresh_request(&tenant.refresh_token)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let rotated = body["data"]["refresh_token"].as_str().unwrap().to_string();
+
+        let replayed = test::call_service(&app, refresh_request(&tenant.refresh_token)).await;
+        assert_eq!(replayed.status(), StatusCode::UNAUTHORIZED);
+
+        // the token issued by the legitimate rotation is burnt along with the rest of the family
+        let resp = test::call_service(&app, refresh_request(&rotated)).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let user = get_user_by_id(tenant.user.id, &state).await.unwrap();
+
+        assert!(user.session_id.is_none());
+        assert!(!is_session_active(session_id, &state).await.unwrap());
+    }
+
+    #[actix_web::test]
+    async fn refused_refresh_does_not_spend_the_token() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        save_organization_settings(
+            tenant.organization.id,
+            UpdateOrganizationSettingsDto {
+                require_two_factor: true,
+            },
+            &state,
+        )
+        .await
+        .unwrap();
+
+        // a retry gets the same answer instead of being taken for a replay
+        for _ in 0..2 {
+            let resp = test::call_service(&app, refresh_request(&tenant.refresh_token)).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+            let body: serde_json::Value = test::read_body_json(resp).await;
+
+            assert_eq!(body["message"], "Two-Factor Enrollment Required");
+        }
+
+        save_organization_settings(
+            tenant.organization.id,
+            UpdateOrganizationSettingsDto {
+                require_two_factor: false,
+            },
+            &state,
+        )
+        .await
+        .unwrap();
+
+        let resp = test::call_service(&app, refresh_request(&tenant.refresh_token)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn logout_revokes_the_refresh_token() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/auth/logout")
+            .set_json(serde_json::json!({ "refresh_token": tenant.refresh_token }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = test::call_service(&app, refresh_request(&tenant.refresh_token)).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    fn login_history(token: &str) -> actix_http::Request {
+        test::TestRequest::get()
+            .uri("/api/v1/auth/sessions/history")
+            .insert_header(bearer(token))
+            .to_request()
+    }
+
+    #[actix_web::test]
+    async fn access_token_is_rejected_after_logout() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let session_id = get_user_by_id(tenant.user.id, &state)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+
+        let resp = test::call_service(&app, login_history(&tenant.token)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(state.sessions.get(&session_id), Some(true));
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/auth/logout")
+            .set_json(serde_json::json!({ "refresh_token": tenant.refresh_token }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // the cached session check must not outlive the logout
+        let err = test::try_call_service(&app, login_history(&tenant.token))
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[actix_web::test]
+    async fn access_token_is_rejected_after_admin_revokes_the_session() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let session_id = get_user_by_id(tenant.user.id, &state)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        let (admin_token, _) = sign_in(&tenant.user, &tenant.member, &state).await;
+
+        let resp = test::call_service(&app, login_history(&tenant.token)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(state.sessions.get(&session_id), Some(true));
+
+        let req = test::TestRequest::delete()
+            .uri(&format!(
+                "/api/v1/auth/users/{}/sessions/{}",
+                tenant.user.id, session_id
+            ))
+            .insert_header(bearer(&admin_token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let err = test::try_call_service(&app, login_history(&tenant.token))
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // only the revoked session is signed out
+        let resp = test::call_service(&app, login_history(&admin_token)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    // Creates a key through the admin endpoint and returns its id and secret.
+    async fn create_key(
+        app: &impl actix_web::dev::Service<
+            actix_http::Request,
+            Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+            Error = actix_web::Error,
+        >,
+        tenant: &Tenant,
+        scopes: &[&str],
+    ) -> (String, String) {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/auth/api-keys")
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({ "name": "Kiosk", "scopes": scopes }))
+            .to_request();
+        let resp = test::call_service(app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+
+        (
+            body["data"]["api_key"]["id"].as_str().unwrap().to_string(),
+            body["data"]["key"].as_str().unwrap().to_string(),
+        )
+    }
+
+    fn list_members(key: &str) -> actix_http::Request {
+        test::TestRequest::get()
+            .uri("/api/v1/members/get")
+            .insert_header(("Authorization", format!("ApiKey {}", key)))
+            .to_request()
+    }
+
+    // Status of a request the middleware may refuse before it reaches the handler.
+    async fn status_of(
+        app: &impl actix_web::dev::Service<
+            actix_http::Request,
+            Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+            Error = actix_web::Error,
+        >,
+        req: actix_http::Request,
+    ) -> StatusCode {
+        match test::try_call_service(app, req).await {
+            Ok(resp) => resp.status(),
+            Err(err) => err.as_response_error().status_code(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn api_key_is_limited_to_its_scopes() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let (_, key) = create_key(&app, &tenant, &["members:read"]).await;
+
+        assert_eq!(status_of(&app, list_members(&key)).await, StatusCode::OK);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/members/add")
+            .insert_header(("Authorization", format!("ApiKey {}", key)))
+            .set_json(serde_json::json!({}))
+            .to_request();
+        assert_eq!(status_of(&app, req).await, StatusCode::FORBIDDEN);
+
+        // routes that never opted in to API keys only take bearer tokens
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/members/{}", tenant.member.id))
+            .insert_header(("Authorization", format!("ApiKey {}", key)))
+            .to_request();
+        assert_eq!(status_of(&app, req).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn expired_api_key_is_rejected() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let generated = generate_api_key(&state);
+
+        // the endpoint refuses past expiries, so the key is stored as if it had lapsed
+        save_api_key(
+            SaveApiKeyDto {
+                organization_id: tenant.organization.id,
+                created_by: tenant.user.id,
+                name: "Kiosk".to_string(),
+                prefix: generated.prefix,
+                key_hash: generated.hash,
+                scopes: vec!["members:read".to_string()],
+                expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+            },
+            &state,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            status_of(&app, list_members(&generated.key)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[actix_web::test]
+    async fn revoked_api_key_is_rejected() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let (id, key) = create_key(&app, &tenant, &["members:read"]).await;
+
+        assert_eq!(status_of(&app, list_members(&key)).await, StatusCode::OK);
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/auth/api-keys/{}", id))
+            .insert_header(bearer(&tenant.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        assert_eq!(
+            status_of(&app, list_members(&key)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    const CLIENT_SECRET: &str = "mock-client-secret";
+
+    async fn configure_provider(
+        tenant: &Tenant,
+        idp: &MockIdentityProvider,
+        client_secret: Option<&str>,
+        state: &actix_web::web::Data<AppState>,
+    ) {
+        save_identity_provider(
+            tenant.organization.id,
+            SaveIdentityProviderDto {
+                issuer: idp.issuer.clone(),
+                client_id: MOCK_IDP_CLIENT_ID.to_string(),
+                client_secret: client_secret.map(String::from),
+                authorization_endpoint: format!("{}/authorize", idp.issuer),
+                token_endpoint: format!("{}/token", idp.issuer),
+                jwks_uri: Some(format!("{}/jwks", idp.issuer)),
+                redirect_uri: "http://localhost:3000/sso".to_string(),
+                scopes: "openid email".to_string(),
+                is_enabled: true,
+            },
+            state,
+        )
+        .await
+        .unwrap();
+    }
+
+    // Starts a sign-in and returns the state and nonce the provider would be sent.
+    async fn authorize(
+        app: &impl actix_web::dev::Service<
+            actix_http::Request,
+            Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+            Error = actix_web::Error,
+        >,
+        tenant: &Tenant,
+    ) -> (String, String) {
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/v1/auth/oidc/{}/authorize",
+                tenant.organization.id
+            ))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(app, req).await;
+
+        let url = reqwest::Url::parse(body["data"]["authorization_url"].as_str().unwrap()).unwrap();
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string())
+                .unwrap()
+        };
+
+        (param("state"), param("nonce"))
+    }
+
+    fn id_token_claims(
+        idp: &MockIdentityProvider,
+        tenant: &Tenant,
+        nonce: &str,
+        email_verified: Option<bool>,
+    ) -> serde_json::Value {
+        let now = chrono::Utc::now().timestamp();
+
+        let mut claims = serde_json::json!({
+            "iss": idp.issuer,
+            "aud": MOCK_IDP_CLIENT_ID,
+            "sub": format!("subject-{}", tenant.user.id),
+            "email": tenant.user.email,
+            "nonce": nonce,
+            "iat": now,
+            "exp": now + 300,
+        });
+
+        if let Some(verified) = email_verified {
+            claims["email_verified"] = serde_json::json!(verified);
+        }
+
+        claims
+    }
+
+    fn sign_hs256(claims: &serde_json::Value, secret: &str) -> String {
+        encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn sign_rs256(claims: &serde_json::Value) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(MOCK_IDP_KEY_ID.to_string());
+
+        encode(
+            &header,
+            claims,
+            &EncodingKey::from_rsa_pem(MOCK_IDP_RSA_KEY.as_bytes()).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn callback(login_state: &str, code: &str) -> actix_http::Request {
+        test::TestRequest::post()
+            .uri("/api/v1/auth/oidc/callback")
+            .set_json(serde_json::json!({ "state": login_state, "code": code }))
+            .to_request()
+    }
+
+    #[actix_web::test]
+    async fn oidc_code_exchange_links_verified_email() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        let (login_state, nonce) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, &nonce, Some(true));
+        idp.queue_id_token(sign_hs256(&claims, CLIENT_SECRET));
+
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let user = get_user_by_id(tenant.user.id, &state).await.unwrap();
+
+        assert_eq!(user.oidc_issuer.as_deref(), Some(idp.issuer.as_str()));
+        assert_eq!(user.oidc_subject, claims["sub"].as_str().map(String::from));
+    }
+
+    #[actix_web::test]
+    async fn oidc_verifies_tokens_against_provider_jwks() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, None, &state).await;
+        let app = test_app(&state).await;
+
+        let (login_state, nonce) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, &nonce, Some(true));
+        idp.queue_id_token(sign_rs256(&claims));
+
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn oidc_login_still_asks_for_the_totp_code() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        update_user_totp(
+            tenant.user.id,
+            Some("JBSWY3DPEHPK3PXP".to_string()),
+            true,
+            &state,
+        )
+        .await
+        .unwrap();
+
+        let (login_state, nonce) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, &nonce, Some(true));
+        idp.queue_id_token(sign_hs256(&claims, CLIENT_SECRET));
+
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+
+        assert_eq!(body["data"]["scope"], SCOPE_MFA);
+        assert!(body["data"]["refresh_token"].is_null());
+    }
+
+    #[actix_web::test]
+    async fn oidc_login_is_refused_while_the_account_is_locked() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        update_user_lock(
+            tenant.user.id,
+            Some(chrono::Utc::now() + chrono::Duration::minutes(15)),
+            &state,
+        )
+        .await
+        .unwrap();
+
+        let (login_state, nonce) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, &nonce, Some(true));
+        idp.queue_id_token(sign_hs256(&claims, CLIENT_SECRET));
+
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+        assert_eq!(resp.status(), StatusCode::LOCKED);
+    }
+
+    #[actix_web::test]
+    async fn oidc_rejects_tokens_with_a_bad_signature() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        let (login_state, nonce) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, &nonce, Some(true));
+        idp.queue_id_token(sign_hs256(&claims, "not-the-client-secret"));
+
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn oidc_rejects_rejected_code_and_unknown_state() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        let (login_state, nonce) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, &nonce, Some(true));
+        idp.queue_id_token(sign_hs256(&claims, CLIENT_SECRET));
+
+        let resp = test::call_service(&app, callback(&login_state, "forged-code")).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // the state was spent by the failed attempt
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = test::call_service(&app, callback("unknown-state", MOCK_IDP_CODE)).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn oidc_rejects_nonce_mismatch() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        let (login_state, _) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, "some-other-nonce", Some(true));
+        idp.queue_id_token(sign_hs256(&claims, CLIENT_SECRET));
+
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn oidc_never_links_an_email_shared_by_two_accounts() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        let member = save_member(
+            AddMemberDto {
+                first_name: "Shared".to_string(),
+                last_name: "Email".to_string(),
+                email: tenant.user.email.clone(),
+                phone: random_phone(),
+                organization_id: tenant.organization.id,
+                address: "Test Address".to_string(),
+                gender: "female".to_string(),
+                date_joined: None,
+                date_of_birth: None,
+                added_by: None,
+                status: "member".to_string(),
+            },
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap();
+        let account = provision_account(&member, ADMIN, state.pg_db.get_ref(), &state)
+            .await
+            .unwrap();
+
+        let (login_state, nonce) = authorize(&app, &tenant).await;
+        let claims = id_token_claims(&idp, &tenant, &nonce, Some(true));
+        idp.queue_id_token(sign_hs256(&claims, CLIENT_SECRET));
+
+        let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        for user_id in [tenant.user.id, account.user_id.parse().unwrap()] {
+            let user = get_user_by_id(user_id, &state).await.unwrap();
+
+            assert_eq!(user.oidc_subject, None);
+        }
+    }
+
+    #[actix_web::test]
+    async fn oidc_never_links_an_unverified_email() {
+        let state = test_state().await;
+        let idp = MockIdentityProvider::start().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        configure_provider(&tenant, &idp, Some(CLIENT_SECRET), &state).await;
+        let app = test_app(&state).await;
+
+        for email_verified in [None, Some(false)] {
+            let (login_state, nonce) = authorize(&app, &tenant).await;
+            let claims = id_token_claims(&idp, &tenant, &nonce, email_verified);
+            idp.queue_id_token(sign_hs256(&claims, CLIENT_SECRET));
+
+            let resp = test::call_service(&app, callback(&login_state, MOCK_IDP_CODE)).await;
+
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        }
+
+        let user = get_user_by_id(tenant.user.id, &state).await.unwrap();
+
+        assert_eq!(user.oidc_subject, None);
+    }
+
+    fn failed_login(username: &str, peer: &str) -> actix_http::Request {
+        test::TestRequest::post()
+            .uri("/api/v1/auth/login")
+            .peer_addr(peer.parse().unwrap())
+            .set_json(serde_json::json!({ "username": username, "password": "wrong-password" }))
+            .to_request()
+    }
+
+    #[actix_web::test]
+    async fn email_and_contact_share_the_account_window() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        // a fresh address for every attempt, so only the account window can throttle
+        for peer in ["10.1.0.1:4000", "10.1.0.2:4000", "10.1.0.3:4000"] {
+            let resp = test::call_service(&app, failed_login(&tenant.user.contact, peer)).await;
+
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let email = tenant.user.email.clone().unwrap();
+        let resp = test::call_service(&app, failed_login(&email, "10.1.0.4:4000")).await;
+
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[actix_web::test]
+    async fn legacy_password_is_upgraded_on_login() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        update_user_password(
+            tenant.user.id,
+            legacy_hash("Kente#Weaver7", &tenant.user.id),
+            &state,
+        )
+        .await
+        .unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/auth/login")
+            .set_json(serde_json::json!({
+                "username": tenant.user.contact,
+                "password": "Kente#Weaver7",
+            }))
+            .to_request();
+
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let user = get_user_by_id(tenant.user.id, &state).await.unwrap();
+        let check = verify_password("Kente#Weaver7", &user.id, &user.password, &state.config);
+
+        assert!(user.password.starts_with("$argon2id$"));
+        assert!(check.valid);
+        assert!(!check.needs_rehash);
+    }
+}
@@ -0,0 +1,952 @@
+use actix_web::web;
+use sea_orm::{
+    sea_query::{Expr, Func},
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait,
+    InsertResult, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+    TransactionTrait,
+};
+
+use crate::{
+    app::auth::models::model::{
+        SaveApiKeyDto, SaveLockoutEventDto, SaveLoginAttemptDto, SaveLoginStateDto,
+        SaveRefreshTokenDto,
+    },
+    AppState,
+};
+
+pub async fn get_user_by_username(
+    username: &String,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<entity::users::Model, DbErr> {
+    // emails are not unique across users, nor contacts across organizations, so an
+    // identifier matching more than one account is refused rather than checked against
+    // an arbitrary one
+    let mut query = entity::users::Entity::find().filter(
+        Condition::any()
+            .add(entity::users::Column::Contact.eq(username))
+            .add(entity::users::Column::Email.eq(username)),
+    );
+
+    if let Some(organization_id) = organization_id {
+        query = query.filter(entity::users::Column::OrganizationId.eq(organization_id));
+    }
+
+    let mut users = query.limit(2).all(state.pg_db.get_ref()).await?;
+
+    if users.len() > 1 {
+        return Err(DbErr::Custom("Username matches more than one user".into()));
+    }
+
+    users
+        .pop()
+        .ok_or_else(|| DbErr::RecordNotFound("User not found".into()))
+}
+
+pub async fn get_user_by_id(
+    id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<entity::users::Model, DbErr> {
+    let user = entity::users::Entity::find_by_id(id)
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("User not found".into()));
+
+    user
+}
+
+pub async fn update_user_session(
+    id: uuid::Uuid,
+    session_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    let exists = entity::users::Entity::find_by_id(id)
+        .one(state.pg_db.get_ref())
+        .await?;
+
+    let exists = match exists {
+        Some(user) => user,
+        None => return Err(DbErr::Custom("User not found".to_string())),
+    };
+
+    let mut model: entity::users::ActiveModel = exists.into();
+
+    model.session_id = ActiveValue::Set(session_id);
+    model.updated_at = ActiveValue::Set(chrono::Utc::now().into());
+
+    ActiveModelTrait::update(model, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+pub async fn update_user_password(
+    id: uuid::Uuid,
+    password: String,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    let exists = entity::users::Entity::find_by_id(id)
+        .one(state.pg_db.get_ref())
+        .await?;
+
+    let exists = match exists {
+        Some(user) => user,
+        None => return Err(DbErr::Custom("User not found".to_string())),
+    };
+
+    let mut model: entity::users::ActiveModel = exists.into();
+
+    model.password = ActiveValue::Set(password);
+    model.updated_at = ActiveValue::Set(chrono::Utc::now().into());
+
+    ActiveModelTrait::update(model, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+// The new password and its history entry are written together, so reuse checks
+// never see a password the account does not have, or miss one it does.
+pub async fn change_user_password(
+    id: uuid::Uuid,
+    password: String,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    let txn = state.pg_db.begin().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    let exists = entity::users::Entity::find_by_id(id).one(&txn).await?;
+
+    let exists = match exists {
+        Some(user) => user,
+        None => return Err(DbErr::Custom("User not found".to_string())),
+    };
+
+    let mut model: entity::users::ActiveModel = exists.into();
+
+    model.password = ActiveValue::Set(password.clone());
+    model.is_password_changed = ActiveValue::Set(true);
+    model.updated_at = ActiveValue::Set(chrono::Utc::now().into());
+
+    ActiveModelTrait::update(model, &txn).await.map_err(|err| {
+        eprintln!("Database update error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    let history = entity::password_history::ActiveModel {
+        user_id: Set(id),
+        password: Set(password),
+        ..Default::default()
+    };
+
+    entity::password_history::Entity::insert(history)
+        .exec(&txn)
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    txn.commit().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    Ok(())
+}
+
+pub async fn get_password_history(
+    user_id: uuid::Uuid,
+    limit: u64,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::password_history::Model>, DbErr> {
+    let history = entity::password_history::Entity::find()
+        .filter(entity::password_history::Column::UserId.eq(user_id))
+        .order_by_desc(entity::password_history::Column::CreatedAt)
+        .limit(limit)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(history)
+}
+
+pub async fn save_refresh_token(
+    data: SaveRefreshTokenDto,
+    state: &web::Data<AppState>,
+) -> Result<InsertResult<entity::refresh_tokens::ActiveModel>, DbErr> {
+    let token = entity::refresh_tokens::ActiveModel {
+        user_id: Set(data.user_id),
+        family_id: Set(data.family_id),
+        token_hash: Set(data.token_hash),
+        expires_at: Set(data.expires_at.into()),
+        ..Default::default()
+    };
+
+    let insertion = entity::refresh_tokens::Entity::insert(token)
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(insertion)
+}
+
+pub async fn get_refresh_token_by_hash(
+    hash: &String,
+    state: &web::Data<AppState>,
+) -> Result<entity::refresh_tokens::Model, DbErr> {
+    let token = entity::refresh_tokens::Entity::find()
+        .filter(entity::refresh_tokens::Column::TokenHash.eq(hash))
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Refresh token not found".into()));
+
+    token
+}
+
+// Returns false when the token was already used, so concurrent refreshes with the
+// same token are treated as reuse instead of both succeeding.
+pub async fn mark_refresh_token_used(
+    id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::refresh_tokens::Entity::update_many()
+        .col_expr(entity::refresh_tokens::Column::IsUsed, Expr::value(true))
+        .col_expr(
+            entity::refresh_tokens::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::refresh_tokens::Column::Id.eq(id))
+                .add(entity::refresh_tokens::Column::IsUsed.eq(false)),
+        )
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected == 1)
+}
+
+pub async fn revoke_token_family(
+    family_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::refresh_tokens::Entity::update_many()
+        .col_expr(entity::refresh_tokens::Column::IsRevoked, Expr::value(true))
+        .col_expr(
+            entity::refresh_tokens::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::refresh_tokens::Column::FamilyId.eq(family_id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    entity::users::Entity::update_many()
+        .col_expr(
+            entity::users::Column::SessionId,
+            Expr::value(Option::<uuid::Uuid>::None),
+        )
+        .filter(entity::users::Column::SessionId.eq(family_id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    state.sessions.invalidate(&family_id);
+
+    Ok(())
+}
+
+pub async fn get_active_sessions(
+    user_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::refresh_tokens::Model>, DbErr> {
+    let sessions = entity::refresh_tokens::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::refresh_tokens::Column::UserId.eq(user_id))
+                .add(entity::refresh_tokens::Column::IsUsed.eq(false))
+                .add(entity::refresh_tokens::Column::IsRevoked.eq(false))
+                .add(entity::refresh_tokens::Column::ExpiresAt.gt(chrono::Utc::now())),
+        )
+        .order_by_desc(entity::refresh_tokens::Column::CreatedAt)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(sessions)
+}
+
+pub async fn revoke_user_sessions(
+    user_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    let sessions = revoke_user_tokens(user_id, state.pg_db.get_ref()).await?;
+
+    for session_id in sessions {
+        state.sessions.invalidate(&session_id);
+    }
+
+    update_user_session(user_id, None, state).await
+}
+
+// Revokes every refresh token the user holds on `db` and returns the sessions they
+// belonged to, for the caller to drop from the session cache once the change is committed.
+pub async fn revoke_user_tokens(
+    user_id: uuid::Uuid,
+    db: &impl ConnectionTrait,
+) -> Result<Vec<uuid::Uuid>, DbErr> {
+    let tokens = entity::refresh_tokens::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::refresh_tokens::Column::UserId.eq(user_id))
+                .add(entity::refresh_tokens::Column::IsRevoked.eq(false)),
+        )
+        .all(db)
+        .await?;
+
+    entity::refresh_tokens::Entity::update_many()
+        .col_expr(entity::refresh_tokens::Column::IsRevoked, Expr::value(true))
+        .col_expr(
+            entity::refresh_tokens::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::refresh_tokens::Column::UserId.eq(user_id))
+        .exec(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(tokens.into_iter().map(|token| token.family_id).collect())
+}
+
+pub async fn count_reset_codes_since(
+    user_id: uuid::Uuid,
+    since: chrono::DateTime<chrono::Utc>,
+    state: &web::Data<AppState>,
+) -> Result<u64, DbErr> {
+    entity::password_reset_codes::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::password_reset_codes::Column::UserId.eq(user_id))
+                .add(entity::password_reset_codes::Column::CreatedAt.gte(since)),
+        )
+        .count(state.pg_db.get_ref())
+        .await
+}
+
+// Issuing a new code retires any code still outstanding for the user.
+pub async fn save_reset_code(
+    user_id: uuid::Uuid,
+    code_hash: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    state: &web::Data<AppState>,
+) -> Result<InsertResult<entity::password_reset_codes::ActiveModel>, DbErr> {
+    entity::password_reset_codes::Entity::update_many()
+        .col_expr(
+            entity::password_reset_codes::Column::IsUsed,
+            Expr::value(true),
+        )
+        .col_expr(
+            entity::password_reset_codes::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::password_reset_codes::Column::UserId.eq(user_id))
+                .add(entity::password_reset_codes::Column::IsUsed.eq(false)),
+        )
+        .exec(state.pg_db.get_ref())
+        .await?;
+
+    let code = entity::password_reset_codes::ActiveModel {
+        user_id: Set(user_id),
+        code_hash: Set(code_hash),
+        expires_at: Set(expires_at.into()),
+        ..Default::default()
+    };
+
+    let insertion = entity::password_reset_codes::Entity::insert(code)
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(insertion)
+}
+
+pub async fn get_active_reset_code(
+    user_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<entity::password_reset_codes::Model, DbErr> {
+    let code = entity::password_reset_codes::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::password_reset_codes::Column::UserId.eq(user_id))
+                .add(entity::password_reset_codes::Column::IsUsed.eq(false))
+                .add(entity::password_reset_codes::Column::ExpiresAt.gt(chrono::Utc::now())),
+        )
+        .order_by_desc(entity::password_reset_codes::Column::CreatedAt)
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Reset code not found".into()));
+
+    code
+}
+
+pub async fn update_reset_code(
+    id: uuid::Uuid,
+    attempts: i32,
+    is_used: bool,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::password_reset_codes::Entity::update_many()
+        .col_expr(
+            entity::password_reset_codes::Column::Attempts,
+            Expr::value(attempts),
+        )
+        .col_expr(
+            entity::password_reset_codes::Column::IsUsed,
+            Expr::value(is_used),
+        )
+        .col_expr(
+            entity::password_reset_codes::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::password_reset_codes::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+pub async fn update_user_totp(
+    id: uuid::Uuid,
+    secret: Option<String>,
+    is_enabled: bool,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::users::Entity::update_many()
+        .col_expr(entity::users::Column::TotpSecret, Expr::value(secret))
+        .col_expr(
+            entity::users::Column::IsTotpEnabled,
+            Expr::value(is_enabled),
+        )
+        .col_expr(
+            entity::users::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::users::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+pub async fn replace_recovery_codes(
+    user_id: uuid::Uuid,
+    code_hashes: Vec<String>,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::recovery_codes::Entity::delete_many()
+        .filter(entity::recovery_codes::Column::UserId.eq(user_id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database delete error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    if code_hashes.is_empty() {
+        return Ok(());
+    }
+
+    let codes = code_hashes
+        .into_iter()
+        .map(|code_hash| entity::recovery_codes::ActiveModel {
+            user_id: Set(user_id),
+            code_hash: Set(code_hash),
+            ..Default::default()
+        });
+
+    entity::recovery_codes::Entity::insert_many(codes)
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+// Conditional on is_used so a recovery code can only ever be redeemed once.
+pub async fn use_recovery_code(
+    user_id: uuid::Uuid,
+    code_hash: String,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::recovery_codes::Entity::update_many()
+        .col_expr(entity::recovery_codes::Column::IsUsed, Expr::value(true))
+        .col_expr(
+            entity::recovery_codes::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::recovery_codes::Column::UserId.eq(user_id))
+                .add(entity::recovery_codes::Column::CodeHash.eq(code_hash))
+                .add(entity::recovery_codes::Column::IsUsed.eq(false)),
+        )
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+// Conditional on the stored step so each TOTP step is accepted at most once, even
+// when the same code is submitted concurrently.
+pub async fn claim_totp_step(
+    user_id: uuid::Uuid,
+    step: i64,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::users::Entity::update_many()
+        .col_expr(entity::users::Column::TotpLastStep, Expr::value(step))
+        .filter(
+            Condition::all()
+                .add(entity::users::Column::Id.eq(user_id))
+                .add(
+                    Condition::any()
+                        .add(entity::users::Column::TotpLastStep.is_null())
+                        .add(entity::users::Column::TotpLastStep.lt(step)),
+                ),
+        )
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+// Incremented in the database so concurrent failures against one account all count.
+pub async fn increment_failed_logins(
+    id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<i32, DbErr> {
+    entity::users::Entity::update_many()
+        .col_expr(
+            entity::users::Column::FailedLoginAttempts,
+            Expr::col(entity::users::Column::FailedLoginAttempts).add(1),
+        )
+        .filter(entity::users::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    let user = get_user_by_id(id, state).await?;
+
+    Ok(user.failed_login_attempts)
+}
+
+pub async fn update_user_lock(
+    id: uuid::Uuid,
+    locked_until: Option<chrono::DateTime<chrono::Utc>>,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::users::Entity::update_many()
+        .col_expr(entity::users::Column::FailedLoginAttempts, Expr::value(0))
+        .col_expr(
+            entity::users::Column::LockedUntil,
+            Expr::value(locked_until),
+        )
+        .col_expr(
+            entity::users::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::users::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+pub async fn save_lockout_event(
+    data: SaveLockoutEventDto,
+    state: &web::Data<AppState>,
+) -> Result<InsertResult<entity::lockout_events::ActiveModel>, DbErr> {
+    let event = entity::lockout_events::ActiveModel {
+        user_id: Set(data.user_id),
+        organization_id: Set(data.organization_id),
+        ip_address: Set(data.ip_address),
+        user_agent: Set(data.user_agent),
+        locked_until: Set(data.locked_until.into()),
+        ..Default::default()
+    };
+
+    let insertion = entity::lockout_events::Entity::insert(event)
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(insertion)
+}
+
+pub async fn get_lockout_events(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::lockout_events::Model>, DbErr> {
+    let events = entity::lockout_events::Entity::find()
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::lockout_events::Column::OrganizationId.eq(org))
+        })
+        .order_by_desc(entity::lockout_events::Column::CreatedAt)
+        .limit(100)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(events)
+}
+
+pub async fn save_login_attempt(
+    data: SaveLoginAttemptDto,
+    state: &web::Data<AppState>,
+) -> Result<InsertResult<entity::login_history::ActiveModel>, DbErr> {
+    let attempt = entity::login_history::ActiveModel {
+        user_id: Set(data.user_id),
+        ip_address: Set(data.ip_address),
+        user_agent: Set(data.user_agent),
+        is_successful: Set(data.failure_reason.is_none()),
+        failure_reason: Set(data.failure_reason),
+        ..Default::default()
+    };
+
+    let insertion = entity::login_history::Entity::insert(attempt)
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(insertion)
+}
+
+pub async fn count_successful_logins(
+    user_id: uuid::Uuid,
+    user_agent: Option<String>,
+    state: &web::Data<AppState>,
+) -> Result<u64, DbErr> {
+    let count = entity::login_history::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::login_history::Column::UserId.eq(user_id))
+                .add(entity::login_history::Column::IsSuccessful.eq(true)),
+        )
+        .apply_if(user_agent, |query, agent| {
+            query.filter(entity::login_history::Column::UserAgent.eq(agent))
+        })
+        .count(state.pg_db.get_ref())
+        .await?;
+
+    Ok(count)
+}
+
+pub async fn get_login_history(
+    user_id: uuid::Uuid,
+    limit: u64,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::login_history::Model>, DbErr> {
+    let history = entity::login_history::Entity::find()
+        .filter(entity::login_history::Column::UserId.eq(user_id))
+        .order_by_desc(entity::login_history::Column::CreatedAt)
+        .limit(limit)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(history)
+}
+
+pub async fn save_api_key(
+    data: SaveApiKeyDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::api_keys::Model, DbErr> {
+    let api_key = entity::api_keys::ActiveModel {
+        organization_id: Set(data.organization_id),
+        created_by: Set(Some(data.created_by)),
+        name: Set(data.name),
+        prefix: Set(data.prefix),
+        key_hash: Set(data.key_hash),
+        scopes: Set(data.scopes.join(" ")),
+        expires_at: Set(data.expires_at.map(|at| at.into())),
+        ..Default::default()
+    };
+
+    let saved = ActiveModelTrait::insert(api_key, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(saved)
+}
+
+pub async fn get_api_keys(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::api_keys::Model>, DbErr> {
+    let api_keys = entity::api_keys::Entity::find()
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::api_keys::Column::OrganizationId.eq(org))
+        })
+        .order_by_desc(entity::api_keys::Column::CreatedAt)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(api_keys)
+}
+
+// Returns false when no live key with that id exists in the organization.
+pub async fn revoke_api_key(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::api_keys::Entity::update_many()
+        .col_expr(entity::api_keys::Column::IsRevoked, Expr::value(true))
+        .col_expr(
+            entity::api_keys::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::api_keys::Column::Id.eq(id))
+                .add(entity::api_keys::Column::IsRevoked.eq(false)),
+        )
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::api_keys::Column::OrganizationId.eq(org))
+        })
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+pub async fn save_login_state(
+    data: SaveLoginStateDto,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    let login_state = entity::oidc_login_states::ActiveModel {
+        organization_id: Set(data.organization_id),
+        state_hash: Set(data.state_hash),
+        code_verifier: Set(data.code_verifier),
+        nonce: Set(data.nonce),
+        expires_at: Set(data.expires_at.into()),
+        ..Default::default()
+    };
+
+    entity::oidc_login_states::Entity::insert(login_state)
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+// Marks the state used before handing it back, so a callback can only be replayed once.
+pub async fn take_login_state(
+    state_hash: String,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::oidc_login_states::Model>, DbErr> {
+    let result = entity::oidc_login_states::Entity::update_many()
+        .col_expr(entity::oidc_login_states::Column::IsUsed, Expr::value(true))
+        .col_expr(
+            entity::oidc_login_states::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::oidc_login_states::Column::StateHash.eq(state_hash.clone()))
+                .add(entity::oidc_login_states::Column::IsUsed.eq(false))
+                .add(entity::oidc_login_states::Column::ExpiresAt.gt(chrono::Utc::now())),
+        )
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    if result.rows_affected == 0 {
+        return Ok(None);
+    }
+
+    let login_state = entity::oidc_login_states::Entity::find()
+        .filter(entity::oidc_login_states::Column::StateHash.eq(state_hash))
+        .one(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(login_state)
+}
+
+pub async fn get_user_by_oidc_subject(
+    issuer: &str,
+    subject: &str,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::users::Model>, DbErr> {
+    let user = entity::users::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::users::Column::OidcIssuer.eq(issuer))
+                .add(entity::users::Column::OidcSubject.eq(subject)),
+        )
+        .one(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(user)
+}
+
+pub async fn get_user_by_email_in_organization(
+    email: &str,
+    organization_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::users::Model>, DbErr> {
+    // emails are not unique, and linking is permanent, so an email shared by more than
+    // one account links none of them
+    let mut users = entity::users::Entity::find()
+        .inner_join(entity::members::Entity)
+        .filter(
+            Condition::all()
+                .add(
+                    Expr::expr(Func::lower(Expr::col((
+                        entity::users::Entity,
+                        entity::users::Column::Email,
+                    ))))
+                    .eq(email.to_lowercase()),
+                )
+                .add(entity::members::Column::OrganizationId.eq(organization_id)),
+        )
+        .limit(2)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    if users.len() > 1 {
+        return Ok(None);
+    }
+
+    Ok(users.pop())
+}
+
+pub async fn link_oidc_subject(
+    id: uuid::Uuid,
+    issuer: &str,
+    subject: &str,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::users::Entity::update_many()
+        .col_expr(entity::users::Column::OidcIssuer, Expr::value(issuer))
+        .col_expr(entity::users::Column::OidcSubject, Expr::value(subject))
+        .col_expr(
+            entity::users::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::users::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginModel {
+    pub username: String,
+    pub password: String,
+    // only needed when the username has accounts in more than one organization
+    pub organization_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginResponseModel {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_expires_in: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangePasswordModel {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshTokenModel {
+    pub refresh_token: String,
+}
+
+pub struct SaveRefreshTokenDto {
+    pub user_id: uuid::Uuid,
+    pub family_id: uuid::Uuid,
+    pub token_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionResponseModel {
+    pub session_id: String,
+    pub last_active_at: String,
+    pub expires_at: String,
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForgotPasswordModel {
+    pub username: String,
+    pub organization_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetPasswordModel {
+    pub username: String,
+    pub organization_id: Option<String>,
+    pub code: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorEnrollmentModel {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorCodeModel {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyTwoFactorModel {
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorActivatedModel {
+    pub recovery_codes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<LoginResponseModel>,
+}
+
+pub struct SaveLockoutEventDto {
+    pub user_id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub locked_until: chrono::DateTime<chrono::Utc>,
+}
+
+pub struct SaveLoginAttemptDto {
+    pub user_id: uuid::Uuid,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginHistoryQueryModel {
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateApiKeyModel {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub struct SaveApiKeyDto {
+    pub organization_id: uuid::Uuid,
+    pub created_by: uuid::Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyResponseModel {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub is_revoked: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedApiKeyModel {
+    pub key: String,
+    pub api_key: ApiKeyResponseModel,
+}
+
+pub struct SaveLoginStateDto {
+    pub organization_id: uuid::Uuid,
+    pub state_hash: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcAuthorizeResponseModel {
+    pub authorization_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcCallbackModel {
+    pub state: String,
+    pub code: String,
+}
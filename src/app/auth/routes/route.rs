@@ -0,0 +1,120 @@
+use actix_web::web;
+
+use crate::{
+    app::auth::controllers::controller::{
+        activate_two_factor, change_password, create_api_key, delete_api_key, disable_two_factor,
+        enroll_two_factor, forgot_password, list_api_keys, list_lockouts, list_sessions, login,
+        login_history, logout, oidc_authorize, oidc_callback, refresh, reset_password,
+        revoke_all_sessions, revoke_session, unlock_user, verify_two_factor,
+    },
+    libs::jwt::{SCOPE_FULL, SCOPE_MFA, SCOPE_MFA_ENROLL, SCOPE_PASSWORD_CHANGE},
+    middlewares::{
+        auth::{JwtAuthMiddleware, ScopedJwtAuth},
+        role::{RequireRole, ADMIN, SUPER_ADMIN},
+    },
+    AppState,
+};
+
+pub fn all_routes(cfg: &mut web::ServiceConfig, _state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/api/v1/auth")
+            .route("/login", web::post().to(login))
+            .route("/refresh", web::post().to(refresh))
+            .route("/logout", web::post().to(logout))
+            .route("/forgot", web::post().to(forgot_password))
+            .route("/reset", web::post().to(reset_password))
+            .route(
+                "/oidc/{organization_id}/authorize",
+                web::get().to(oidc_authorize),
+            )
+            .route("/oidc/callback", web::post().to(oidc_callback))
+            .route(
+                "/change-password",
+                web::post()
+                    .to(change_password)
+                    .wrap(ScopedJwtAuth(&[SCOPE_FULL, SCOPE_PASSWORD_CHANGE])),
+            )
+            .route(
+                "/2fa/enroll",
+                web::post()
+                    .to(enroll_two_factor)
+                    .wrap(ScopedJwtAuth(&[SCOPE_FULL, SCOPE_MFA_ENROLL])),
+            )
+            .route(
+                "/2fa/activate",
+                web::post()
+                    .to(activate_two_factor)
+                    .wrap(ScopedJwtAuth(&[SCOPE_FULL, SCOPE_MFA_ENROLL])),
+            )
+            .route(
+                "/2fa/verify",
+                web::post()
+                    .to(verify_two_factor)
+                    .wrap(ScopedJwtAuth(&[SCOPE_MFA])),
+            )
+            .route(
+                "/2fa/disable",
+                web::post().to(disable_two_factor).wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/sessions/history",
+                web::get().to(login_history).wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/api-keys",
+                web::post()
+                    .to(create_api_key)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/api-keys",
+                web::get()
+                    .to(list_api_keys)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/api-keys/{id}",
+                web::delete()
+                    .to(delete_api_key)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/lockouts",
+                web::get()
+                    .to(list_lockouts)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/users/{id}/unlock",
+                web::post()
+                    .to(unlock_user)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/users/{id}/sessions",
+                web::get()
+                    .to(list_sessions)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/users/{id}/sessions",
+                web::delete()
+                    .to(revoke_all_sessions)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/users/{id}/sessions/{session_id}",
+                web::delete()
+                    .to(revoke_session)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
+    );
+}
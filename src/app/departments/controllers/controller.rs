@@ -0,0 +1,602 @@
+use std::collections::HashMap;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use sea_orm::{SqlErr, TransactionTrait};
+use serde_json::json;
+
+use crate::{
+    app::departments::{
+        dto::dtos::{
+            add_department_members, delete_department, department_name_taken, get_department_by_id,
+            get_department_member_counts, get_department_members, get_department_tree,
+            get_departments, get_sub_departments, lock_organization_departments,
+            remove_department_member, save_department, update_department,
+        },
+        models::model::{
+            AddDepartmentMembersModel, AddDepartmentModel, DepartmentDetailModel,
+            DepartmentMemberModel, DepartmentResponseModel, SaveDepartmentDto, UpdateDepartmentDto,
+            UpdateDepartmentModel,
+        },
+    },
+    libs::{error, validator},
+    middlewares::auth::AuthContext,
+    utils::models::HttpClientResponse,
+    AppState,
+};
+
+pub async fn add_department(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<AddDepartmentModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let name = validator::required_str(payload.name.trim(), "Name")?;
+    let parent_id = payload
+        .parent_id
+        .as_deref()
+        .map(|id| validator::uuid(id, "Parent ID"))
+        .transpose()?;
+
+    let organization_id = auth.organization_id;
+
+    if let Some(parent_id) = parent_id {
+        get_department_by_id(parent_id, Some(organization_id), &state)
+            .await
+            .map_err(|_| error::new_error(1004, "Parent Department Not Found", 404))?;
+    }
+
+    if department_name_taken(organization_id, &name, None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        return Err(error::new_error(
+            1006,
+            &format!("Department {} Exists", name),
+            409,
+        ));
+    }
+
+    let department = save_department(
+        SaveDepartmentDto {
+            organization_id,
+            parent_id,
+            name,
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Department Added Successfully".to_string(),
+        data: json!(department_detail(department, &state).await?),
+    }))
+}
+
+// Flat and sorted by name; parent_id is enough for clients to build the tree.
+pub async fn list_departments(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let departments = get_departments(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let counts: HashMap<uuid::Uuid, i64> = get_department_member_counts(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .into_iter()
+        .collect();
+
+    let departments: Vec<DepartmentResponseModel> = departments
+        .into_iter()
+        .map(|department| department_response(department, &counts))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Departments Retrieved Successfully".to_string(),
+        data: json!(departments),
+    }))
+}
+
+pub async fn get_department(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Department ID")?;
+
+    let department = get_department_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Department Not Found", 404))?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Department Retrieved Successfully".to_string(),
+        data: json!(department_detail(department, &state).await?),
+    }))
+}
+
+// A department can be renamed or moved under another one, but never under itself or
+// one of its own sub-departments.
+pub async fn patch_department(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<UpdateDepartmentModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Department ID")?;
+
+    let data = UpdateDepartmentDto {
+        name: payload
+            .name
+            .as_deref()
+            .map(|name| validator::required_str(name.trim(), "Name"))
+            .transpose()?,
+        parent_id: match payload.parent_id.as_deref() {
+            Some("") => Some(None),
+            Some(parent_id) => Some(Some(validator::uuid(parent_id, "Parent ID")?)),
+            None => None,
+        },
+    };
+
+    let department = get_department_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Department Not Found", 404))?;
+
+    if let Some(name) = &data.name {
+        if department_name_taken(
+            department.organization_id,
+            name,
+            Some(department.id),
+            &state,
+        )
+        .await
+        .map_err(error::Error::from_db_err)?
+        {
+            return Err(error::new_error(
+                1006,
+                &format!("Department {} Exists", name),
+                409,
+            ));
+        }
+    }
+
+    let txn = state
+        .pg_db
+        .begin()
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if let Some(Some(parent_id)) = data.parent_id {
+        get_department_by_id(parent_id, Some(department.organization_id), &state)
+            .await
+            .map_err(|_| error::new_error(1004, "Parent Department Not Found", 404))?;
+
+        lock_organization_departments(department.organization_id, &txn)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        let tree = get_department_tree(department.id, &txn)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        if tree.contains(&parent_id) {
+            return Err(error::new_error(
+                1002,
+                "A Department Cannot Be Moved Under Itself",
+                422,
+            ));
+        }
+    }
+
+    let department = update_department(id, auth.scope(), data, &txn)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    txn.commit().await.map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Department Updated Successfully".to_string(),
+        data: json!(department_detail(department, &state).await?),
+    }))
+}
+
+// Sub-departments have to be moved or removed first, so deleting a department never
+// takes others with it. The foreign key refuses the delete too, which covers one added
+// after the check below.
+pub async fn remove_department(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Department ID")?;
+
+    let department = get_department_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Department Not Found", 404))?;
+
+    if !get_sub_departments(department.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .is_empty()
+    {
+        return Err(error::new_error(
+            1006,
+            "Department Has Sub-Departments",
+            409,
+        ));
+    }
+
+    if !delete_department(department.id, auth.scope(), &state)
+        .await
+        .map_err(|err| match err.sql_err() {
+            Some(SqlErr::ForeignKeyConstraintViolation(_)) => {
+                error::new_error(1006, "Department Has Sub-Departments", 409)
+            }
+            _ => error::Error::from_db_err(err),
+        })?
+    {
+        return Err(error::new_error(1004, "Department Not Found", 404));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Department Deleted Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+// Members can serve in any number of departments, so adding never removes them from
+// another one.
+pub async fn add_members(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<AddDepartmentMembersModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Department ID")?;
+    let mut member_ids = payload
+        .member_ids
+        .iter()
+        .map(|id| validator::uuid(id, "Member ID"))
+        .collect::<Result<Vec<uuid::Uuid>, error::Error>>()?;
+    member_ids.sort_unstable();
+    member_ids.dedup();
+
+    if member_ids.is_empty() {
+        return Err(error::new_error(1002, "Member IDs are required", 422));
+    }
+
+    let department = get_department_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Department Not Found", 404))?;
+
+    if add_department_members(&department, member_ids, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .is_none()
+    {
+        return Err(error::new_error(1004, "Member Not Found", 404));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Department Members Added Successfully".to_string(),
+        data: json!(department_detail(department, &state).await?),
+    }))
+}
+
+pub async fn remove_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let (id, member_id) = path.into_inner();
+    let id = validator::uuid(&id, "Department ID")?;
+    let member_id = validator::uuid(&member_id, "Member ID")?;
+
+    let department = get_department_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Department Not Found", 404))?;
+
+    if !remove_department_member(department.id, member_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        return Err(error::new_error(
+            1004,
+            "Member Is Not in This Department",
+            404,
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Department Member Removed Successfully".to_string(),
+        data: json!(department_detail(department, &state).await?),
+    }))
+}
+
+async fn department_detail(
+    department: entity::departments::Model,
+    state: &web::Data<AppState>,
+) -> Result<DepartmentDetailModel, error::Error> {
+    let sub_departments = get_sub_departments(department.id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let counts: HashMap<uuid::Uuid, i64> =
+        get_department_member_counts(Some(department.organization_id), state)
+            .await
+            .map_err(error::Error::from_db_err)?
+            .into_iter()
+            .collect();
+
+    let members = get_department_members(department.id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(DepartmentDetailModel {
+        id: department.id.to_string(),
+        parent_id: department.parent_id.map(|id| id.to_string()),
+        name: department.name,
+        sub_departments: sub_departments
+            .into_iter()
+            .map(|department| department_response(department, &counts))
+            .collect(),
+        members: members
+            .into_iter()
+            .map(|member| DepartmentMemberModel {
+                id: member.id.to_string(),
+                first_name: member.first_name,
+                last_name: member.last_name,
+                status: member.status,
+            })
+            .collect(),
+    })
+}
+
+fn department_response(
+    department: entity::departments::Model,
+    counts: &HashMap<uuid::Uuid, i64>,
+) -> DepartmentResponseModel {
+    DepartmentResponseModel {
+        id: department.id.to_string(),
+        parent_id: department.parent_id.map(|id| id.to_string()),
+        member_count: counts.get(&department.id).copied().unwrap_or(0),
+        name: department.name,
+        created_at: department.created_at.to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test};
+
+    use crate::{
+        app::departments::{
+            dto::dtos::{delete_department, save_department},
+            models::model::SaveDepartmentDto,
+        },
+        middlewares::role::ADMIN,
+        utils::testing::{bearer, seed_tenant, test_app, test_state, Tenant},
+    };
+
+    async fn seed_department(
+        tenant: &Tenant,
+        parent_id: Option<uuid::Uuid>,
+        name: &str,
+        state: &actix_web::web::Data<crate::AppState>,
+    ) -> uuid::Uuid {
+        save_department(
+            SaveDepartmentDto {
+                organization_id: tenant.organization.id,
+                parent_id,
+                name: name.to_string(),
+            },
+            state,
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_list_other_organization_departments() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let department_id = seed_department(&org_b, None, "Choir", &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/departments/get")
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+        assert!(!body.contains(&department_id.to_string()));
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_read_update_or_delete_other_organization_department() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let department_id = seed_department(&org_b, None, "Choir", &state).await;
+        let app = test_app(&state).await;
+        let uri = format!("/api/v1/departments/{}", department_id);
+
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::patch()
+            .uri(&uri)
+            .insert_header(bearer(&org_a.token))
+            .set_json(serde_json::json!({ "name": "Hijacked" }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::delete()
+            .uri(&uri)
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(bearer(&org_b.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+        assert!(body.contains("Choir"));
+    }
+
+    #[actix_web::test]
+    async fn department_cannot_be_moved_under_its_descendant() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let music = seed_department(&tenant, None, "Music", &state).await;
+        let choir = seed_department(&tenant, Some(music), "Choir", &state).await;
+        let altos = seed_department(&tenant, Some(choir), "Altos", &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/v1/departments/{}", music))
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({ "parent_id": altos.to_string() }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/v1/departments/{}", altos))
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({ "parent_id": music.to_string() }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn department_with_sub_departments_is_not_deleted() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let music = seed_department(&tenant, None, "Music", &state).await;
+        let choir = seed_department(&tenant, Some(music), "Choir", &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/departments/{}", music))
+            .insert_header(bearer(&tenant.token))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        // the foreign key holds even when the sub-department check is skipped
+        let err = delete_department(music, Some(tenant.organization.id), &state)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.sql_err(),
+            Some(sea_orm::SqlErr::ForeignKeyConstraintViolation(_))
+        ));
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/departments/{}", choir))
+            .insert_header(bearer(&tenant.token))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn adding_members_outside_the_organization_is_refused() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let other = seed_tenant(ADMIN, &state).await;
+        let choir = seed_department(&tenant, None, "Choir", &state).await;
+        let app = test_app(&state).await;
+        let uri = format!("/api/v1/departments/{}/members", choir);
+        let own = tenant.member.id.to_string();
+
+        let req = test::TestRequest::post()
+            .uri(&uri)
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({
+                "member_ids": [own, other.member.id.to_string()]
+            }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/departments/{}", choir))
+            .insert_header(bearer(&tenant.token))
+            .to_request();
+        let body = String::from_utf8(
+            test::read_body(test::call_service(&app, req).await)
+                .await
+                .to_vec(),
+        )
+        .unwrap();
+
+        assert!(!body.contains(&own));
+
+        // the same member listed twice is still just one member
+        let req = test::TestRequest::post()
+            .uri(&uri)
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({ "member_ids": [own, own] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+        assert!(body.contains(&own));
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use actix_web::web;
+use sea_orm::{
+    sea_query::{Expr, Func, OnConflict},
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+};
+
+use crate::{
+    app::departments::models::model::{SaveDepartmentDto, UpdateDepartmentDto},
+    apply_update_wrap, AppState,
+};
+
+// The ids of a department and every department beneath it, for use as `IN (...)`. UNION
+// drops rows already visited, so a parent_id cycle ends the recursion instead of looping.
+pub const DEPARTMENT_TREE: &str = "WITH RECURSIVE tree AS (SELECT id FROM departments WHERE id = $1 UNION SELECT departments.id FROM departments JOIN tree ON departments.parent_id = tree.id) SELECT id FROM tree";
+
+pub async fn save_department(
+    data: SaveDepartmentDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::departments::Model, DbErr> {
+    let department = entity::departments::ActiveModel {
+        organization_id: Set(data.organization_id),
+        parent_id: Set(data.parent_id),
+        name: Set(data.name),
+        ..Default::default()
+    };
+
+    let saved = ActiveModelTrait::insert(department, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(saved)
+}
+
+pub async fn get_departments(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::departments::Model>, DbErr> {
+    let departments = entity::departments::Entity::find()
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::departments::Column::OrganizationId.eq(org))
+        })
+        .order_by_asc(entity::departments::Column::Name)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(departments)
+}
+
+pub async fn get_department_by_id(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<entity::departments::Model, DbErr> {
+    let department = entity::departments::Entity::find_by_id(id)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::departments::Column::OrganizationId.eq(org))
+        })
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Department not found".into()));
+
+    department
+}
+
+// Names are compared case-insensitively, as idx_departments_organization_name does.
+pub async fn department_name_taken(
+    organization_id: uuid::Uuid,
+    name: &str,
+    except: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let department = entity::departments::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::departments::Column::OrganizationId.eq(organization_id))
+                .add(
+                    Expr::expr(Func::lower(Expr::col(entity::departments::Column::Name)))
+                        .eq(name.to_lowercase()),
+                ),
+        )
+        .apply_if(except, |query, id| {
+            query.filter(entity::departments::Column::Id.ne(id))
+        })
+        .one(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(department.is_some())
+}
+
+// Locks every department of the organization until the transaction ends, so two moves
+// cannot each pass the subtree check and together commit a cycle.
+pub async fn lock_organization_departments(
+    organization_id: uuid::Uuid,
+    db: &impl ConnectionTrait,
+) -> Result<(), DbErr> {
+    entity::departments::Entity::find()
+        .filter(entity::departments::Column::OrganizationId.eq(organization_id))
+        .lock_exclusive()
+        .all(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+// The department itself and everything nested under it.
+pub async fn get_department_tree(
+    id: uuid::Uuid,
+    db: &impl ConnectionTrait,
+) -> Result<Vec<uuid::Uuid>, DbErr> {
+    let ids = entity::departments::Entity::find()
+        .select_only()
+        .column(entity::departments::Column::Id)
+        .filter(Expr::cust_with_values(
+            format!("departments.id IN ({})", DEPARTMENT_TREE),
+            [id],
+        ))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(ids)
+}
+
+pub async fn update_department(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    department: UpdateDepartmentDto,
+    db: &impl ConnectionTrait,
+) -> Result<entity::departments::Model, DbErr> {
+    let exists = entity::departments::Entity::find_by_id(id)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::departments::Column::OrganizationId.eq(org))
+        })
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Department not found".into()))?;
+
+    let mut model: entity::departments::ActiveModel = exists.into();
+
+    apply_update_wrap!(model, department,
+        name: name,
+        parent_id: parent_id
+    );
+
+    model.updated_at = ActiveValue::set(chrono::Utc::now().into());
+
+    let updated = ActiveModelTrait::update(model, db).await.map_err(|err| {
+        eprintln!("Database update error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    Ok(updated)
+}
+
+// Memberships go with the department through the member_departments foreign key.
+pub async fn delete_department(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::departments::Entity::delete_many()
+        .filter(entity::departments::Column::Id.eq(id))
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::departments::Column::OrganizationId.eq(org))
+        })
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database delete error: {}", err);
+            err
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+pub async fn get_sub_departments(
+    parent_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::departments::Model>, DbErr> {
+    let departments = entity::departments::Entity::find()
+        .filter(entity::departments::Column::ParentId.eq(parent_id))
+        .order_by_asc(entity::departments::Column::Name)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(departments)
+}
+
+// Direct members only; members of sub-departments are listed under those.
+pub async fn get_department_members(
+    department_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::members::Model>, DbErr> {
+    let members = entity::members::Entity::find()
+        .inner_join(entity::member_departments::Entity)
+        .filter(entity::member_departments::Column::DepartmentId.eq(department_id))
+        .order_by_asc(entity::members::Column::LastName)
+        .order_by_asc(entity::members::Column::FirstName)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(members)
+}
+
+pub async fn get_department_member_counts(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<(uuid::Uuid, i64)>, DbErr> {
+    let counts = entity::member_departments::Entity::find()
+        .select_only()
+        .column(entity::member_departments::Column::DepartmentId)
+        .column_as(
+            entity::member_departments::Column::Id.count(),
+            "member_count",
+        )
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::member_departments::Column::OrganizationId.eq(org))
+        })
+        .group_by(entity::member_departments::Column::DepartmentId)
+        .into_tuple()
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(counts)
+}
+
+// The departments each of the given members serves in, keyed by member. Exports pass
+// whole directories, so the ids are looked up in batches.
+pub async fn get_departments_by_member(
+    member_ids: Vec<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<HashMap<uuid::Uuid, Vec<entity::departments::Model>>, DbErr> {
+    let mut departments: HashMap<uuid::Uuid, Vec<entity::departments::Model>> = HashMap::new();
+
+    for chunk in member_ids.chunks(1000) {
+        let memberships = entity::member_departments::Entity::find()
+            .find_also_related(entity::departments::Entity)
+            .filter(entity::member_departments::Column::MemberId.is_in(chunk.to_vec()))
+            .order_by_asc(entity::departments::Column::Name)
+            .all(state.pg_db.get_ref())
+            .await
+            .map_err(|err| {
+                eprintln!("Database retrieval error: {}", err);
+                DbErr::Custom(err.to_string())
+            })?;
+
+        for (membership, department) in memberships {
+            if let Some(department) = department {
+                departments
+                    .entry(membership.member_id)
+                    .or_default()
+                    .push(department);
+            }
+        }
+    }
+
+    Ok(departments)
+}
+
+// Adds members of the organization to the department; those already in it are left
+// as they are. Returns how many were added, or None without adding any when one of the
+// ids isn't a member of the organization.
+pub async fn add_department_members(
+    department: &entity::departments::Model,
+    member_ids: Vec<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Option<u64>, DbErr> {
+    let requested = member_ids.len();
+    let members: Vec<uuid::Uuid> = entity::members::Entity::find()
+        .select_only()
+        .column(entity::members::Column::Id)
+        .filter(
+            Condition::all()
+                .add(entity::members::Column::Id.is_in(member_ids))
+                .add(entity::members::Column::OrganizationId.eq(department.organization_id)),
+        )
+        .into_tuple()
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    if members.len() != requested {
+        return Ok(None);
+    }
+
+    let memberships =
+        members
+            .into_iter()
+            .map(|member_id| entity::member_departments::ActiveModel {
+                organization_id: Set(department.organization_id),
+                member_id: Set(member_id),
+                department_id: Set(department.id),
+                ..Default::default()
+            });
+
+    let added = entity::member_departments::Entity::insert_many(memberships)
+        .on_conflict(
+            OnConflict::columns([
+                entity::member_departments::Column::MemberId,
+                entity::member_departments::Column::DepartmentId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(Some(added))
+}
+
+pub async fn remove_department_member(
+    department_id: uuid::Uuid,
+    member_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::member_departments::Entity::delete_many()
+        .filter(
+            Condition::all()
+                .add(entity::member_departments::Column::DepartmentId.eq(department_id))
+                .add(entity::member_departments::Column::MemberId.eq(member_id)),
+        )
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database delete error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
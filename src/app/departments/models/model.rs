@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddDepartmentModel {
+    pub name: String,
+    pub parent_id: Option<String>,
+}
+
+pub struct SaveDepartmentDto {
+    pub organization_id: uuid::Uuid,
+    pub parent_id: Option<uuid::Uuid>,
+    pub name: String,
+}
+
+// An empty parent_id moves the department to the top level.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateDepartmentModel {
+    pub name: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+pub struct UpdateDepartmentDto {
+    pub name: Option<String>,
+    pub parent_id: Option<Option<uuid::Uuid>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddDepartmentMembersModel {
+    pub member_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepartmentResponseModel {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub name: String,
+    pub member_count: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepartmentMemberModel {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepartmentDetailModel {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub name: String,
+    pub sub_departments: Vec<DepartmentResponseModel>,
+    pub members: Vec<DepartmentMemberModel>,
+}
+
+// How a department appears on a member.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberDepartmentModel {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub name: String,
+}
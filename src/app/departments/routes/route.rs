@@ -0,0 +1,68 @@
+use actix_web::web;
+
+use crate::{
+    app::departments::controllers::controller::{
+        add_department, add_members, get_department, list_departments, patch_department,
+        remove_department, remove_member,
+    },
+    middlewares::{
+        auth::JwtAuthMiddleware,
+        role::{RequireRole, ADMIN, SECRETARY, SUPER_ADMIN},
+    },
+    AppState,
+};
+
+pub fn all_routes(cfg: &mut web::ServiceConfig, _state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/api/v1/departments")
+            .route(
+                "/add",
+                web::post()
+                    .to(add_department)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/get",
+                web::get()
+                    .to(list_departments)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(get_department)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::patch()
+                    .to(patch_department)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::delete()
+                    .to(remove_department)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/members",
+                web::post()
+                    .to(add_members)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/members/{member_id}",
+                web::delete()
+                    .to(remove_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
+    );
+}
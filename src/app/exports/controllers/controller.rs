@@ -0,0 +1,577 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+
+use crate::{
+    app::{
+        departments::dto::dtos::get_departments_by_member,
+        exports::{
+            dto::dtos::{
+                complete_export_job, delete_export_job, fail_export_job,
+                fail_interrupted_export_jobs, get_expired_export_jobs, get_export_job,
+                get_export_jobs, save_export_job, start_export_job,
+            },
+            models::model::{
+                ExportJobResponseModel, ExportMembersModel, SaveExportJobDto, COMPLETED,
+                EXPORT_COLUMNS, EXPORT_FORMATS,
+            },
+        },
+        members::{
+            controllers::controller::validate_member_filter,
+            dto::dtos::{count_members, get_members_for_export},
+            models::model::MemberFilterDto,
+        },
+        organization::dto::dtos::find_organization_by_id,
+    },
+    libs::{
+        error,
+        export::{self, DirectoryEntry},
+        validator,
+    },
+    middlewares::auth::AuthContext,
+    utils::{
+        file_methods::read_file, models::HttpClientResponse, shared::get_images_by_organization,
+    },
+    AppState,
+};
+
+pub async fn export_members(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<ExportMembersModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let format = validator::one_of(&payload.format, &EXPORT_FORMATS, "Format")?;
+    let include_photos = payload.include_photos.unwrap_or(false);
+    let sync_limit = state.config.get::<u64>("export.sync_limit").unwrap();
+
+    let filter = MemberFilterDto {
+        department_id: payload
+            .department_id
+            .as_deref()
+            .map(|id| validator::uuid(id, "Department ID"))
+            .transpose()?,
+        member_type: payload.member_type.clone(),
+        gender: payload.gender.clone(),
+        status: payload.status.clone(),
+        joined_from: payload.joined_from,
+        joined_to: payload.joined_to,
+        born_from: payload.born_from,
+        born_to: payload.born_to,
+    };
+
+    validate_member_filter(&filter)?;
+
+    let organization_id = auth.organization_id;
+
+    let total = count_members(Some(organization_id), &filter, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    // small exports are answered straight away, larger ones become a job to poll
+    if total <= sync_limit {
+        let (data, _) =
+            render_members(organization_id, &format, &filter, include_photos, &state).await?;
+
+        return Ok(file_response(&format, data));
+    }
+
+    let job = save_export_job(
+        SaveExportJobDto {
+            organization_id,
+            requested_by: auth.user_id,
+            format,
+            filters: json!(filter),
+            include_photos,
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    tokio::spawn(run_export(job.id, state.clone()));
+
+    Ok(HttpResponse::Accepted().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Export Started Successfully".to_string(),
+        data: json!(export_job_response(job)),
+    }))
+}
+
+pub async fn list_exports(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let jobs = get_export_jobs(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let jobs: Vec<ExportJobResponseModel> = jobs.into_iter().map(export_job_response).collect();
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Exports Retrieved Successfully".to_string(),
+        data: json!(jobs),
+    }))
+}
+
+pub async fn get_export(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Export ID")?;
+
+    let job = get_export_job(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Export not found", 404))?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Export Retrieved Successfully".to_string(),
+        data: json!(export_job_response(job)),
+    }))
+}
+
+pub async fn download_export(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Export ID")?;
+
+    let job = get_export_job(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Export not found", 404))?;
+
+    let file_name = match job.file_name {
+        Some(file_name) if job.status == COMPLETED => file_name,
+        _ => return Err(error::new_error(1006, "Export is not ready", 409)),
+    };
+
+    let data = tokio::fs::read(export_path(&file_name, &job.format, &state))
+        .await
+        .map_err(|_| error::new_error(1004, "Export file not found", 404))?;
+
+    Ok(file_response(&job.format, data))
+}
+
+// Runs a job outside the request that created it. Failures are recorded on the job,
+// there is no one waiting to be told.
+async fn run_export(id: uuid::Uuid, state: web::Data<AppState>) {
+    match start_export_job(id, &state).await {
+        Ok(true) => {}
+        _ => return,
+    }
+
+    let job = match get_export_job(id, None, &state).await {
+        Ok(job) => job,
+        Err(_) => return,
+    };
+
+    let result = match serde_json::from_value::<MemberFilterDto>(job.filters.clone()) {
+        Ok(filter) => {
+            render_members(
+                job.organization_id,
+                &job.format,
+                &filter,
+                job.include_photos,
+                &state,
+            )
+            .await
+        }
+        Err(_) => Err(error::new_error(1002, "Export filters are invalid", 422)),
+    };
+
+    let outcome = match result {
+        Ok((data, rows)) => {
+            let file_name = format!("export-{}", job.id);
+
+            match save_export_file(&file_name, &job.format, &data, &state).await {
+                Ok(_) => complete_export_job(job.id, file_name, rows as i32, &state).await,
+                Err(e) => fail_export_job(job.id, e.to_string(), &state).await,
+            }
+        }
+        Err(e) => fail_export_job(job.id, e.message, &state).await,
+    };
+
+    if let Err(e) = outcome {
+        eprintln!("Export job {} could not be updated: {}", job.id, e);
+    }
+}
+
+// Called once at startup, before any new job can be queued.
+pub async fn recover_interrupted_exports(state: &web::Data<AppState>) {
+    match fail_interrupted_export_jobs(state).await {
+        Ok(0) => {}
+        Ok(count) => log::info!("Marked {} interrupted export jobs failed", count),
+        Err(e) => eprintln!("Interrupted export jobs could not be recovered: {}", e),
+    }
+}
+
+// Removes finished jobs and their files once they are older than the retention period.
+pub async fn sweep_exports(state: &web::Data<AppState>) {
+    let retention = state.config.get::<i64>("export.retention").unwrap();
+    let finished_before = chrono::Utc::now() - chrono::Duration::seconds(retention);
+
+    let jobs = match get_expired_export_jobs(finished_before, state).await {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            eprintln!("Expired export jobs could not be loaded: {}", e);
+            return;
+        }
+    };
+
+    for job in jobs {
+        if let Some(file_name) = &job.file_name {
+            match tokio::fs::remove_file(export_path(file_name, &job.format, state)).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    eprintln!("Export file {} could not be removed: {}", file_name, e);
+                    continue;
+                }
+            }
+        }
+
+        if let Err(e) = delete_export_job(job.id, state).await {
+            eprintln!("Export job {} could not be deleted: {}", job.id, e);
+        }
+    }
+}
+
+pub async fn run_export_sweeper(state: web::Data<AppState>) {
+    let every = state.config.get::<u64>("export.sweep_interval").unwrap();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(every));
+
+    loop {
+        interval.tick().await;
+        sweep_exports(&state).await;
+    }
+}
+
+// Exports live in their own directory, apart from uploaded media, so the sweep only
+// ever touches files it wrote.
+fn export_path(file_name: &str, format: &str, state: &web::Data<AppState>) -> PathBuf {
+    let directory = state.config.get::<String>("export.directory").unwrap();
+
+    Path::new(&directory).join(format!("{}.{}", file_name, format))
+}
+
+async fn save_export_file(
+    file_name: &str,
+    format: &str,
+    data: &[u8],
+    state: &web::Data<AppState>,
+) -> Result<(), std::io::Error> {
+    let path = export_path(file_name, format, state);
+
+    if let Some(directory) = path.parent() {
+        tokio::fs::create_dir_all(directory).await?;
+    }
+
+    tokio::fs::write(path, data).await
+}
+
+// Builds the file and returns it with the number of members in it. Rendering is moved
+// off the async workers since a large directory with photos takes a while.
+async fn render_members(
+    organization_id: uuid::Uuid,
+    format: &str,
+    filter: &MemberFilterDto,
+    include_photos: bool,
+    state: &web::Data<AppState>,
+) -> Result<(Vec<u8>, usize), error::Error> {
+    let members = get_members_for_export(organization_id, filter, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+    let count = members.len();
+
+    let mut departments: HashMap<uuid::Uuid, Vec<String>> =
+        get_departments_by_member(members.iter().map(|member| member.id).collect(), state)
+            .await
+            .map_err(error::Error::from_db_err)?
+            .into_iter()
+            .map(|(member_id, departments)| {
+                let names = departments
+                    .into_iter()
+                    .map(|department| department.name)
+                    .collect();
+
+                (member_id, names)
+            })
+            .collect();
+
+    let data = match format {
+        "pdf" => {
+            let title = match find_organization_by_id(organization_id, state).await {
+                Ok(Some(organization)) => format!("{} Member Directory", organization.name),
+                _ => "Member Directory".to_string(),
+            };
+
+            let mut photos = if include_photos {
+                member_photos(organization_id, &members, state).await?
+            } else {
+                HashMap::new()
+            };
+
+            let entries: Vec<DirectoryEntry> = members
+                .into_iter()
+                .map(|member| directory_entry(&mut photos, &mut departments, member))
+                .collect();
+
+            web::block(move || export::to_pdf(&title, &entries)).await
+        }
+        _ => {
+            let rows: Vec<Vec<String>> = members
+                .into_iter()
+                .map(|member| member_row(&mut departments, member))
+                .collect();
+            let format = format.to_string();
+
+            web::block(move || match format.as_str() {
+                "xlsx" => export::to_xlsx(&EXPORT_COLUMNS, &rows),
+                _ => export::to_csv(&EXPORT_COLUMNS, &rows),
+            })
+            .await
+        }
+    }
+    .map_err(|_| error::new_error(2001, "Export could not be generated", 500))??;
+
+    Ok((data, count))
+}
+
+// The current picture of each member, skipping any whose file has gone missing.
+async fn member_photos(
+    organization_id: uuid::Uuid,
+    members: &[entity::members::Model],
+    state: &web::Data<AppState>,
+) -> Result<HashMap<uuid::Uuid, Vec<u8>>, error::Error> {
+    let ids: HashSet<uuid::Uuid> = members.iter().map(|member| member.id).collect();
+    let images = get_images_by_organization(organization_id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let mut photos = HashMap::new();
+
+    for image in images {
+        if !ids.contains(&image.owner_id) || photos.contains_key(&image.owner_id) {
+            continue;
+        }
+
+        let (Some(file_name), Some(mime_type)) = (image.file_name, image.mime_type) else {
+            continue;
+        };
+
+        let extension = mime_type.split('/').nth(1).unwrap_or("");
+
+        if let Ok(data) = read_file(&file_name, extension).await {
+            photos.insert(image.owner_id, data);
+        }
+    }
+
+    Ok(photos)
+}
+
+fn directory_entry(
+    photos: &mut HashMap<uuid::Uuid, Vec<u8>>,
+    departments: &mut HashMap<uuid::Uuid, Vec<String>>,
+    member: entity::members::Model,
+) -> DirectoryEntry {
+    let name = match &member.alias {
+        Some(alias) => format!("{} {} ({})", member.first_name, member.last_name, alias),
+        None => format!("{} {}", member.first_name, member.last_name),
+    };
+
+    let mut details = vec![member.contact];
+
+    if let Some(email) = member.email {
+        details.push(email);
+    }
+
+    details.push(member.residential_address);
+
+    if let Some(departments) = departments.remove(&member.id) {
+        details.push(departments.join(", "));
+    }
+
+    DirectoryEntry {
+        name,
+        details,
+        photo: photos.remove(&member.id),
+    }
+}
+
+fn member_row(
+    departments: &mut HashMap<uuid::Uuid, Vec<String>>,
+    member: entity::members::Model,
+) -> Vec<String> {
+    vec![
+        member.first_name,
+        member.last_name,
+        member.alias.unwrap_or_default(),
+        member.gender,
+        member.contact,
+        member.email.unwrap_or_default(),
+        member.residential_address,
+        member.date_of_birth.to_string(),
+        member
+            .date_joined
+            .map(|date| date.to_string())
+            .unwrap_or_default(),
+        departments
+            .remove(&member.id)
+            .unwrap_or_default()
+            .join(", "),
+        member.member_type,
+        member.status,
+    ]
+}
+
+fn file_response(format: &str, data: Vec<u8>) -> HttpResponse {
+    let content_type = match format {
+        "pdf" => "application/pdf",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        _ => "text/csv",
+    };
+
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"members.{}\"", format),
+        ))
+        .body(data)
+}
+
+fn export_job_response(job: entity::export_jobs::Model) -> ExportJobResponseModel {
+    ExportJobResponseModel {
+        id: job.id.to_string(),
+        download_url: (job.status == COMPLETED)
+            .then(|| format!("/api/v1/exports/{}/download", job.id)),
+        format: job.format,
+        status: job.status,
+        row_count: job.row_count,
+        error: job.error,
+        created_at: job.created_at.to_rfc3339(),
+        completed_at: job.completed_at.map(|date| date.to_rfc3339()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+
+    use super::{export_path, recover_interrupted_exports, save_export_file, sweep_exports};
+    use crate::{
+        app::exports::{
+            dto::dtos::{complete_export_job, save_export_job, start_export_job},
+            models::model::{SaveExportJobDto, FAILED},
+        },
+        middlewares::role::ADMIN,
+        utils::testing::{seed_tenant, test_state, Tenant},
+        AppState,
+    };
+
+    async fn seed_job(
+        tenant: &Tenant,
+        state: &actix_web::web::Data<AppState>,
+    ) -> entity::export_jobs::Model {
+        save_export_job(
+            SaveExportJobDto {
+                organization_id: tenant.organization.id,
+                requested_by: tenant.user.id,
+                format: "csv".to_string(),
+                filters: serde_json::json!({}),
+                include_photos: false,
+            },
+            state,
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn find_job(
+        id: uuid::Uuid,
+        state: &actix_web::web::Data<AppState>,
+    ) -> Option<entity::export_jobs::Model> {
+        entity::export_jobs::Entity::find_by_id(id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn interrupted_jobs_are_failed_on_startup() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let pending = seed_job(&tenant, &state).await;
+        let running = seed_job(&tenant, &state).await;
+        start_export_job(running.id, &state).await.unwrap();
+
+        recover_interrupted_exports(&state).await;
+
+        for id in [pending.id, running.id] {
+            let job = find_job(id, &state).await.unwrap();
+
+            assert_eq!(job.status, FAILED);
+            assert!(job.completed_at.is_some());
+        }
+    }
+
+    #[actix_web::test]
+    async fn sweep_removes_expired_exports_and_their_files() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let retention = state.config.get::<i64>("export.retention").unwrap();
+
+        let mut jobs = Vec::new();
+
+        for age in [retention + 60, 0] {
+            let job = seed_job(&tenant, &state).await;
+            let file_name = format!("export-{}", job.id);
+
+            save_export_file(&file_name, "csv", b"First Name\n", &state)
+                .await
+                .unwrap();
+            complete_export_job(job.id, file_name.clone(), 0, &state)
+                .await
+                .unwrap();
+
+            entity::export_jobs::ActiveModel {
+                id: Set(job.id),
+                completed_at: Set(Some(
+                    (chrono::Utc::now() - chrono::Duration::seconds(age)).into(),
+                )),
+                ..Default::default()
+            }
+            .update(state.pg_db.get_ref())
+            .await
+            .unwrap();
+
+            jobs.push((job.id, export_path(&file_name, "csv", &state)));
+        }
+
+        sweep_exports(&state).await;
+
+        let (expired, expired_path) = &jobs[0];
+        let (recent, recent_path) = &jobs[1];
+
+        assert!(find_job(*expired, &state).await.is_none());
+        assert!(!expired_path.exists());
+        assert!(find_job(*recent, &state).await.is_some());
+        assert!(recent_path.exists());
+
+        std::fs::remove_file(recent_path).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use actix_web::web;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QueryTrait, Set,
+};
+
+use crate::{
+    app::exports::models::model::{SaveExportJobDto, COMPLETED, FAILED, PENDING, RUNNING},
+    AppState,
+};
+
+pub async fn save_export_job(
+    data: SaveExportJobDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::export_jobs::Model, DbErr> {
+    let job = entity::export_jobs::ActiveModel {
+        organization_id: Set(data.organization_id),
+        requested_by: Set(Some(data.requested_by)),
+        format: Set(data.format),
+        filters: Set(data.filters),
+        include_photos: Set(data.include_photos),
+        ..Default::default()
+    };
+
+    let saved = ActiveModelTrait::insert(job, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(saved)
+}
+
+pub async fn get_export_jobs(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::export_jobs::Model>, DbErr> {
+    let jobs = entity::export_jobs::Entity::find()
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::export_jobs::Column::OrganizationId.eq(org))
+        })
+        .order_by_desc(entity::export_jobs::Column::CreatedAt)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(jobs)
+}
+
+pub async fn get_export_job(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<entity::export_jobs::Model, DbErr> {
+    let job = entity::export_jobs::Entity::find_by_id(id)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::export_jobs::Column::OrganizationId.eq(org))
+        })
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Export not found".into()));
+
+    job
+}
+
+// Claims a pending job for a worker. Conditional on the status so a job is only ever
+// run once.
+pub async fn start_export_job(id: uuid::Uuid, state: &web::Data<AppState>) -> Result<bool, DbErr> {
+    let result = entity::export_jobs::Entity::update_many()
+        .col_expr(entity::export_jobs::Column::Status, Expr::value(RUNNING))
+        .col_expr(
+            entity::export_jobs::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::export_jobs::Column::Id.eq(id))
+                .add(entity::export_jobs::Column::Status.eq(PENDING)),
+        )
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+pub async fn complete_export_job(
+    id: uuid::Uuid,
+    file_name: String,
+    row_count: i32,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::export_jobs::Entity::update_many()
+        .col_expr(entity::export_jobs::Column::Status, Expr::value(COMPLETED))
+        .col_expr(
+            entity::export_jobs::Column::FileName,
+            Expr::value(file_name),
+        )
+        .col_expr(
+            entity::export_jobs::Column::RowCount,
+            Expr::value(row_count),
+        )
+        .col_expr(
+            entity::export_jobs::Column::CompletedAt,
+            Expr::current_timestamp().into(),
+        )
+        .col_expr(
+            entity::export_jobs::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::export_jobs::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+pub async fn fail_export_job(
+    id: uuid::Uuid,
+    error: String,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    entity::export_jobs::Entity::update_many()
+        .col_expr(entity::export_jobs::Column::Status, Expr::value(FAILED))
+        .col_expr(entity::export_jobs::Column::Error, Expr::value(error))
+        .col_expr(
+            entity::export_jobs::Column::CompletedAt,
+            Expr::current_timestamp().into(),
+        )
+        .col_expr(
+            entity::export_jobs::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::export_jobs::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+// Jobs still pending or running when the server starts were cut off by the previous
+// process stopping, and no worker will ever pick them up again.
+pub async fn fail_interrupted_export_jobs(state: &web::Data<AppState>) -> Result<u64, DbErr> {
+    let result = entity::export_jobs::Entity::update_many()
+        .col_expr(entity::export_jobs::Column::Status, Expr::value(FAILED))
+        .col_expr(
+            entity::export_jobs::Column::Error,
+            Expr::value("Interrupted by a server restart"),
+        )
+        .col_expr(
+            entity::export_jobs::Column::CompletedAt,
+            Expr::current_timestamp().into(),
+        )
+        .col_expr(
+            entity::export_jobs::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::export_jobs::Column::Status.is_in([PENDING, RUNNING]))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected)
+}
+
+pub async fn get_expired_export_jobs(
+    finished_before: chrono::DateTime<chrono::Utc>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::export_jobs::Model>, DbErr> {
+    let jobs = entity::export_jobs::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::export_jobs::Column::Status.is_in([COMPLETED, FAILED]))
+                .add(entity::export_jobs::Column::CompletedAt.lt(finished_before)),
+        )
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(jobs)
+}
+
+pub async fn delete_export_job(id: uuid::Uuid, state: &web::Data<AppState>) -> Result<(), DbErr> {
+    entity::export_jobs::Entity::delete_by_id(id)
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database delete error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
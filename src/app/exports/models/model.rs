@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+pub const PENDING: &str = "pending";
+pub const RUNNING: &str = "running";
+pub const COMPLETED: &str = "completed";
+pub const FAILED: &str = "failed";
+
+pub const EXPORT_FORMATS: [&str; 3] = ["csv", "xlsx", "pdf"];
+pub const EXPORT_COLUMNS: [&str; 12] = [
+    "First Name",
+    "Last Name",
+    "Alias",
+    "Gender",
+    "Phone",
+    "Email",
+    "Address",
+    "Date of Birth",
+    "Date Joined",
+    "Departments",
+    "Member Type",
+    "Status",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportMembersModel {
+    pub format: String,
+    pub include_photos: Option<bool>,
+    pub department_id: Option<String>,
+    pub member_type: Option<String>,
+    pub gender: Option<String>,
+    pub status: Option<String>,
+    pub joined_from: Option<chrono::NaiveDate>,
+    pub joined_to: Option<chrono::NaiveDate>,
+    pub born_from: Option<chrono::NaiveDate>,
+    pub born_to: Option<chrono::NaiveDate>,
+}
+
+pub struct SaveExportJobDto {
+    pub organization_id: uuid::Uuid,
+    pub requested_by: uuid::Uuid,
+    pub format: String,
+    pub filters: serde_json::Value,
+    pub include_photos: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportJobResponseModel {
+    pub id: String,
+    pub format: String,
+    pub status: String,
+    pub row_count: Option<i32>,
+    pub error: Option<String>,
+    pub download_url: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
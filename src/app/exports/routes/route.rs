@@ -0,0 +1,46 @@
+use actix_web::web;
+
+use crate::{
+    app::exports::controllers::controller::{
+        download_export, export_members, get_export, list_exports,
+    },
+    middlewares::{
+        auth::JwtAuthMiddleware,
+        role::{RequireRole, ADMIN, SECRETARY, SUPER_ADMIN},
+    },
+    AppState,
+};
+
+pub fn all_routes(cfg: &mut web::ServiceConfig, _state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/api/v1/exports")
+            .route(
+                "/members",
+                web::post()
+                    .to(export_members)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/get",
+                web::get()
+                    .to(list_exports)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(get_export)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/download",
+                web::get()
+                    .to(download_export)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
+    );
+}
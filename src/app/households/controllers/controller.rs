@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use sea_orm::TransactionTrait;
+use serde_json::json;
+
+use crate::{
+    app::{
+        departments::dto::dtos::get_departments_by_member,
+        households::{
+            dto::dtos::{
+                delete_household, get_household_by_id, get_household_member_counts,
+                get_household_members, get_households, save_household, set_members_household,
+                update_household,
+            },
+            models::model::{
+                AddHouseholdMemberModel, AddHouseholdModel, HouseholdDetailModel,
+                HouseholdMemberModel, HouseholdResponseModel, SaveHouseholdDto, UpdateHouseholdDto,
+                UpdateHouseholdModel,
+            },
+        },
+        members::dto::dtos::get_member_by_id,
+    },
+    libs::{error, validator},
+    middlewares::auth::AuthContext,
+    utils::models::HttpClientResponse,
+    AppState,
+};
+
+pub async fn add_household(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<AddHouseholdModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let name = validator::required_str(payload.name.trim(), "Name")?;
+    let member_ids = payload
+        .member_ids
+        .as_deref()
+        .unwrap_or(&[])
+        .iter()
+        .map(|id| validator::uuid(id, "Member ID"))
+        .collect::<Result<HashSet<uuid::Uuid>, error::Error>>()?
+        .into_iter()
+        .collect::<Vec<uuid::Uuid>>();
+
+    let txn = state
+        .pg_db
+        .begin()
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let household = save_household(
+        SaveHouseholdDto {
+            organization_id: auth.organization_id,
+            name,
+            address: payload.address.clone(),
+        },
+        &txn,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    if !member_ids.is_empty() {
+        let expected = member_ids.len() as u64;
+
+        let updated = set_members_household(
+            member_ids,
+            household.organization_id,
+            Some(household.id),
+            &txn,
+        )
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+        // any id missing from the organization leaves the household uncreated
+        if updated != expected {
+            return Err(error::new_error(1004, "Member Not Found", 404));
+        }
+    }
+
+    txn.commit().await.map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Household Added Successfully".to_string(),
+        data: json!(household_detail(household, &state).await?),
+    }))
+}
+
+pub async fn list_households(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let households = get_households(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let counts: HashMap<uuid::Uuid, i64> = get_household_member_counts(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .into_iter()
+        .collect();
+
+    let households: Vec<HouseholdResponseModel> = households
+        .into_iter()
+        .map(|household| HouseholdResponseModel {
+            id: household.id.to_string(),
+            member_count: counts.get(&household.id).copied().unwrap_or(0),
+            name: household.name,
+            address: household.address,
+            created_at: household.created_at.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Households Retrieved Successfully".to_string(),
+        data: json!(households),
+    }))
+}
+
+pub async fn get_household(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Household ID")?;
+
+    let household = get_household_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Household Not Found", 404))?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Household Retrieved Successfully".to_string(),
+        data: json!(household_detail(household, &state).await?),
+    }))
+}
+
+pub async fn patch_household(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<UpdateHouseholdModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Household ID")?;
+
+    let data = UpdateHouseholdDto {
+        name: payload
+            .name
+            .as_deref()
+            .map(|name| validator::required_str(name.trim(), "Name"))
+            .transpose()?,
+        address: payload.address.clone(),
+    };
+
+    get_household_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Household Not Found", 404))?;
+
+    let household = update_household(id, auth.scope(), data, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Household Updated Successfully".to_string(),
+        data: json!(household_detail(household, &state).await?),
+    }))
+}
+
+pub async fn remove_household(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Household ID")?;
+
+    if !delete_household(id, auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        return Err(error::new_error(1004, "Household Not Found", 404));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Household Deleted Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+// A member lives in one household at a time, so adding moves them out of any other.
+pub async fn add_household_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<AddHouseholdMemberModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Household ID")?;
+    let member_id = validator::uuid(&payload.member_id, "Member ID")?;
+
+    let household = get_household_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Household Not Found", 404))?;
+
+    let updated = set_members_household(
+        vec![member_id],
+        household.organization_id,
+        Some(household.id),
+        state.pg_db.get_ref(),
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    if updated == 0 {
+        return Err(error::new_error(1004, "Member Not Found", 404));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Household Member Added Successfully".to_string(),
+        data: json!(household_detail(household, &state).await?),
+    }))
+}
+
+pub async fn remove_household_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let (id, member_id) = path.into_inner();
+    let id = validator::uuid(&id, "Household ID")?;
+    let member_id = validator::uuid(&member_id, "Member ID")?;
+
+    let household = get_household_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Household Not Found", 404))?;
+
+    let member = get_member_by_id(member_id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    if member.household_id != Some(household.id) {
+        return Err(error::new_error(
+            1004,
+            "Member Is Not in This Household",
+            404,
+        ));
+    }
+
+    set_members_household(
+        vec![member.id],
+        household.organization_id,
+        None,
+        state.pg_db.get_ref(),
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Household Member Removed Successfully".to_string(),
+        data: json!(household_detail(household, &state).await?),
+    }))
+}
+
+// Also used for the household section of member detail responses.
+pub async fn household_detail(
+    household: entity::households::Model,
+    state: &web::Data<AppState>,
+) -> Result<HouseholdDetailModel, error::Error> {
+    let members = get_household_members(household.id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let mut departments =
+        get_departments_by_member(members.iter().map(|member| member.id).collect(), state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+    Ok(HouseholdDetailModel {
+        id: household.id.to_string(),
+        name: household.name,
+        address: household.address,
+        members: members
+            .into_iter()
+            .map(|member| HouseholdMemberModel {
+                id: member.id.to_string(),
+                first_name: member.first_name,
+                last_name: member.last_name,
+                departments: departments
+                    .remove(&member.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|department| department.name)
+                    .collect(),
+                member_type: member.member_type,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    use crate::{
+        libs::code::gen_numeric_code,
+        middlewares::role::ADMIN,
+        utils::testing::{bearer, seed_tenant, test_app, test_state},
+    };
+
+    #[actix_web::test]
+    async fn household_is_not_created_with_unknown_members() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let name = format!("Household {}", gen_numeric_code(8));
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/households/add")
+            .insert_header(bearer(&org_a.token))
+            .set_json(serde_json::json!({
+                "name": name,
+                "member_ids": [org_a.member.id.to_string(), org_b.member.id.to_string()],
+            }))
+            .to_request();
+
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let households = entity::households::Entity::find()
+            .filter(entity::households::Column::Name.eq(name))
+            .all(state.pg_db.get_ref())
+            .await
+            .unwrap();
+
+        assert!(households.is_empty());
+
+        let member = entity::members::Entity::find_by_id(org_a.member.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(member.household_id, None);
+    }
+
+    #[actix_web::test]
+    async fn member_is_removed_from_household() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/households/add")
+            .insert_header(bearer(&org.token))
+            .set_json(serde_json::json!({
+                "name": "The Mensahs",
+                "member_ids": [org.member.id.to_string()],
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let household_id = body["data"]["id"].as_str().unwrap().to_string();
+
+        assert_eq!(body["data"]["members"].as_array().unwrap().len(), 1);
+
+        let uri = format!(
+            "/api/v1/households/{}/members/{}",
+            household_id, org.member.id
+        );
+
+        for expected in [StatusCode::OK, StatusCode::NOT_FOUND] {
+            let req = test::TestRequest::delete()
+                .uri(&uri)
+                .insert_header(bearer(&org.token))
+                .to_request();
+
+            assert_eq!(test::call_service(&app, req).await.status(), expected);
+        }
+
+        let member = entity::members::Entity::find_by_id(org.member.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(member.household_id, None);
+    }
+}
@@ -0,0 +1,190 @@
+use actix_web::web;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+};
+
+use crate::{
+    app::households::models::model::{SaveHouseholdDto, UpdateHouseholdDto},
+    apply_update_wrap, AppState,
+};
+
+// Takes the connection to insert on, so the household can be created in the same
+// transaction that moves its first members in.
+pub async fn save_household(
+    data: SaveHouseholdDto,
+    db: &impl ConnectionTrait,
+) -> Result<entity::households::Model, DbErr> {
+    let household = entity::households::ActiveModel {
+        organization_id: Set(data.organization_id),
+        name: Set(data.name),
+        address: Set(data.address),
+        ..Default::default()
+    };
+
+    let saved = ActiveModelTrait::insert(household, db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(saved)
+}
+
+pub async fn get_households(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::households::Model>, DbErr> {
+    let households = entity::households::Entity::find()
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::households::Column::OrganizationId.eq(org))
+        })
+        .order_by_asc(entity::households::Column::Name)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(households)
+}
+
+pub async fn get_household_by_id(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<entity::households::Model, DbErr> {
+    let household = entity::households::Entity::find_by_id(id)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::households::Column::OrganizationId.eq(org))
+        })
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Household not found".into()));
+
+    household
+}
+
+pub async fn update_household(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    household: UpdateHouseholdDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::households::Model, DbErr> {
+    let exists = get_household_by_id(id, organization_id, state).await?;
+
+    let mut model: entity::households::ActiveModel = exists.into();
+
+    apply_update_wrap!(model, household,
+        name: name,
+        address: address => Some
+    );
+
+    model.updated_at = ActiveValue::set(chrono::Utc::now().into());
+
+    let updated = ActiveModelTrait::update(model, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(updated)
+}
+
+// Members of a deleted household are kept, the foreign key just clears their link.
+pub async fn delete_household(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::households::Entity::delete_many()
+        .filter(entity::households::Column::Id.eq(id))
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::households::Column::OrganizationId.eq(org))
+        })
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database delete error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+pub async fn get_household_members(
+    household_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::members::Model>, DbErr> {
+    let members = entity::members::Entity::find()
+        .filter(entity::members::Column::HouseholdId.eq(household_id))
+        .order_by_asc(entity::members::Column::LastName)
+        .order_by_asc(entity::members::Column::FirstName)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(members)
+}
+
+pub async fn get_household_member_counts(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<(uuid::Uuid, i64)>, DbErr> {
+    let counts = entity::members::Entity::find()
+        .select_only()
+        .column(entity::members::Column::HouseholdId)
+        .column_as(entity::members::Column::Id.count(), "member_count")
+        .filter(entity::members::Column::HouseholdId.is_not_null())
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::members::Column::OrganizationId.eq(org))
+        })
+        .group_by(entity::members::Column::HouseholdId)
+        .into_tuple()
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(counts)
+}
+
+// Moves members into a household, or out of any with None. Only members of the given
+// organization are touched; returns how many were.
+pub async fn set_members_household(
+    member_ids: Vec<uuid::Uuid>,
+    organization_id: uuid::Uuid,
+    household_id: Option<uuid::Uuid>,
+    db: &impl ConnectionTrait,
+) -> Result<u64, DbErr> {
+    let result = entity::members::Entity::update_many()
+        .col_expr(
+            entity::members::Column::HouseholdId,
+            Expr::value(household_id),
+        )
+        .col_expr(
+            entity::members::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::members::Column::Id.is_in(member_ids))
+                .add(entity::members::Column::OrganizationId.eq(organization_id)),
+        )
+        .exec(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected)
+}
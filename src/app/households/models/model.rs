@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddHouseholdModel {
+    pub name: String,
+    pub address: Option<String>,
+    pub member_ids: Option<Vec<String>>,
+}
+
+pub struct SaveHouseholdDto {
+    pub organization_id: uuid::Uuid,
+    pub name: String,
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateHouseholdModel {
+    pub name: Option<String>,
+    pub address: Option<String>,
+}
+
+pub struct UpdateHouseholdDto {
+    pub name: Option<String>,
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddHouseholdMemberModel {
+    pub member_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HouseholdResponseModel {
+    pub id: String,
+    pub name: String,
+    pub address: Option<String>,
+    pub member_count: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HouseholdMemberModel {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub departments: Vec<String>,
+    pub member_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HouseholdDetailModel {
+    pub id: String,
+    pub name: String,
+    pub address: Option<String>,
+    pub members: Vec<HouseholdMemberModel>,
+}
@@ -0,0 +1,68 @@
+use actix_web::web;
+
+use crate::{
+    app::households::controllers::controller::{
+        add_household, add_household_member, get_household, list_households, patch_household,
+        remove_household, remove_household_member,
+    },
+    middlewares::{
+        auth::JwtAuthMiddleware,
+        role::{RequireRole, ADMIN, SECRETARY, SUPER_ADMIN},
+    },
+    AppState,
+};
+
+pub fn all_routes(cfg: &mut web::ServiceConfig, _state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/api/v1/households")
+            .route(
+                "/add",
+                web::post()
+                    .to(add_household)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/get",
+                web::get()
+                    .to(list_households)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(get_household)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::patch()
+                    .to(patch_household)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::delete()
+                    .to(remove_household)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/members",
+                web::post()
+                    .to(add_household_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/members/{member_id}",
+                web::delete()
+                    .to(remove_household_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
+    );
+}
@@ -0,0 +1,660 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use sea_orm::TransactionTrait;
+use serde_json::json;
+
+use crate::{
+    app::{
+        invitations::{
+            dto::dtos::{
+                get_application_by_id, get_applications, get_invitation_by_code, get_invitations,
+                get_pending_application_by_phone, link_application_member, review_application,
+                revoke_invitation, save_application, save_invitation, use_invitation,
+            },
+            models::model::{
+                ApplicationQueryModel, ApproveApplicationModel, ApprovedApplicationModel,
+                CreateInvitationModel, CreatedInvitationModel, InvitationDetailsModel,
+                InvitationResponseModel, RejectApplicationModel, SaveApplicationDto,
+                SaveInvitationDto, SubmitApplicationModel, APPLICATION_STATUSES, APPROVED,
+                REJECTED,
+            },
+        },
+        members::{
+            dto::dtos::{get_member_by_phone, save_member},
+            models::model::{AddMemberDto, GENDERS},
+        },
+        organization::dto::dtos::get_organization_by_id,
+        users::{controllers::controller::provision_account, dto::dtos::get_user_by_contact},
+    },
+    libs::{
+        error,
+        jwt::{gen_string, hash_token},
+        validator,
+    },
+    middlewares::{
+        auth::AuthContext,
+        role::{ADMIN, MEMBER, ORGANIZATION_ROLES, SUPER_ADMIN},
+    },
+    utils::models::HttpClientResponse,
+    AppState,
+};
+
+pub async fn create_invitation(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<CreateInvitationModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let default_expire = state.config.get::<i64>("invitation.expire").unwrap();
+    let max_expire = state.config.get::<i64>("invitation.max_expire").unwrap();
+
+    let expires_in = payload.expires_in.unwrap_or(default_expire);
+
+    if expires_in <= 0 || expires_in > max_expire {
+        return Err(error::new_error(
+            1002,
+            &format!("Expiry must be between 1 and {} seconds", max_expire),
+            422,
+        ));
+    }
+
+    if payload.max_uses.is_some_and(|uses| uses <= 0) {
+        return Err(error::new_error(1002, "Max Uses must be positive", 422));
+    }
+
+    let code = gen_string(16);
+
+    let invitation = save_invitation(
+        SaveInvitationDto {
+            organization_id: auth.organization_id,
+            created_by: auth.user_id,
+            code_hash: hash_token(&code),
+            prefix: code[..6].to_string(),
+            expires_at: chrono::Utc::now() + chrono::Duration::seconds(expires_in),
+            max_uses: payload.max_uses,
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    let link_base = state.config.get::<String>("invitation.link_base").unwrap();
+
+    // like an API key, the code is only ever shown in this response
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Invitation Created Successfully".to_string(),
+        data: json!(CreatedInvitationModel {
+            link: format!("{}/{}", link_base.trim_end_matches('/'), code),
+            code,
+            invitation: invitation_response(invitation),
+        }),
+    }))
+}
+
+pub async fn list_invitations(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let invitations = get_invitations(auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let invitations: Vec<InvitationResponseModel> =
+        invitations.into_iter().map(invitation_response).collect();
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Invitations Retrieved Successfully".to_string(),
+        data: json!(invitations),
+    }))
+}
+
+pub async fn delete_invitation(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Invitation ID")?;
+
+    let revoked = revoke_invitation(id, auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !revoked {
+        return Err(error::new_error(1004, "Invitation Not Found", 404));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Invitation Revoked Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+// Lets the registration page show which organization the visitor is joining.
+pub async fn get_invitation(
+    _req: HttpRequest,
+    code: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let invitation = find_invitation(&code, &state).await?;
+
+    let organization = get_organization_by_id(invitation.organization_id, &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Invitation Not Found", 404))?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Invitation Fetched Successfully".to_string(),
+        data: json!(InvitationDetailsModel {
+            organization: organization.name,
+            expires_at: invitation.expires_at.to_rfc3339(),
+        }),
+    }))
+}
+
+pub async fn submit_application(
+    _req: HttpRequest,
+    code: web::Path<String>,
+    payload: web::Json<SubmitApplicationModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let first_name = validator::required_str(&payload.first_name, "First Name")?;
+    let last_name = validator::required_str(&payload.last_name, "Last Name")?;
+    let email = payload
+        .email
+        .as_deref()
+        .filter(|e| !e.is_empty())
+        .map(|e| validator::email(e, "Email"))
+        .transpose()?;
+    let mobile = validator::mobile(&payload.phone, "Phone")?;
+    let address = validator::required_str(&payload.address, "Address")?;
+    let gender = validator::one_of(&payload.gender, &GENDERS, "Gender")?;
+    let date_of_birth = validator::date(
+        payload
+            .date_of_birth
+            .map(|d| d.to_string())
+            .as_deref()
+            .unwrap_or(""),
+        "Date of Birth",
+    )
+    .and_then(|date| validator::date_of_birth(date, "Date of Birth"))?;
+
+    let invitation = find_invitation(&code, &state).await?;
+    let organization_id = invitation.organization_id;
+
+    get_organization_by_id(organization_id, &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Invitation Not Found", 404))?;
+
+    if get_member_by_phone(&mobile, organization_id, &state)
+        .await
+        .is_ok()
+    {
+        return Err(error::new_error(
+            1006,
+            &format!("Member With Contact {} Exists", mobile),
+            409,
+        ));
+    }
+
+    let pending = get_pending_application_by_phone(&mobile, organization_id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if pending.is_some() {
+        return Err(error::new_error(
+            1006,
+            "An Application With This Contact is Awaiting Approval",
+            409,
+        ));
+    }
+
+    let used = use_invitation(invitation.code_hash.clone(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !used {
+        return Err(error::new_error(1004, "Invitation Not Found", 404));
+    }
+
+    let application = save_application(
+        SaveApplicationDto {
+            organization_id,
+            invitation_id: invitation.id,
+            first_name,
+            last_name,
+            email,
+            phone: mobile,
+            address,
+            gender,
+            date_of_birth,
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Application Submitted Successfully".to_string(),
+        data: json!({ "id": application.id.to_string() }),
+    }))
+}
+
+pub async fn list_applications(
+    _req: HttpRequest,
+    auth: AuthContext,
+    query: web::Query<ApplicationQueryModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let status = query
+        .status
+        .as_deref()
+        .map(|s| validator::one_of(s, &APPLICATION_STATUSES, "Status"))
+        .transpose()?;
+
+    let applications = get_applications(auth.scope(), status, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Applications Retrieved Successfully".to_string(),
+        data: json!(applications),
+    }))
+}
+
+// Approving creates the member on the applicant's behalf, recorded as added by the
+// approver, and can provision a login for them in the same step. The application is
+// claimed first so it can only ever produce one member.
+pub async fn approve_application(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<ApproveApplicationModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Application ID")?;
+    let create_account = payload.create_account.unwrap_or(false);
+    let role = validator::one_of(
+        payload.role.as_deref().unwrap_or(MEMBER),
+        &ORGANIZATION_ROLES,
+        "Role",
+    )?;
+
+    if create_account && ![ADMIN, SUPER_ADMIN].contains(&auth.role.as_str()) {
+        return Err(error::new_error(
+            1003,
+            "Only Admins Can Create Accounts",
+            403,
+        ));
+    }
+
+    let application = get_application_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Application Not Found", 404))?;
+
+    if get_member_by_phone(&application.contact, application.organization_id, &state)
+        .await
+        .is_ok()
+    {
+        return Err(error::new_error(
+            1006,
+            &format!("Member With Contact {} Exists", application.contact),
+            409,
+        ));
+    }
+
+    if create_account
+        && get_user_by_contact(&application.contact, application.organization_id, &state)
+            .await
+            .is_ok()
+    {
+        return Err(error::new_error(
+            1006,
+            &format!("An Account With Contact {} Exists", application.contact),
+            409,
+        ));
+    }
+
+    // Claiming the application, creating the member and their account commit together,
+    // so a failure at any step leaves the application pending for another try.
+    let txn = state
+        .pg_db
+        .begin()
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let claimed = review_application(id, APPROVED, auth.user_id, None, &txn)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !claimed {
+        return Err(error::new_error(1006, "Application Already Reviewed", 409));
+    }
+
+    let member = save_member(
+        AddMemberDto {
+            first_name: application.first_name.clone(),
+            last_name: application.last_name.clone(),
+            email: application.email.clone(),
+            phone: application.contact.clone(),
+            organization_id: application.organization_id,
+            address: application.residential_address.clone(),
+            gender: application.gender.clone(),
+            date_joined: Some(chrono::Utc::now().date_naive()),
+            date_of_birth: Some(application.date_of_birth),
+            added_by: Some(auth.user_id),
+            status: "member".to_string(),
+        },
+        &txn,
+    )
+    .await
+    .map_err(|err| {
+        error::Error::from_db_err_or_conflict(
+            err,
+            &format!("Member With Contact {} Exists", application.contact),
+        )
+    })?;
+
+    link_application_member(id, member.id, &txn)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let account = if create_account {
+        Some(provision_account(&member, &role, &txn, &state).await?)
+    } else {
+        None
+    };
+
+    txn.commit().await.map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Application Approved Successfully".to_string(),
+        data: json!(ApprovedApplicationModel {
+            application: id.to_string(),
+            member: member.id.to_string(),
+            account,
+        }),
+    }))
+}
+
+pub async fn reject_application(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<RejectApplicationModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Application ID")?;
+
+    get_application_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Application Not Found", 404))?;
+
+    let reason = payload.reason.clone().filter(|r| !r.trim().is_empty());
+
+    let rejected = review_application(id, REJECTED, auth.user_id, reason, state.pg_db.get_ref())
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !rejected {
+        return Err(error::new_error(1006, "Application Already Reviewed", 409));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Application Rejected Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+async fn find_invitation(
+    code: &str,
+    state: &web::Data<AppState>,
+) -> Result<entity::invitations::Model, error::Error> {
+    let code = validator::required_str(code.trim(), "Code")?;
+
+    get_invitation_by_code(hash_token(&code), state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .ok_or_else(|| error::new_error(1004, "Invitation Not Found", 404))
+}
+
+fn invitation_response(invitation: entity::invitations::Model) -> InvitationResponseModel {
+    InvitationResponseModel {
+        id: invitation.id.to_string(),
+        prefix: invitation.prefix,
+        expires_at: invitation.expires_at.to_rfc3339(),
+        max_uses: invitation.max_uses,
+        use_count: invitation.use_count,
+        is_revoked: invitation.is_revoked,
+        created_at: invitation.created_at.to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test};
+    use sea_orm::EntityTrait;
+
+    use crate::{
+        app::{
+            invitations::{
+                dto::dtos::{save_application, save_invitation},
+                models::model::{SaveApplicationDto, SaveInvitationDto, APPROVED, PENDING},
+            },
+            members::{
+                dto::dtos::{save_member, set_member_blocked},
+                models::model::AddMemberDto,
+            },
+            users::dto::dtos::get_user_by_member_id,
+        },
+        libs::jwt::{gen_string, hash_token},
+        middlewares::role::{ADMIN, MEMBER},
+        utils::testing::{bearer, random_phone, seed_tenant, test_app, test_state, Tenant},
+        AppState,
+    };
+
+    async fn seed_application(
+        tenant: &Tenant,
+        date_of_birth: chrono::NaiveDate,
+        state: &actix_web::web::Data<AppState>,
+    ) -> entity::member_applications::Model {
+        let invitation = save_invitation(
+            SaveInvitationDto {
+                organization_id: tenant.organization.id,
+                created_by: tenant.user.id,
+                code_hash: gen_string(32),
+                prefix: gen_string(8),
+                expires_at: chrono::Utc::now() + chrono::Duration::days(1),
+                max_uses: None,
+            },
+            state,
+        )
+        .await
+        .unwrap();
+
+        save_application(
+            SaveApplicationDto {
+                organization_id: tenant.organization.id,
+                invitation_id: invitation.id,
+                first_name: "Applying".to_string(),
+                last_name: "Member".to_string(),
+                email: None,
+                phone: random_phone(),
+                address: "Test Address".to_string(),
+                gender: "female".to_string(),
+                date_of_birth,
+            },
+            state,
+        )
+        .await
+        .unwrap()
+    }
+
+    fn approve(tenant: &Tenant, id: uuid::Uuid) -> actix_http::Request {
+        test::TestRequest::post()
+            .uri(&format!("/api/v1/invitations/applications/{}/approve", id))
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({ "create_account": true, "role": MEMBER }))
+            .to_request()
+    }
+
+    #[actix_web::test]
+    async fn approval_creates_member_and_account() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let application = seed_application(
+            &tenant,
+            chrono::NaiveDate::from_ymd_opt(1990, 5, 1).unwrap(),
+            &state,
+        )
+        .await;
+        let app = test_app(&state).await;
+
+        let resp = test::call_service(&app, approve(&tenant, application.id)).await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let application = entity::member_applications::Entity::find_by_id(application.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(application.status, APPROVED);
+
+        let member_id = application.member_id.unwrap();
+
+        assert!(get_user_by_member_id(member_id, &state).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn application_with_out_of_range_birth_date_is_rejected() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let code = gen_string(32);
+
+        save_invitation(
+            SaveInvitationDto {
+                organization_id: tenant.organization.id,
+                created_by: tenant.user.id,
+                code_hash: hash_token(&code),
+                prefix: code[..8].to_string(),
+                expires_at: chrono::Utc::now() + chrono::Duration::days(1),
+                max_uses: None,
+            },
+            &state,
+        )
+        .await
+        .unwrap();
+
+        let tomorrow = chrono::Utc::now().date_naive() + chrono::Duration::days(1);
+
+        for date_of_birth in [tomorrow.to_string(), "1850-01-01".to_string()] {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/v1/invitations/join/{}", code))
+                .set_json(serde_json::json!({
+                    "first_name": "Applying",
+                    "last_name": "Member",
+                    "phone": random_phone(),
+                    "address": "Test Address",
+                    "gender": "female",
+                    "date_of_birth": date_of_birth,
+                }))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+
+            assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    #[actix_web::test]
+    async fn failed_approval_leaves_application_pending() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        // submissions are range-checked, but an application stored before they were
+        // can still fail the members table's CHECK on dates of birth
+        let application = seed_application(
+            &tenant,
+            chrono::NaiveDate::from_ymd_opt(1850, 1, 1).unwrap(),
+            &state,
+        )
+        .await;
+        let app = test_app(&state).await;
+
+        let resp = test::call_service(&app, approve(&tenant, application.id)).await;
+
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let application = entity::member_applications::Entity::find_by_id(application.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(application.status, PENDING);
+        assert_eq!(application.reviewed_by, None);
+        assert_eq!(application.member_id, None);
+    }
+
+    #[actix_web::test]
+    async fn application_from_blocked_member_contact_is_a_conflict() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let application = seed_application(
+            &tenant,
+            chrono::NaiveDate::from_ymd_opt(1990, 5, 1).unwrap(),
+            &state,
+        )
+        .await;
+        let blocked = save_member(
+            AddMemberDto {
+                first_name: "Blocked".to_string(),
+                last_name: "Member".to_string(),
+                email: None,
+                phone: application.contact.clone(),
+                organization_id: tenant.organization.id,
+                address: "Test Address".to_string(),
+                gender: "female".to_string(),
+                date_joined: None,
+                date_of_birth: None,
+                added_by: None,
+                status: "member".to_string(),
+            },
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap();
+        set_member_blocked(blocked.id, Some(tenant.organization.id), true, &state)
+            .await
+            .unwrap();
+        let app = test_app(&state).await;
+
+        let resp = test::call_service(&app, approve(&tenant, application.id)).await;
+
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let application = entity::member_applications::Entity::find_by_id(application.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(application.status, PENDING);
+    }
+}
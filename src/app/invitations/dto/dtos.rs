@@ -0,0 +1,296 @@
+use actix_web::web;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QueryTrait, Set,
+};
+
+use crate::{
+    app::invitations::models::model::{SaveApplicationDto, SaveInvitationDto, PENDING},
+    AppState,
+};
+
+pub async fn save_invitation(
+    data: SaveInvitationDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::invitations::Model, DbErr> {
+    let invitation = entity::invitations::ActiveModel {
+        organization_id: Set(data.organization_id),
+        created_by: Set(Some(data.created_by)),
+        code_hash: Set(data.code_hash),
+        prefix: Set(data.prefix),
+        expires_at: Set(data.expires_at.into()),
+        max_uses: Set(data.max_uses),
+        ..Default::default()
+    };
+
+    let saved = ActiveModelTrait::insert(invitation, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(saved)
+}
+
+pub async fn get_invitations(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::invitations::Model>, DbErr> {
+    let invitations = entity::invitations::Entity::find()
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::invitations::Column::OrganizationId.eq(org))
+        })
+        .order_by_desc(entity::invitations::Column::CreatedAt)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(invitations)
+}
+
+// Only invitations that are live and have uses left are found by their code.
+pub async fn get_invitation_by_code(
+    code_hash: String,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::invitations::Model>, DbErr> {
+    let invitation = entity::invitations::Entity::find()
+        .filter(usable_invitation(code_hash))
+        .one(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(invitation)
+}
+
+// Counted in the database against the limit, so concurrent submissions can never
+// use an invitation more often than it allows.
+pub async fn use_invitation(code_hash: String, state: &web::Data<AppState>) -> Result<bool, DbErr> {
+    let result = entity::invitations::Entity::update_many()
+        .col_expr(
+            entity::invitations::Column::UseCount,
+            Expr::col(entity::invitations::Column::UseCount).add(1),
+        )
+        .col_expr(
+            entity::invitations::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(usable_invitation(code_hash))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+// Returns false when no live invitation with that id exists in the organization.
+pub async fn revoke_invitation(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::invitations::Entity::update_many()
+        .col_expr(entity::invitations::Column::IsRevoked, Expr::value(true))
+        .col_expr(
+            entity::invitations::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::invitations::Column::Id.eq(id))
+                .add(entity::invitations::Column::IsRevoked.eq(false)),
+        )
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::invitations::Column::OrganizationId.eq(org))
+        })
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+pub async fn save_application(
+    data: SaveApplicationDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::member_applications::Model, DbErr> {
+    let application = entity::member_applications::ActiveModel {
+        organization_id: Set(data.organization_id),
+        invitation_id: Set(Some(data.invitation_id)),
+        first_name: Set(data.first_name),
+        last_name: Set(data.last_name),
+        email: Set(data.email),
+        contact: Set(data.phone),
+        gender: Set(data.gender),
+        date_of_birth: Set(data.date_of_birth),
+        residential_address: Set(data.address),
+        ..Default::default()
+    };
+
+    let saved = ActiveModelTrait::insert(application, state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(saved)
+}
+
+pub async fn get_applications(
+    organization_id: Option<uuid::Uuid>,
+    status: Option<String>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::member_applications::Model>, DbErr> {
+    let applications = entity::member_applications::Entity::find()
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::member_applications::Column::OrganizationId.eq(org))
+        })
+        .apply_if(status, |query, status| {
+            query.filter(entity::member_applications::Column::Status.eq(status))
+        })
+        .order_by_asc(entity::member_applications::Column::CreatedAt)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(applications)
+}
+
+pub async fn get_application_by_id(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<entity::member_applications::Model, DbErr> {
+    let application = entity::member_applications::Entity::find_by_id(id)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::member_applications::Column::OrganizationId.eq(org))
+        })
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Application not found".into()));
+
+    application
+}
+
+pub async fn get_pending_application_by_phone(
+    phone: &String,
+    organization_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::member_applications::Model>, DbErr> {
+    let application = entity::member_applications::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::member_applications::Column::Contact.eq(phone))
+                .add(entity::member_applications::Column::OrganizationId.eq(organization_id))
+                .add(entity::member_applications::Column::Status.eq(PENDING)),
+        )
+        .one(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(application)
+}
+
+// Moves an application out of pending. Conditional on the current status so two
+// reviewers acting at once cannot both decide the same application.
+pub async fn review_application(
+    id: uuid::Uuid,
+    status: &str,
+    reviewed_by: uuid::Uuid,
+    rejection_reason: Option<String>,
+    db: &impl ConnectionTrait,
+) -> Result<bool, DbErr> {
+    let result = entity::member_applications::Entity::update_many()
+        .col_expr(
+            entity::member_applications::Column::Status,
+            Expr::value(status),
+        )
+        .col_expr(
+            entity::member_applications::Column::ReviewedBy,
+            Expr::value(reviewed_by),
+        )
+        .col_expr(
+            entity::member_applications::Column::ReviewedAt,
+            Expr::current_timestamp().into(),
+        )
+        .col_expr(
+            entity::member_applications::Column::RejectionReason,
+            Expr::value(rejection_reason),
+        )
+        .col_expr(
+            entity::member_applications::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(
+            Condition::all()
+                .add(entity::member_applications::Column::Id.eq(id))
+                .add(entity::member_applications::Column::Status.eq(PENDING)),
+        )
+        .exec(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+pub async fn link_application_member(
+    id: uuid::Uuid,
+    member_id: uuid::Uuid,
+    db: &impl ConnectionTrait,
+) -> Result<(), DbErr> {
+    entity::member_applications::Entity::update_many()
+        .col_expr(
+            entity::member_applications::Column::MemberId,
+            Expr::value(member_id),
+        )
+        .col_expr(
+            entity::member_applications::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::member_applications::Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+fn usable_invitation(code_hash: String) -> Condition {
+    Condition::all()
+        .add(entity::invitations::Column::CodeHash.eq(code_hash))
+        .add(entity::invitations::Column::IsRevoked.eq(false))
+        .add(entity::invitations::Column::ExpiresAt.gt(chrono::Utc::now()))
+        .add(
+            Condition::any()
+                .add(entity::invitations::Column::MaxUses.is_null())
+                .add(
+                    Expr::col(entity::invitations::Column::UseCount)
+                        .lt(Expr::col(entity::invitations::Column::MaxUses)),
+                ),
+        )
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::users::models::model::ProvisionedAccountModel;
+
+pub const PENDING: &str = "pending";
+pub const APPROVED: &str = "approved";
+pub const REJECTED: &str = "rejected";
+
+pub const APPLICATION_STATUSES: [&str; 3] = [PENDING, APPROVED, REJECTED];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateInvitationModel {
+    pub expires_in: Option<i64>,
+    pub max_uses: Option<i32>,
+}
+
+pub struct SaveInvitationDto {
+    pub organization_id: uuid::Uuid,
+    pub created_by: uuid::Uuid,
+    pub code_hash: String,
+    pub prefix: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub max_uses: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvitationResponseModel {
+    pub id: String,
+    pub prefix: String,
+    pub expires_at: String,
+    pub max_uses: Option<i32>,
+    pub use_count: i32,
+    pub is_revoked: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedInvitationModel {
+    pub code: String,
+    pub link: String,
+    pub invitation: InvitationResponseModel,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvitationDetailsModel {
+    pub organization: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmitApplicationModel {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub phone: String,
+    pub address: String,
+    pub gender: String,
+    pub date_of_birth: Option<chrono::NaiveDate>,
+}
+
+pub struct SaveApplicationDto {
+    pub organization_id: uuid::Uuid,
+    pub invitation_id: uuid::Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub phone: String,
+    pub address: String,
+    pub gender: String,
+    pub date_of_birth: chrono::NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplicationQueryModel {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApproveApplicationModel {
+    pub create_account: Option<bool>,
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RejectApplicationModel {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApprovedApplicationModel {
+    pub application: String,
+    pub member: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<ProvisionedAccountModel>,
+}
@@ -0,0 +1,63 @@
+use actix_web::web;
+
+use crate::{
+    app::invitations::controllers::controller::{
+        approve_application, create_invitation, delete_invitation, get_invitation,
+        list_applications, list_invitations, reject_application, submit_application,
+    },
+    middlewares::{
+        auth::JwtAuthMiddleware,
+        role::{RequireRole, ADMIN, SECRETARY, SUPER_ADMIN},
+    },
+    AppState,
+};
+
+pub fn all_routes(cfg: &mut web::ServiceConfig, _state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/api/v1/invitations")
+            .route("/join/{code}", web::get().to(get_invitation))
+            .route("/join/{code}", web::post().to(submit_application))
+            .route(
+                "/add",
+                web::post()
+                    .to(create_invitation)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/get",
+                web::get()
+                    .to(list_invitations)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/applications",
+                web::get()
+                    .to(list_applications)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/applications/{id}/approve",
+                web::post()
+                    .to(approve_application)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/applications/{id}/reject",
+                web::post()
+                    .to(reject_application)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::delete()
+                    .to(delete_invitation)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
+    );
+}
@@ -1,17 +1,47 @@
-use std::sync::Arc;
+use std::collections::HashMap;
 
-use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
+use base64::{engine::general_purpose, Engine as _};
+use sea_orm::TransactionTrait;
 use serde_json::json;
 
 use crate::{
-    app::members::{dto::dtos::{get_all_members, get_member_by_phone, save_member}, models::model::{AddMemberDto, AddMemberModel}},
-    libs::{error, jwt::Claims, pword::parse_uuid, validator},
-    utils::models::HttpClientResponse,
+    app::{
+        auth::dto::dtos::{revoke_user_sessions, revoke_user_tokens},
+        departments::{
+            dto::dtos::{get_departments, get_departments_by_member},
+            models::model::MemberDepartmentModel,
+        },
+        households::{controllers::controller::household_detail, dto::dtos::get_household_by_id},
+        members::{
+            dto::dtos::{
+                change_member_status, delete_member, delete_relationship, get_existing_contacts,
+                get_member_by_id, get_member_by_phone, get_members_page, get_relationships,
+                get_status_history, has_relationship, member_cursor_values, member_sort_values,
+                save_imported_members, save_member, save_relationship, search_members,
+                set_member_blocked, update_member,
+            },
+            models::model::{
+                AddMemberDto, AddMemberModel, AddRelationshipModel, ChangeStatusModel,
+                ImportMemberDto, ImportMembersModel, ImportReportModel, ImportRowErrorModel,
+                MemberCursorModel, MemberDetailModel, MemberFilterDto, MemberPageDto,
+                MemberQueryModel, MemberSearchModel, RelationshipModel, SaveRelationshipDto,
+                SaveStatusChangeDto, UpdateMemberDto, UpdateMemberModel, GENDERS, IMPORT_FIELDS,
+                IMPORT_FORMATS, IMPORT_REQUIRED_FIELDS, INITIAL_STATUSES, MEMBER_SORTS,
+                MEMBER_STATUSES, MEMBER_TYPES, RELATIONSHIPS, SORT_ORDERS,
+            },
+        },
+        users::dto::dtos::get_user_by_member_id,
+    },
+    libs::{error, pagination, spreadsheet, validator},
+    middlewares::auth::AuthContext,
+    utils::models::{HttpClientResponse, PaginatedModel},
     AppState,
 };
 
 pub async fn add_member(
-    req: HttpRequest,
+    _req: HttpRequest,
+    auth: AuthContext,
     payload: web::Json<AddMemberModel>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, error::Error> {
@@ -20,7 +50,7 @@ pub async fn add_member(
     let email = validator::email(payload.email.as_deref().unwrap_or(""), "Email")?;
     let mobile = validator::mobile(&payload.phone, "Phone")?;
     let address = validator::required_str(&payload.address, "Address")?;
-    let gender = validator::required_str(&payload.gender, "Gender")?;
+    let gender = validator::one_of(&payload.gender, &GENDERS, "Gender")?;
     let date_joined = validator::date(
         payload
             .date_joined
@@ -31,34 +61,27 @@ pub async fn add_member(
     )?;
     let date_of_birth = validator::date(
         payload
-            .date_joined
+            .date_of_birth
             .map(|d| d.to_string())
             .as_deref()
             .unwrap_or(""),
         "Date of Birth",
-    )?;
+    )
+    .and_then(|date| validator::date_of_birth(date, "Date of Birth"))?;
+    let status = optional(&payload.status, |v| {
+        validator::one_of(v, &INITIAL_STATUSES, "Status")
+    })?
+    .unwrap_or_else(|| "member".to_string());
 
-    let claims = req.extensions().get::<Arc<Claims>>().cloned();
-
-    //move this check into middleware that checks user role and add only org id to the extension
-    // if claims.is_none() {
-    //     return Ok(HttpResponse::Unauthorized().json(HttpClientResponse {
-    //         code: 2000,
-    //         status: false,
-    //         message: "Unauthorized".to_string(),
-    //         data: json!({}),
-    //     }));
-    // }
-
-    let organization_id = parse_uuid(&claims.unwrap()._id);
-
-    if let Ok(_) = get_member_by_phone(&mobile, &state).await {
-        return Ok(HttpResponse::Forbidden().json(HttpClientResponse {
-            code: 2001,
-            status: false,
-            message: format!("Member With Contact {} Exists", mobile),
-            data: json!({}),
-        }));
+    let organization_id = auth.organization_id;
+
+    let conflict = format!("Member With Contact {} Exists", mobile);
+
+    if get_member_by_phone(&mobile, organization_id, &state)
+        .await
+        .is_ok()
+    {
+        return Err(error::new_error(1006, &conflict, 409));
     }
 
     let member = AddMemberDto {
@@ -70,42 +93,1575 @@ pub async fn add_member(
         gender,
         address,
         date_joined: Some(date_joined),
-        date_of_birth: Some(date_of_birth)
+        date_of_birth: Some(date_of_birth),
+        added_by: Some(auth.user_id),
+        status,
     };
 
-    let result = save_member(member, &state).await;
+    let result = save_member(member, state.pg_db.get_ref())
+        .await
+        .map_err(|err| error::Error::from_db_err_or_conflict(err, &conflict));
 
     match result {
         Ok(res) => Ok(HttpResponse::Ok().json(HttpClientResponse {
             code: 200,
             status: true,
             message: "Member Added Successfully".to_string(),
-            data: json!(res.last_insert_id),
+            data: json!(res.id),
         })),
-        Err(err) => Ok(HttpResponse::InternalServerError().json(HttpClientResponse {
-            code: 500,
-            status: false,
-            message: format!("Failed to Add Member: {}", err),
-            data: json!({}),
+        Err(err) if err.status == 409 => Err(err),
+        Err(err) => Ok(
+            HttpResponse::InternalServerError().json(HttpClientResponse {
+                code: 500,
+                status: false,
+                message: format!("Failed to Add Member: {}", err.message),
+                data: json!({}),
+            }),
+        ),
+    }
+}
+
+pub async fn get_all(
+    _req: HttpRequest,
+    auth: AuthContext,
+    query: web::Query<MemberQueryModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let query = query.into_inner();
+
+    let limit = pagination::page_limit(query.limit, &state)?;
+    let sort = optional(&query.sort, |v| validator::one_of(v, &MEMBER_SORTS, "Sort"))?
+        .unwrap_or_else(|| "name".to_string());
+    let order = optional(&query.order, |v| {
+        validator::one_of(v, &SORT_ORDERS, "Order")
+    })?
+    .unwrap_or_else(|| "asc".to_string());
+
+    let filter = MemberFilterDto {
+        department_id: query
+            .department_id
+            .as_deref()
+            .map(|id| validator::uuid(id, "Department ID"))
+            .transpose()?,
+        member_type: query.member_type.clone(),
+        gender: query.gender.clone(),
+        status: query.status.clone(),
+        joined_from: query.joined_from,
+        joined_to: query.joined_to,
+        born_from: query.born_from,
+        born_to: query.born_to,
+    };
+
+    validate_member_filter(&filter)?;
+
+    // A cursor only continues the listing it came from.
+    let after = match &query.cursor {
+        Some(cursor) => {
+            let cursor = pagination::decode_cursor::<MemberCursorModel>(cursor)?;
+
+            if cursor.sort != sort || cursor.order != order {
+                return Err(error::new_error(
+                    1002,
+                    "Cursor does not match the requested sort",
+                    422,
+                ));
+            }
+
+            let values = member_cursor_values(&sort, &cursor.values)
+                .ok_or_else(|| error::new_error(1002, "Cursor is invalid", 422))?;
+
+            Some((values, cursor.id))
+        }
+        None => None,
+    };
+    let offset = query.offset.unwrap_or(0);
+    let cursor_mode = after.is_some();
+
+    // One row past the page tells whether there is another page.
+    let page = MemberPageDto {
+        sort: sort.clone(),
+        descending: order == "desc",
+        limit: limit + 1,
+        offset,
+        after,
+    };
+
+    let (mut members, total) = get_members_page(auth.scope(), &filter, page, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let has_more = members.len() as u64 > limit;
+    members.truncate(limit as usize);
+
+    let next_cursor = match members.last() {
+        Some(last) if has_more => Some(pagination::encode_cursor(&MemberCursorModel {
+            sort: sort.clone(),
+            order: order.clone(),
+            values: member_sort_values(last, &sort),
+            id: last.id,
         })),
+        _ => None,
+    };
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Members Retrieved Successfully".to_string(),
+        data: json!(PaginatedModel {
+            items: members,
+            total,
+            limit,
+            offset: if cursor_mode { None } else { Some(offset) },
+            next_cursor,
+        }),
+    }))
+}
+
+pub async fn search(
+    _req: HttpRequest,
+    auth: AuthContext,
+    query: web::Query<MemberSearchModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let q = validator::required_str(query.q.as_deref().unwrap_or("").trim(), "Search")?;
+    let limit = pagination::page_limit(query.limit, &state)?;
+
+    if q.chars().count() < 2 {
+        return Err(error::new_error(
+            1002,
+            "Search must be at least 2 characters",
+            422,
+        ));
     }
+
+    let terms: Vec<String> = q
+        .to_lowercase()
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+
+    let members = search_members(auth.scope(), terms, limit, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Members Retrieved Successfully".to_string(),
+        data: json!(members),
+    }))
 }
 
-pub async fn get_all(_req: HttpRequest, state: web::Data<AppState>) -> Result<HttpResponse, error::Error> {
-    let members = get_all_members(&state).await;
+pub async fn import_members(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<ImportMembersModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let format = validator::one_of(&payload.format, &IMPORT_FORMATS, "Format")?;
+    let dry_run = payload.dry_run.unwrap_or(false);
+    let max_rows = state.config.get::<usize>("import.max_rows").unwrap();
+
+    let data = general_purpose::STANDARD
+        .decode(&payload.data)
+        .map_err(|_| error::new_error(1002, "Data is not valid base64", 422))?;
 
-    match members {
-        Ok(res) => Ok(HttpResponse::Ok().json(HttpClientResponse {
+    let mut rows = spreadsheet::read_rows(&format, &data)?.into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| error::new_error(1002, "File is empty", 422))?;
+
+    for field in IMPORT_REQUIRED_FIELDS {
+        if !payload.mapping.contains_key(field) {
+            return Err(error::new_error(
+                1002,
+                &format!("Mapping for {} is required", field),
+                422,
+            ));
+        }
+    }
+
+    let mut columns = HashMap::new();
+
+    for (field, column) in payload.mapping.iter() {
+        validator::one_of(field, &IMPORT_FIELDS, "Mapped Field")?;
+
+        let index = header
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(column.trim()))
+            .ok_or_else(|| error::new_error(1002, &format!("Column {} not found", column), 422))?;
+
+        columns.insert(field.as_str(), index);
+    }
+
+    // numbered as a spreadsheet shows them, with the header as row 1
+    let rows: Vec<(usize, Vec<String>)> = rows
+        .enumerate()
+        .map(|(i, row)| (i + 2, row))
+        .filter(|(_, row)| row.iter().any(|cell| !cell.is_empty()))
+        .collect();
+
+    if rows.len() > max_rows {
+        return Err(error::new_error(
+            1002,
+            &format!("Import is limited to {} rows", max_rows),
+            422,
+        ));
+    }
+
+    let departments: HashMap<String, uuid::Uuid> =
+        get_departments(Some(auth.organization_id), &state)
+            .await
+            .map_err(error::Error::from_db_err)?
+            .into_iter()
+            .map(|department| (department.name.to_lowercase(), department.id))
+            .collect();
+
+    let mut members = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (number, row) in rows.iter() {
+        let cell = |field: &str| {
+            columns
+                .get(field)
+                .and_then(|index| row.get(*index))
+                .map(|value| value.as_str())
+                .filter(|value| !value.is_empty())
+        };
+
+        match import_row(cell, &departments) {
+            Ok(member) => match seen.get(&member.phone) {
+                Some(first) => errors.push(ImportRowErrorModel {
+                    row: *number,
+                    errors: vec![format!("Phone duplicates row {}", first)],
+                }),
+                None => {
+                    seen.insert(member.phone.clone(), *number);
+                    members.push(member);
+                }
+            },
+            Err(messages) => errors.push(ImportRowErrorModel {
+                row: *number,
+                errors: messages,
+            }),
+        }
+    }
+
+    let existing = get_existing_contacts(
+        auth.organization_id,
+        members.iter().map(|member| member.phone.clone()).collect(),
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    members.retain(|member| !existing.contains(&member.phone));
+
+    let mut report = ImportReportModel {
+        dry_run,
+        rows: rows.len(),
+        created: members.len(),
+        existing: existing.len(),
+        errors,
+    };
+
+    if dry_run {
+        return Ok(HttpResponse::Ok().json(HttpClientResponse {
             code: 2000,
             status: true,
-            message: "Members Retrieved Successfully".to_string(),
-            data: json!(res)
-        })),
-        Err(e) => Ok(HttpResponse::InternalServerError().json(HttpClientResponse {
-            code: 2001,
-            status: false,
-            message: format!("Error Retrieving Members: {}", e),
-            data: json!({})
-        }))
-    }
-}
\ No newline at end of file
+            message: "Import Checked Successfully".to_string(),
+            data: json!(report),
+        }));
+    }
+
+    // an import is all or nothing, so any invalid row stops it before anything is written
+    if !report.errors.is_empty() {
+        return Ok(
+            HttpResponse::UnprocessableEntity().json(HttpClientResponse {
+                code: 1002,
+                status: false,
+                message: "Import Has Invalid Rows".to_string(),
+                data: json!(report),
+            }),
+        );
+    }
+
+    let pending = members.len();
+
+    let created = save_imported_members(auth.organization_id, auth.user_id, members, &state)
+        .await
+        .map_err(error::Error::from_db_err)? as usize;
+
+    // rows added by someone else since the check are skipped, not duplicated
+    report.created = created;
+    report.existing += pending - created;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Members Imported Successfully".to_string(),
+        data: json!(report),
+    }))
+}
+
+pub async fn get_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+
+    let member = get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    let household = match member.household_id {
+        Some(household_id) => match get_household_by_id(household_id, None, &state).await {
+            Ok(household) => Some(household_detail(household, &state).await?),
+            Err(_) => None,
+        },
+        None => None,
+    };
+
+    let departments = get_departments_by_member(vec![member.id], &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .remove(&member.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|department| MemberDepartmentModel {
+            id: department.id.to_string(),
+            parent_id: department.parent_id.map(|id| id.to_string()),
+            name: department.name,
+        })
+        .collect();
+
+    let relationships = relationship_responses(member.id, &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Member Retrieved Successfully".to_string(),
+        data: json!(MemberDetailModel {
+            member,
+            household,
+            departments,
+            relationships,
+        }),
+    }))
+}
+
+pub async fn list_relationships(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+
+    let member = get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Relationships Retrieved Successfully".to_string(),
+        data: json!(relationship_responses(member.id, &state).await?),
+    }))
+}
+
+pub async fn add_relationship(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<AddRelationshipModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+    let related_member_id = validator::uuid(&payload.related_member_id, "Related Member ID")?;
+    let relationship = validator::one_of(&payload.relationship, &RELATIONSHIPS, "Relationship")?;
+
+    if id == related_member_id {
+        return Err(error::new_error(
+            1002,
+            "A Member Cannot Be Related to Themselves",
+            422,
+        ));
+    }
+
+    let member = get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    // relatives must be in the member's own organization, whoever is asking
+    let related = get_member_by_id(related_member_id, Some(member.organization_id), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Related Member Not Found", 404))?;
+
+    if has_relationship(member.id, Some(related.id), None, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        return Err(error::new_error(1006, "Members Are Already Related", 409));
+    }
+
+    if relationship == "spouse" {
+        for spouse_of in [member.id, related.id] {
+            if has_relationship(spouse_of, None, Some("spouse"), &state)
+                .await
+                .map_err(error::Error::from_db_err)?
+            {
+                return Err(error::new_error(1006, "Member Already Has a Spouse", 409));
+            }
+        }
+    }
+
+    save_relationship(
+        SaveRelationshipDto {
+            organization_id: member.organization_id,
+            member_id: member.id,
+            related_member_id: related.id,
+            reciprocal: reciprocal(&relationship).to_string(),
+            relationship: relationship.clone(),
+        },
+        &state,
+    )
+    .await
+    .map_err(|err| {
+        // a concurrent request can get the same pair or a second spouse past the checks
+        // above, and then the unique indexes turn it away
+        let message = if err
+            .to_string()
+            .contains("idx_member_relationships_one_spouse")
+        {
+            "Member Already Has a Spouse"
+        } else {
+            "Members Are Already Related"
+        };
+
+        error::Error::from_db_err_or_conflict(err, message)
+    })?;
+
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Relationship Added Successfully".to_string(),
+        data: json!(RelationshipModel {
+            member_id: related.id.to_string(),
+            first_name: related.first_name,
+            last_name: related.last_name,
+            relationship,
+        }),
+    }))
+}
+
+pub async fn remove_relationship(
+    _req: HttpRequest,
+    auth: AuthContext,
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let (id, related_member_id) = path.into_inner();
+    let id = validator::uuid(&id, "Member ID")?;
+    let related_member_id = validator::uuid(&related_member_id, "Related Member ID")?;
+
+    if !delete_relationship(id, related_member_id, auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        return Err(error::new_error(1004, "Relationship Not Found", 404));
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Relationship Removed Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+pub async fn patch_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<UpdateMemberModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+
+    let data = UpdateMemberDto {
+        first_name: optional(&payload.first_name, |v| {
+            validator::required_str(v, "First Name")
+        })?,
+        last_name: optional(&payload.last_name, |v| {
+            validator::required_str(v, "Last Name")
+        })?,
+        email: optional(&payload.email, |v| validator::email(v, "Email"))?,
+        phone: optional(&payload.phone, |v| validator::mobile(v, "Phone"))?,
+        address: optional(&payload.address, |v| validator::required_str(v, "Address"))?,
+        gender: optional(&payload.gender, |v| {
+            validator::one_of(v, &GENDERS, "Gender")
+        })?,
+        member_type: optional(&payload.member_type, |v| {
+            validator::one_of(v, &MEMBER_TYPES, "Member Type")
+        })?,
+    };
+
+    let member = get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    if member.is_blocked {
+        return Err(error::new_error(1003, "Member is Blocked", 403));
+    }
+
+    if let Some(phone) = &data.phone {
+        let existing = get_member_by_phone(phone, member.organization_id, &state).await;
+
+        if existing.is_ok_and(|other| other.id != member.id) {
+            return Err(error::new_error(
+                1006,
+                &format!("Member With Contact {} Exists", phone),
+                409,
+            ));
+        }
+    }
+
+    let conflict = format!(
+        "Member With Contact {} Exists",
+        data.phone.as_deref().unwrap_or_default()
+    );
+
+    update_member(id, auth.scope(), data, &state)
+        .await
+        .map_err(|err| error::Error::from_db_err_or_conflict(err, &conflict))?;
+
+    let member = get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Member Updated Successfully".to_string(),
+        data: json!(member),
+    }))
+}
+
+pub async fn block_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    set_blocked(auth, id, true, state).await
+}
+
+pub async fn unblock_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    set_blocked(auth, id, false, state).await
+}
+
+pub async fn remove_member(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+
+    if id == auth.member_id {
+        return Err(error::new_error(1003, "You Cannot Delete Yourself", 403));
+    }
+
+    get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    // the account's sessions are cut in the same transaction that deletes it with the
+    // member, so a failed delete leaves them signed in
+    let txn = state
+        .pg_db
+        .begin()
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let sessions = match get_user_by_member_id(id, &state).await {
+        Ok(user) => revoke_user_tokens(user.id, &txn)
+            .await
+            .map_err(error::Error::from_db_err)?,
+        Err(_) => vec![],
+    };
+
+    let deleted = delete_member(id, auth.scope(), &txn)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !deleted {
+        return Err(error::new_error(1004, "Member Not Found", 404));
+    }
+
+    txn.commit().await.map_err(error::Error::from_db_err)?;
+
+    for session_id in sessions {
+        state.sessions.invalidate(&session_id);
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Member Deleted Successfully".to_string(),
+        data: json!({}),
+    }))
+}
+
+// Both actions are idempotent, so repeating one reports success without changes.
+// Blocking also ends the member's sessions, since login only checks it on sign-in.
+async fn set_blocked(
+    auth: AuthContext,
+    id: web::Path<String>,
+    is_blocked: bool,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+
+    if is_blocked && id == auth.member_id {
+        return Err(error::new_error(1003, "You Cannot Block Yourself", 403));
+    }
+
+    let found = set_member_blocked(id, auth.scope(), is_blocked, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !found {
+        return Err(error::new_error(1004, "Member Not Found", 404));
+    }
+
+    if is_blocked {
+        if let Ok(user) = get_user_by_member_id(id, &state).await {
+            revoke_user_sessions(user.id, &state)
+                .await
+                .map_err(error::Error::from_db_err)?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: if is_blocked {
+            "Member Blocked Successfully".to_string()
+        } else {
+            "Member Unblocked Successfully".to_string()
+        },
+        data: json!({}),
+    }))
+}
+
+// Each change is checked against the member's current status and recorded with the
+// date it took effect, which may be in the past but not the future.
+pub async fn change_status(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<ChangeStatusModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+    let status = validator::one_of(&payload.status, &MEMBER_STATUSES, "Status")?;
+    let reason = validator::required_str(&payload.reason, "Reason")?;
+    let today = chrono::Utc::now().date_naive();
+    let effective_date = payload.effective_date.unwrap_or(today);
+
+    if effective_date > today {
+        return Err(error::new_error(
+            1002,
+            "Effective Date Cannot Be in the Future",
+            422,
+        ));
+    }
+
+    let member = get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    if !status_transitions(&member.status).contains(&status.as_str()) {
+        return Err(error::new_error(
+            1002,
+            &format!("A Member Cannot Move From {} to {}", member.status, status),
+            422,
+        ));
+    }
+
+    let change = change_member_status(
+        SaveStatusChangeDto {
+            organization_id: member.organization_id,
+            member_id: member.id,
+            from_status: member.status,
+            to_status: status,
+            effective_date,
+            reason,
+            changed_by: auth.user_id,
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?
+    .ok_or_else(|| error::new_error(1006, "Member Status Was Changed Meanwhile", 409))?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Member Status Changed Successfully".to_string(),
+        data: json!(change),
+    }))
+}
+
+pub async fn status_history(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "Member ID")?;
+
+    let member = get_member_by_id(id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Member Not Found", 404))?;
+
+    let history = get_status_history(member.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Status History Retrieved Successfully".to_string(),
+        data: json!(history),
+    }))
+}
+
+fn optional<F>(value: &Option<String>, validate: F) -> Result<Option<String>, error::Error>
+where
+    F: Fn(&str) -> Result<String, error::Error>,
+{
+    value.as_deref().map(validate).transpose()
+}
+
+async fn relationship_responses(
+    member_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<RelationshipModel>, error::Error> {
+    let relationships = get_relationships(member_id, state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    Ok(relationships
+        .into_iter()
+        .map(|(relationship, relative)| RelationshipModel {
+            member_id: relative.id.to_string(),
+            first_name: relative.first_name,
+            last_name: relative.last_name,
+            relationship: relationship.relationship,
+        })
+        .collect())
+}
+
+// The statuses a member may move to from their current one. Returning transferred
+// members are taken back as members; deceased is final.
+fn status_transitions(status: &str) -> &'static [&'static str] {
+    match status {
+        "visitor" => &["convert", "member", "inactive", "transferred", "deceased"],
+        "convert" => &["member", "inactive", "transferred", "deceased"],
+        "member" => &["inactive", "transferred", "deceased"],
+        "inactive" => &["member", "transferred", "deceased"],
+        "transferred" => &["member"],
+        _ => &[],
+    }
+}
+
+// What the member is to the relative when the relative is <relationship> to the member.
+fn reciprocal(relationship: &str) -> &'static str {
+    match relationship {
+        "parent" => "child",
+        "child" => "parent",
+        "guardian" => "ward",
+        "ward" => "guardian",
+        "spouse" => "spouse",
+        _ => "sibling",
+    }
+}
+
+// Shared by the listing and exports, which take the same filters.
+pub fn validate_member_filter(filter: &MemberFilterDto) -> Result<(), error::Error> {
+    optional(&filter.member_type, |v| {
+        validator::one_of(v, &MEMBER_TYPES, "Member Type")
+    })?;
+    optional(&filter.gender, |v| validator::one_of(v, &GENDERS, "Gender"))?;
+    optional(&filter.status, |v| {
+        validator::one_of(v, &MEMBER_STATUSES, "Status")
+    })?;
+
+    Ok(())
+}
+
+// Validates one imported row the way add_member validates a single record, collecting
+// every problem instead of stopping at the first. The departments cell lists department
+// names separated by commas.
+fn import_row<'a>(
+    cell: impl Fn(&str) -> Option<&'a str>,
+    departments: &HashMap<String, uuid::Uuid>,
+) -> Result<ImportMemberDto, Vec<String>> {
+    let mut errors = Vec::new();
+
+    let first_name = collect(
+        &mut errors,
+        validator::required_str(cell("first_name").unwrap_or(""), "First Name"),
+    );
+    let last_name = collect(
+        &mut errors,
+        validator::required_str(cell("last_name").unwrap_or(""), "Last Name"),
+    );
+    let email = cell("email").and_then(|v| collect(&mut errors, validator::email(v, "Email")));
+    let phone = collect(
+        &mut errors,
+        validator::mobile(
+            &spreadsheet::restore_leading_zero(cell("phone").unwrap_or(""), 10),
+            "Phone",
+        ),
+    );
+    let address = collect(
+        &mut errors,
+        validator::required_str(cell("address").unwrap_or(""), "Address"),
+    );
+    let gender = collect(
+        &mut errors,
+        validator::one_of(
+            &cell("gender").unwrap_or("").to_lowercase(),
+            &GENDERS,
+            "Gender",
+        ),
+    );
+    // the members table's CHECKs are applied here, so a row the dry-run passes cannot fail
+    // the real import
+    let date_of_birth = collect(
+        &mut errors,
+        validator::date(cell("date_of_birth").unwrap_or(""), "Date of Birth")
+            .and_then(|date| validator::date_of_birth(date, "Date of Birth")),
+    );
+    let date_joined = cell("date_joined").and_then(|v| {
+        collect(
+            &mut errors,
+            validator::date(v, "Date Joined")
+                .and_then(|date| validator::not_future(date, "Date Joined")),
+        )
+    });
+    let mut department_ids = Vec::new();
+
+    for name in cell("departments")
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match departments.get(&name.to_lowercase()) {
+            Some(id) if !department_ids.contains(id) => department_ids.push(*id),
+            Some(_) => {}
+            None => errors.push(format!("Department {} not found", name)),
+        }
+    }
+
+    let member_type = choice(
+        &mut errors,
+        cell("member_type"),
+        &MEMBER_TYPES,
+        "Member Type",
+    );
+
+    match (first_name, last_name, phone, address, gender, date_of_birth) {
+        (
+            Some(first_name),
+            Some(last_name),
+            Some(phone),
+            Some(address),
+            Some(gender),
+            Some(date_of_birth),
+        ) if errors.is_empty() => Ok(ImportMemberDto {
+            first_name,
+            last_name,
+            email,
+            phone,
+            address,
+            gender,
+            date_of_birth,
+            date_joined,
+            alias: cell("alias").map(String::from),
+            department_ids,
+            member_type,
+        }),
+        _ => Err(errors),
+    }
+}
+
+fn collect<T>(errors: &mut Vec<String>, result: Result<T, error::Error>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            errors.push(e.message);
+            None
+        }
+    }
+}
+
+// Spreadsheets spell these as labels ("Not Selected"), so they are normalised before
+// being checked. Blank cells fall back to the column default.
+fn choice(errors: &mut Vec<String>, value: Option<&str>, allowed: &[&str], name: &str) -> String {
+    let value = match value {
+        Some(value) => value.to_lowercase().replace(' ', "_"),
+        None => return "not_selected".to_string(),
+    };
+
+    collect(errors, validator::one_of(&value, allowed, name))
+        .unwrap_or_else(|| "not_selected".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test};
+    use base64::{engine::general_purpose, Engine as _};
+    use sea_orm::{EntityTrait, SqlErr};
+
+    use crate::{
+        app::auth::{dto::dtos::save_api_key, models::model::SaveApiKeyDto},
+        app::members::{
+            dto::dtos::{
+                change_member_status, get_relationships, get_status_history, save_member,
+                save_relationship, set_member_blocked,
+            },
+            models::model::{AddMemberDto, SaveRelationshipDto, SaveStatusChangeDto},
+        },
+        app::users::controllers::controller::provision_account,
+        libs::{api_key::generate_api_key, error},
+        middlewares::role::{ADMIN, MEMBER},
+        utils::testing::{bearer, random_phone, seed_tenant, test_app, test_state, Tenant},
+    };
+
+    fn status_change(tenant: &Tenant, member_id: uuid::Uuid, status: &str) -> actix_http::Request {
+        test::TestRequest::post()
+            .uri(&format!("/api/v1/members/{}/status", member_id))
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({ "status": status, "reason": "Testing" }))
+            .to_request()
+    }
+
+    fn relate(
+        tenant: &Tenant,
+        member_id: uuid::Uuid,
+        related_member_id: uuid::Uuid,
+        relationship: &str,
+    ) -> actix_http::Request {
+        test::TestRequest::post()
+            .uri(&format!("/api/v1/members/{}/relationships", member_id))
+            .insert_header(bearer(&tenant.token))
+            .set_json(serde_json::json!({
+                "related_member_id": related_member_id.to_string(),
+                "relationship": relationship,
+            }))
+            .to_request()
+    }
+
+    fn new_member(organization_id: uuid::Uuid, phone: &str) -> AddMemberDto {
+        AddMemberDto {
+            first_name: "Test".to_string(),
+            last_name: "Member".to_string(),
+            email: None,
+            phone: phone.to_string(),
+            organization_id,
+            address: "Test Address".to_string(),
+            gender: "female".to_string(),
+            date_joined: None,
+            date_of_birth: None,
+            added_by: None,
+            status: "member".to_string(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_list_other_organization_members() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/members/get?limit=200")
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+        assert!(body.contains(&org_a.member.id.to_string()));
+        assert!(!body.contains(&org_b.member.id.to_string()));
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_read_other_organization_member() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/members/{}", org_b.member.id))
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn member_cannot_read_other_organization_member() {
+        let state = test_state().await;
+        let org_a = seed_tenant(MEMBER, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/members/{}", org_b.member.id))
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+
+        // rejected by the role guard before the member is even looked up
+        let err = test::try_call_service(&app, req).await.err().unwrap();
+
+        assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_update_other_organization_member() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/v1/members/{}", org_b.member.id))
+            .insert_header(bearer(&org_a.token))
+            .set_json(serde_json::json!({ "first_name": "Hijacked" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let member = entity::members::Entity::find_by_id(org_b.member.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(member.first_name, org_b.member.first_name);
+    }
+
+    #[actix_web::test]
+    async fn admin_cannot_delete_other_organization_member() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/members/{}", org_b.member.id))
+            .insert_header(bearer(&org_a.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let member = entity::members::Entity::find_by_id(org_b.member.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap();
+
+        assert!(member.is_some());
+    }
+
+    #[actix_web::test]
+    async fn api_key_outlives_the_member_who_created_it() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let member = save_member(
+            new_member(org.organization.id, &random_phone()),
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap();
+        let account = provision_account(&member, ADMIN, state.pg_db.get_ref(), &state)
+            .await
+            .unwrap();
+        let generated = generate_api_key(&state);
+        let api_key = save_api_key(
+            SaveApiKeyDto {
+                organization_id: org.organization.id,
+                created_by: account.user_id.parse().unwrap(),
+                name: "Kiosk".to_string(),
+                prefix: generated.prefix,
+                key_hash: generated.hash,
+                scopes: vec!["members:read".to_string()],
+                expires_at: None,
+            },
+            &state,
+        )
+        .await
+        .unwrap();
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/members/{}", member.id))
+            .insert_header(bearer(&org.token))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let api_key = entity::api_keys::Entity::find_by_id(api_key.id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(api_key.created_by, None);
+        assert!(!api_key.is_revoked);
+    }
+
+    #[actix_web::test]
+    async fn import_reports_future_birth_dates_as_row_errors() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let tomorrow = chrono::Utc::now().date_naive() + chrono::Duration::days(1);
+
+        let csv = format!(
+            "First,Last,Phone,Address,Gender,Born\n\
+             Ama,Mensah,{},Test Address,female,1990-05-01\n\
+             Kofi,Boateng,{},Test Address,male,{}\n",
+            random_phone(),
+            random_phone(),
+            tomorrow
+        );
+
+        let import = |dry_run: bool| {
+            test::TestRequest::post()
+                .uri("/api/v1/members/import")
+                .insert_header(bearer(&org.token))
+                .set_json(serde_json::json!({
+                    "format": "csv",
+                    "data": general_purpose::STANDARD.encode(&csv),
+                    "mapping": {
+                        "first_name": "First",
+                        "last_name": "Last",
+                        "phone": "Phone",
+                        "address": "Address",
+                        "gender": "Gender",
+                        "date_of_birth": "Born",
+                    },
+                    "dry_run": dry_run,
+                }))
+                .to_request()
+        };
+
+        let resp = test::call_service(&app, import(true)).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+
+        assert_eq!(body["data"]["created"], 1);
+        assert_eq!(body["data"]["errors"][0]["row"], 3);
+        assert_eq!(
+            body["data"]["errors"][0]["errors"][0],
+            "Date of Birth cannot be in the future"
+        );
+
+        // the real import refuses the file the same way instead of failing on the CHECK
+        let resp = test::call_service(&app, import(false)).await;
+
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[actix_web::test]
+    async fn members_page_by_date_joined_including_unknown_dates() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let mut added = Vec::new();
+
+        for date_joined in [chrono::NaiveDate::from_ymd_opt(2020, 1, 1), None] {
+            let saved = save_member(
+                AddMemberDto {
+                    first_name: "Dated".to_string(),
+                    last_name: "Member".to_string(),
+                    email: None,
+                    phone: random_phone(),
+                    organization_id: org.organization.id,
+                    address: "Test Address".to_string(),
+                    gender: "female".to_string(),
+                    date_joined,
+                    date_of_birth: None,
+                    added_by: None,
+                    status: "member".to_string(),
+                },
+                state.pg_db.get_ref(),
+            )
+            .await
+            .unwrap();
+
+            added.push(saved.id);
+        }
+
+        // members without a join date come first, ties broken by id
+        let mut expected = vec![org.member.id, added[1]];
+        expected.sort();
+        expected.push(added[0]);
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let uri = match &cursor {
+                Some(cursor) => format!(
+                    "/api/v1/members/get?sort=date_joined&limit=1&cursor={}",
+                    cursor
+                ),
+                None => "/api/v1/members/get?sort=date_joined&limit=1".to_string(),
+            };
+            let req = test::TestRequest::get()
+                .uri(&uri)
+                .insert_header(bearer(&org.token))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+
+            assert_eq!(resp.status(), StatusCode::OK);
+
+            let body: serde_json::Value = test::read_body_json(resp).await;
+
+            for item in body["data"]["items"].as_array().unwrap() {
+                seen.push(item["id"].as_str().unwrap().parse::<uuid::Uuid>().unwrap());
+            }
+
+            match body["data"]["next_cursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+
+        assert_eq!(seen, expected);
+    }
+
+    #[actix_web::test]
+    async fn added_member_keeps_the_birth_date_given() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let member = |gender: &str, date_of_birth: &str| {
+            test::TestRequest::post()
+                .uri("/api/v1/members/add")
+                .insert_header(bearer(&org.token))
+                .set_json(serde_json::json!({
+                    "first_name": "New",
+                    "last_name": "Member",
+                    "email": "new@example.com",
+                    "phone": random_phone(),
+                    "address": "Test Address",
+                    "gender": gender,
+                    "date_joined": "2020-01-01",
+                    "date_of_birth": date_of_birth,
+                }))
+                .to_request()
+        };
+
+        for req in [member("Male", "1990-05-17"), member("male", "1899-12-31")] {
+            assert_eq!(
+                test::call_service(&app, req).await.status(),
+                StatusCode::UNPROCESSABLE_ENTITY
+            );
+        }
+
+        let resp = test::call_service(&app, member("male", "1990-05-17")).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let id = uuid::Uuid::parse_str(body["data"].as_str().unwrap()).unwrap();
+        let saved = entity::members::Entity::find_by_id(id)
+            .one(state.pg_db.get_ref())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            Some(saved.date_of_birth),
+            chrono::NaiveDate::from_ymd_opt(1990, 5, 17)
+        );
+        assert_eq!(
+            saved.date_joined,
+            chrono::NaiveDate::from_ymd_opt(2020, 1, 1)
+        );
+    }
+
+    #[actix_web::test]
+    async fn blocked_member_keeps_their_contact() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+        let phone = random_phone();
+
+        let blocked = save_member(
+            new_member(org.organization.id, &phone),
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap();
+        set_member_blocked(blocked.id, Some(org.organization.id), true, &state)
+            .await
+            .unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/members/add")
+            .insert_header(bearer(&org.token))
+            .set_json(serde_json::json!({
+                "first_name": "Second",
+                "last_name": "Member",
+                "email": "second@example.com",
+                "phone": phone,
+                "address": "Test Address",
+                "gender": "male",
+                "date_joined": "2020-01-01",
+                "date_of_birth": "1990-05-17",
+            }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/v1/members/{}", org.member.id))
+            .insert_header(bearer(&org.token))
+            .set_json(serde_json::json!({ "phone": phone }))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        // a duplicate that gets past the check is still a conflict, not a server error
+        let err = save_member(
+            new_member(org.organization.id, &phone),
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            error::Error::from_db_err_or_conflict(err, "Exists").status,
+            409
+        );
+    }
+
+    #[actix_web::test]
+    async fn status_changes_follow_the_allowed_transitions() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let mut visitor = new_member(org.organization.id, &random_phone());
+        visitor.status = "visitor".to_string();
+        let member = save_member(visitor, state.pg_db.get_ref()).await.unwrap();
+
+        let history = get_status_history(member.id, &state).await.unwrap();
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].from_status, None);
+        assert_eq!(history[0].to_status, "visitor");
+
+        for (status, expected) in [
+            ("convert", StatusCode::OK),
+            ("visitor", StatusCode::UNPROCESSABLE_ENTITY),
+            ("deceased", StatusCode::OK),
+            ("member", StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let resp = test::call_service(&app, status_change(&org, member.id, status)).await;
+
+            assert_eq!(resp.status(), expected, "moving to {}", status);
+        }
+
+        let history = get_status_history(member.id, &state).await.unwrap();
+
+        assert_eq!(history.len(), 3);
+    }
+
+    #[actix_web::test]
+    async fn status_change_from_a_stale_status_is_refused() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+
+        let change = |to_status: &str| SaveStatusChangeDto {
+            organization_id: org.organization.id,
+            member_id: org.member.id,
+            from_status: "member".to_string(),
+            to_status: to_status.to_string(),
+            effective_date: chrono::Utc::now().date_naive(),
+            reason: "Testing".to_string(),
+            changed_by: org.user.id,
+        };
+
+        // both read the member as "member"; only the first change may apply
+        let first = change_member_status(change("inactive"), &state)
+            .await
+            .unwrap();
+        let second = change_member_status(change("transferred"), &state)
+            .await
+            .unwrap();
+
+        assert!(first.is_some());
+        assert!(second.is_none());
+
+        let history = get_status_history(org.member.id, &state).await.unwrap();
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].to_status, "inactive");
+    }
+
+    #[actix_web::test]
+    async fn members_who_have_left_are_listed_only_when_asked_for() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let member = save_member(
+            new_member(org.organization.id, &random_phone()),
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap();
+        let resp = test::call_service(&app, status_change(&org, member.id, "deceased")).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        for (uri, listed) in [
+            ("/api/v1/members/get?limit=200", false),
+            ("/api/v1/members/get?limit=200&status=deceased", true),
+        ] {
+            let req = test::TestRequest::get()
+                .uri(uri)
+                .insert_header(bearer(&org.token))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+
+            assert_eq!(resp.status(), StatusCode::OK);
+
+            let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+            assert_eq!(body.contains(&member.id.to_string()), listed, "{}", uri);
+        }
+    }
+
+    #[actix_web::test]
+    async fn relationships_are_stored_from_both_sides() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let child = save_member(
+            new_member(org.organization.id, &random_phone()),
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap();
+
+        let resp = test::call_service(&app, relate(&org, org.member.id, child.id, "parent")).await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let relationships = get_relationships(child.id, &state).await.unwrap();
+
+        assert_eq!(relationships.len(), 1);
+        assert_eq!(relationships[0].0.relationship, "child");
+        assert_eq!(relationships[0].1.id, org.member.id);
+
+        let req = test::TestRequest::delete()
+            .uri(&format!(
+                "/api/v1/members/{}/relationships/{}",
+                child.id, org.member.id
+            ))
+            .insert_header(bearer(&org.token))
+            .to_request();
+
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        assert!(get_relationships(org.member.id, &state)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[actix_web::test]
+    async fn member_has_at_most_one_spouse() {
+        let state = test_state().await;
+        let org = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let mut members = Vec::new();
+
+        for _ in 0..2 {
+            members.push(
+                save_member(
+                    new_member(org.organization.id, &random_phone()),
+                    state.pg_db.get_ref(),
+                )
+                .await
+                .unwrap(),
+            );
+        }
+
+        let resp =
+            test::call_service(&app, relate(&org, org.member.id, members[0].id, "spouse")).await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let resp =
+            test::call_service(&app, relate(&org, members[1].id, org.member.id, "spouse")).await;
+
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        // a second spouse that gets past the check is turned away by the index
+        let err = save_relationship(
+            SaveRelationshipDto {
+                organization_id: org.organization.id,
+                member_id: members[1].id,
+                related_member_id: org.member.id,
+                relationship: "spouse".to_string(),
+                reciprocal: "spouse".to_string(),
+            },
+            &state,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            err.sql_err(),
+            Some(SqlErr::UniqueConstraintViolation(_))
+        ));
+    }
+}
@@ -1,18 +1,35 @@
+use std::collections::{HashMap, HashSet};
+
 use actix_web::web;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DbErr, EntityTrait, InsertResult,
-    QueryFilter, Set,
+    sea_query::{Expr, Func, OnConflict, SimpleExpr},
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait,
+    Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set, TransactionTrait,
+    Value,
 };
 
 use crate::{
-    app::members::models::model::{AddMemberDto, UpdateMemberDto},
+    app::{
+        departments::dto::dtos::DEPARTMENT_TREE,
+        members::models::model::{
+            AddMemberDto, ImportMemberDto, MemberFilterDto, MemberPageDto, SaveRelationshipDto,
+            SaveStatusChangeDto, UpdateMemberDto, ACTIVE_STATUSES,
+        },
+    },
     apply_update_wrap, AppState,
 };
 
-pub async fn save_member(
+// Takes the connection to insert on, so the member can be created inside a caller's
+// transaction. The member and their first status history row are saved together.
+pub async fn save_member<C: ConnectionTrait + TransactionTrait>(
     data: AddMemberDto,
-    state: &web::Data<AppState>,
-) -> Result<InsertResult<entity::members::ActiveModel>, DbErr> {
+    db: &C,
+) -> Result<entity::members::Model, DbErr> {
+    let txn = db.begin().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
     let member = entity::members::ActiveModel {
         first_name: Set(data.first_name),
         last_name: Set(data.last_name),
@@ -23,24 +40,640 @@ pub async fn save_member(
         organization_id: Set(data.organization_id),
         date_joined: Set(data.date_joined),
         date_of_birth: Set(data.date_of_birth.unwrap_or_default()),
+        added_by: Set(data.added_by),
+        status: Set(data.status),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        err
+    })?;
+
+    save_initial_status(&member, &txn).await?;
+
+    txn.commit().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    Ok(member)
+}
+
+// Every member's history starts with the status they were created with, so the trail
+// accounts for how they got to their current one.
+pub async fn save_initial_status(
+    member: &entity::members::Model,
+    db: &impl ConnectionTrait,
+) -> Result<(), DbErr> {
+    entity::member_status_history::Entity::insert(initial_status(
+        member.organization_id,
+        member.id,
+        member.status.clone(),
+        member.date_joined,
+        member.added_by,
+    ))
+    .exec_without_returning(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    Ok(())
+}
+
+fn initial_status(
+    organization_id: uuid::Uuid,
+    member_id: uuid::Uuid,
+    status: String,
+    date_joined: Option<chrono::NaiveDate>,
+    added_by: Option<uuid::Uuid>,
+) -> entity::member_status_history::ActiveModel {
+    entity::member_status_history::ActiveModel {
+        organization_id: Set(organization_id),
+        member_id: Set(member_id),
+        from_status: Set(None),
+        to_status: Set(status),
+        effective_date: Set(date_joined.unwrap_or_else(|| chrono::Utc::now().date_naive())),
+        reason: Set("Initial status".to_string()),
+        changed_by: Set(added_by),
         ..Default::default()
+    }
+}
+
+// One page of members and the total matching the filter. With a cursor the page
+// continues after that row, otherwise it starts at the offset.
+pub async fn get_members_page(
+    organization_id: Option<uuid::Uuid>,
+    filter: &MemberFilterDto,
+    page: MemberPageDto,
+    state: &web::Data<AppState>,
+) -> Result<(Vec<entity::members::Model>, u64), DbErr> {
+    let condition = member_filter(organization_id, filter);
+
+    let total = count_members(organization_id, filter, state).await?;
+
+    let mut keys = sort_keys(&page.sort);
+    keys.push(Expr::col(entity::members::Column::Id).into());
+
+    let order = if page.descending {
+        Order::Desc
+    } else {
+        Order::Asc
     };
 
-    let insertion = entity::members::Entity::insert(member)
-        .exec(state.pg_db.get_ref())
+    let mut query = entity::members::Entity::find().filter(condition);
+
+    for key in keys.iter() {
+        query = query.order_by(key.clone(), order.clone());
+    }
+
+    query = match page.after {
+        Some((mut values, id)) => {
+            values.push(id.into());
+            query.filter(after_position(&keys, values, page.descending))
+        }
+        None => query.offset(page.offset),
+    };
+
+    let members = query
+        .limit(page.limit)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok((members, total))
+}
+
+pub async fn count_members(
+    organization_id: Option<uuid::Uuid>,
+    filter: &MemberFilterDto,
+    state: &web::Data<AppState>,
+) -> Result<u64, DbErr> {
+    let total = entity::members::Entity::find()
+        .filter(member_filter(organization_id, filter))
+        .count(state.pg_db.get_ref())
         .await
         .map_err(|err| {
             eprintln!("Database retrieval error: {}", err);
             DbErr::Custom(err.to_string())
         })?;
 
-    Ok(insertion)
+    Ok(total)
 }
 
-pub async fn get_all_members(
+// Every member matching the filter, in directory order.
+pub async fn get_members_for_export(
+    organization_id: uuid::Uuid,
+    filter: &MemberFilterDto,
     state: &web::Data<AppState>,
 ) -> Result<Vec<entity::members::Model>, DbErr> {
     let members = entity::members::Entity::find()
+        .filter(member_filter(Some(organization_id), filter))
+        .order_by_asc(entity::members::Column::LastName)
+        .order_by_asc(entity::members::Column::FirstName)
+        .order_by_asc(entity::members::Column::Id)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(members)
+}
+
+// Matches members whose names, alias, contact or email contain every term, best
+// matches first. The expression is the one idx_members_search is built on.
+pub async fn search_members(
+    organization_id: Option<uuid::Uuid>,
+    terms: Vec<String>,
+    limit: u64,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::members::Model>, DbErr> {
+    let mut condition = Condition::all();
+
+    for term in terms.iter() {
+        let pattern = format!("%{}%", escape_like(term));
+        condition = condition.add(Expr::cust_with_values(
+            format!("{} LIKE $1", SEARCH_DOCUMENT),
+            [pattern],
+        ));
+    }
+
+    let members = entity::members::Entity::find()
+        .filter(condition)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::members::Column::OrganizationId.eq(org))
+        })
+        .order_by(
+            Expr::cust_with_values(
+                format!("word_similarity($1, {})", SEARCH_DOCUMENT),
+                [terms.join(" ")],
+            ),
+            Order::Desc,
+        )
+        .order_by_asc(entity::members::Column::LastName)
+        .order_by_asc(entity::members::Column::FirstName)
+        .order_by_asc(entity::members::Column::Id)
+        .limit(limit)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(members)
+}
+
+// Which of the given contacts are already taken in the organization.
+pub async fn get_existing_contacts(
+    organization_id: uuid::Uuid,
+    contacts: Vec<String>,
+    state: &web::Data<AppState>,
+) -> Result<HashSet<String>, DbErr> {
+    let existing: Vec<String> = entity::members::Entity::find()
+        .select_only()
+        .column(entity::members::Column::Contact)
+        .filter(
+            Condition::all()
+                .add(entity::members::Column::OrganizationId.eq(organization_id))
+                .add(entity::members::Column::Contact.is_in(contacts)),
+        )
+        .into_tuple()
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(existing.into_iter().collect())
+}
+
+// Inserts an import in one transaction. Rows whose contact already exists in the
+// organization are left alone, so running the same import twice adds nothing.
+// Returns how many members were created.
+pub async fn save_imported_members(
+    organization_id: uuid::Uuid,
+    added_by: uuid::Uuid,
+    rows: Vec<ImportMemberDto>,
+    state: &web::Data<AppState>,
+) -> Result<u64, DbErr> {
+    let txn = state.pg_db.begin().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    // ids are assigned here so department memberships can follow the rows that were
+    // actually created
+    let mut department_ids = HashMap::new();
+
+    let members: Vec<entity::members::ActiveModel> = rows
+        .into_iter()
+        .map(|row| {
+            let id = uuid::Uuid::new_v4();
+            department_ids.insert(id, row.department_ids);
+
+            entity::members::ActiveModel {
+                id: Set(id),
+                first_name: Set(row.first_name),
+                last_name: Set(row.last_name),
+                email: Set(row.email),
+                contact: Set(row.phone),
+                residential_address: Set(row.address),
+                gender: Set(row.gender),
+                date_of_birth: Set(row.date_of_birth),
+                date_joined: Set(row.date_joined),
+                alias: Set(row.alias),
+                member_type: Set(row.member_type),
+                organization_id: Set(organization_id),
+                added_by: Set(Some(added_by)),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    let mut created = 0;
+
+    // keeps each statement well under the postgres bind parameter limit
+    for chunk in members.chunks(1000) {
+        created += entity::members::Entity::insert_many(chunk.to_vec())
+            .on_conflict(
+                OnConflict::columns([
+                    entity::members::Column::OrganizationId,
+                    entity::members::Column::Contact,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await
+            .map_err(|err| {
+                eprintln!("Database insert error: {}", err);
+                DbErr::Custom(err.to_string())
+            })?;
+    }
+
+    let saved: Vec<(uuid::Uuid, String, Option<chrono::NaiveDate>)> =
+        entity::members::Entity::find()
+            .select_only()
+            .columns([
+                entity::members::Column::Id,
+                entity::members::Column::Status,
+                entity::members::Column::DateJoined,
+            ])
+            .filter(entity::members::Column::Id.is_in(department_ids.keys().copied()))
+            .into_tuple()
+            .all(&txn)
+            .await
+            .map_err(|err| {
+                eprintln!("Database retrieval error: {}", err);
+                DbErr::Custom(err.to_string())
+            })?;
+
+    let statuses: Vec<entity::member_status_history::ActiveModel> = saved
+        .iter()
+        .map(|(member_id, status, date_joined)| {
+            initial_status(
+                organization_id,
+                *member_id,
+                status.clone(),
+                *date_joined,
+                Some(added_by),
+            )
+        })
+        .collect();
+
+    for chunk in statuses.chunks(1000) {
+        entity::member_status_history::Entity::insert_many(chunk.to_vec())
+            .exec_without_returning(&txn)
+            .await
+            .map_err(|err| {
+                eprintln!("Database insert error: {}", err);
+                DbErr::Custom(err.to_string())
+            })?;
+    }
+
+    let memberships: Vec<entity::member_departments::ActiveModel> = saved
+        .into_iter()
+        .flat_map(|(member_id, _, _)| {
+            department_ids
+                .remove(&member_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(
+                    move |department_id| entity::member_departments::ActiveModel {
+                        organization_id: Set(organization_id),
+                        member_id: Set(member_id),
+                        department_id: Set(department_id),
+                        ..Default::default()
+                    },
+                )
+        })
+        .collect();
+
+    for chunk in memberships.chunks(1000) {
+        entity::member_departments::Entity::insert_many(chunk.to_vec())
+            .exec_without_returning(&txn)
+            .await
+            .map_err(|err| {
+                eprintln!("Database insert error: {}", err);
+                DbErr::Custom(err.to_string())
+            })?;
+    }
+
+    txn.commit().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    Ok(created)
+}
+
+// Each relationship of the member with the relative it points at.
+pub async fn get_relationships(
+    member_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<(entity::member_relationships::Model, entity::members::Model)>, DbErr> {
+    let relationships = entity::member_relationships::Entity::find()
+        .filter(entity::member_relationships::Column::MemberId.eq(member_id))
+        .order_by_asc(entity::member_relationships::Column::Relationship)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    let relatives = entity::members::Entity::find()
+        .filter(
+            entity::members::Column::Id.is_in(
+                relationships
+                    .iter()
+                    .map(|relationship| relationship.related_member_id)
+                    .collect::<Vec<uuid::Uuid>>(),
+            ),
+        )
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(relationships
+        .into_iter()
+        .filter_map(|relationship| {
+            relatives
+                .iter()
+                .find(|relative| relative.id == relationship.related_member_id)
+                .cloned()
+                .map(|relative| (relationship, relative))
+        })
+        .collect())
+}
+
+// Whether the member already has a relationship of that kind, or with that relative
+// when related_member_id is given.
+pub async fn has_relationship(
+    member_id: uuid::Uuid,
+    related_member_id: Option<uuid::Uuid>,
+    relationship: Option<&str>,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let count = entity::member_relationships::Entity::find()
+        .filter(entity::member_relationships::Column::MemberId.eq(member_id))
+        .apply_if(related_member_id, |query, related| {
+            query.filter(entity::member_relationships::Column::RelatedMemberId.eq(related))
+        })
+        .apply_if(relationship, |query, relationship| {
+            query.filter(entity::member_relationships::Column::Relationship.eq(relationship))
+        })
+        .count(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(count > 0)
+}
+
+// Stores the relationship from both sides in one transaction. The unique indexes still
+// have the last word if the same pair or a second spouse is added concurrently, and
+// their errors are returned as they are so callers can tell which one it was.
+pub async fn save_relationship(
+    data: SaveRelationshipDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::member_relationships::Model, DbErr> {
+    let txn = state.pg_db.begin().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    let saved = entity::member_relationships::ActiveModel {
+        organization_id: Set(data.organization_id),
+        member_id: Set(data.member_id),
+        related_member_id: Set(data.related_member_id),
+        relationship: Set(data.relationship),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        err
+    })?;
+
+    entity::member_relationships::ActiveModel {
+        organization_id: Set(data.organization_id),
+        member_id: Set(data.related_member_id),
+        related_member_id: Set(data.member_id),
+        relationship: Set(data.reciprocal),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        err
+    })?;
+
+    txn.commit().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    Ok(saved)
+}
+
+// Removes both sides of the relationship between the two members.
+pub async fn delete_relationship(
+    member_id: uuid::Uuid,
+    related_member_id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::member_relationships::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(entity::member_relationships::Column::MemberId.eq(member_id))
+                        .add(
+                            entity::member_relationships::Column::RelatedMemberId
+                                .eq(related_member_id),
+                        ),
+                )
+                .add(
+                    Condition::all()
+                        .add(entity::member_relationships::Column::MemberId.eq(related_member_id))
+                        .add(entity::member_relationships::Column::RelatedMemberId.eq(member_id)),
+                ),
+        )
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::member_relationships::Column::OrganizationId.eq(org))
+        })
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database delete error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+pub fn member_filter(organization_id: Option<uuid::Uuid>, filter: &MemberFilterDto) -> Condition {
+    let mut condition = Condition::all();
+
+    if let Some(org) = organization_id {
+        condition = condition.add(entity::members::Column::OrganizationId.eq(org));
+    }
+
+    condition
+        .add_option(filter.department_id.map(|v| {
+            Expr::cust_with_values(
+                format!(
+                    "members.id IN (SELECT member_id FROM member_departments WHERE department_id IN ({}))",
+                    DEPARTMENT_TREE
+                ),
+                [v],
+            )
+        }))
+        .add_option(filter.member_type.as_ref().map(|v| entity::members::Column::MemberType.eq(v)))
+        .add_option(filter.gender.as_ref().map(|v| entity::members::Column::Gender.eq(v)))
+        .add(match &filter.status {
+            Some(status) => entity::members::Column::Status.eq(status),
+            // members who have left only show up when their status is asked for
+            None => entity::members::Column::Status.is_in(ACTIVE_STATUSES),
+        })
+        .add_option(filter.joined_from.map(|v| entity::members::Column::DateJoined.gte(v)))
+        .add_option(filter.joined_to.map(|v| entity::members::Column::DateJoined.lte(v)))
+        .add_option(filter.born_from.map(|v| entity::members::Column::DateOfBirth.gte(v)))
+        .add_option(filter.born_to.map(|v| entity::members::Column::DateOfBirth.lte(v)))
+}
+
+// The values a cursor records for a member, matching sort_keys.
+pub fn member_sort_values(member: &entity::members::Model, sort: &str) -> Vec<String> {
+    match sort {
+        "date_joined" => vec![member.date_joined.unwrap_or(UNKNOWN_DATE).to_string()],
+        "date_of_birth" => vec![member.date_of_birth.to_string()],
+        _ => vec![member.last_name.clone(), member.first_name.clone()],
+    }
+}
+
+// Parses values recorded by member_sort_values back into typed ones, None if they
+// don't fit the sort.
+pub fn member_cursor_values(sort: &str, values: &[String]) -> Option<Vec<Value>> {
+    match sort {
+        "date_joined" | "date_of_birth" => match values {
+            [date] => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .map(|date| vec![date.into()]),
+            _ => None,
+        },
+        _ => match values {
+            [last_name, first_name] => {
+                Some(vec![last_name.clone().into(), first_name.clone().into()])
+            }
+            _ => None,
+        },
+    }
+}
+
+const SEARCH_DOCUMENT: &str = "lower(first_name || ' ' || last_name || ' ' || coalesce(alias, '') || ' ' || contact || ' ' || coalesce(email, ''))";
+
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Members without a join date sort as if they joined first, so the order is total.
+// NaiveDate::MIN lies outside the range of a Postgres date, so the earliest date both
+// sides can represent stands in.
+const UNKNOWN_DATE: chrono::NaiveDate = chrono::NaiveDate::from_ymd_opt(1, 1, 1).unwrap();
+
+fn sort_keys(sort: &str) -> Vec<SimpleExpr> {
+    match sort {
+        "date_joined" => vec![Func::coalesce([
+            Expr::col(entity::members::Column::DateJoined).into(),
+            Expr::val(UNKNOWN_DATE).into(),
+        ])
+        .into()],
+        "date_of_birth" => vec![Expr::col(entity::members::Column::DateOfBirth).into()],
+        _ => vec![
+            Expr::col(entity::members::Column::LastName).into(),
+            Expr::col(entity::members::Column::FirstName).into(),
+        ],
+    }
+}
+
+// Rows strictly after the given position in the (keys...) ordering, i.e.
+// k1 > v1 OR (k1 = v1 AND k2 > v2) OR ...
+fn after_position(keys: &[SimpleExpr], values: Vec<Value>, descending: bool) -> Condition {
+    let mut condition = Condition::any();
+
+    for i in 0..keys.len() {
+        let mut step = Condition::all();
+
+        for j in 0..i {
+            step = step.add(Expr::expr(keys[j].clone()).eq(values[j].clone()));
+        }
+
+        let past = if descending {
+            Expr::expr(keys[i].clone()).lt(values[i].clone())
+        } else {
+            Expr::expr(keys[i].clone()).gt(values[i].clone())
+        };
+
+        condition = condition.add(step.add(past));
+    }
+
+    condition
+}
+
+// Active members of the department or any department beneath it.
+pub async fn get_members_by_department(
+    organization_id: uuid::Uuid,
+    department_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::members::Model>, DbErr> {
+    let filter = MemberFilterDto {
+        department_id: Some(department_id),
+        ..Default::default()
+    };
+
+    let members = entity::members::Entity::find()
+        .filter(member_filter(Some(organization_id), &filter))
         .all(state.pg_db.get_ref())
         .await
         .map_err(|err| {
@@ -53,9 +686,13 @@ pub async fn get_all_members(
 
 pub async fn get_member_by_id(
     id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
     state: &web::Data<AppState>,
 ) -> Result<entity::members::Model, DbErr> {
     let member = entity::members::Entity::find_by_id(id)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::members::Column::OrganizationId.eq(org))
+        })
         .one(state.pg_db.get_ref())
         .await?
         .ok_or_else(|| DbErr::RecordNotFound("Organization not found or is blocked".into()));
@@ -63,61 +700,160 @@ pub async fn get_member_by_id(
     member
 }
 
+// Blocked members still hold their contact, so this is what uniqueness checks use.
 pub async fn get_member_by_phone(
     phone: &String,
+    organization_id: uuid::Uuid,
     state: &web::Data<AppState>,
 ) -> Result<entity::members::Model, DbErr> {
     let member = entity::members::Entity::find()
         .filter(
             Condition::all()
                 .add(entity::members::Column::Contact.eq(phone))
-                .add(entity::members::Column::IsBlocked.eq(false)),
+                .add(entity::members::Column::OrganizationId.eq(organization_id)),
         )
         .one(state.pg_db.get_ref())
         .await?
-        .ok_or_else(|| DbErr::RecordNotFound("Organization not found or is blocked".into()));
+        .ok_or_else(|| DbErr::RecordNotFound("Member not found".into()));
 
     member
 }
 
-pub async fn toggle_member_blocked(
+// Idempotent: blocking a blocked member succeeds. Returns false only when no member
+// with that id exists in the organization.
+pub async fn set_member_blocked(
     id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    is_blocked: bool,
     state: &web::Data<AppState>,
-) -> Result<(), DbErr> {
-    let exists = entity::members::Entity::find_by_id(id)
-        .one(state.pg_db.get_ref())
-        .await?;
+) -> Result<bool, DbErr> {
+    let result = entity::members::Entity::update_many()
+        .col_expr(entity::members::Column::IsBlocked, Expr::value(is_blocked))
+        .col_expr(
+            entity::members::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::members::Column::Id.eq(id))
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::members::Column::OrganizationId.eq(org))
+        })
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
 
-    let exists = match exists {
-        Some(member) => member,
-        None => return Err(DbErr::Custom("Member not found".to_string())),
-    };
+    Ok(result.rows_affected > 0)
+}
 
-    let mut model: entity::members::ActiveModel = exists.into();
+// Moves the member on only if they are still in from_status, so two concurrent changes
+// cannot both apply. Returns None when the status had already moved on.
+pub async fn change_member_status(
+    data: SaveStatusChangeDto,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::member_status_history::Model>, DbErr> {
+    let txn = state.pg_db.begin().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
 
-    if let ActiveValue::Set(is_blocked) = model.is_blocked {
-        model.is_blocked = ActiveValue::Set(!is_blocked);
+    let result = entity::members::Entity::update_many()
+        .col_expr(
+            entity::members::Column::Status,
+            Expr::value(data.to_status.clone()),
+        )
+        .col_expr(
+            entity::members::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::members::Column::Id.eq(data.member_id))
+        .filter(entity::members::Column::Status.eq(data.from_status.clone()))
+        .exec(&txn)
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    if result.rows_affected == 0 {
+        return Ok(None);
     }
 
-    model.updated_at = ActiveValue::set(chrono::Utc::now().into());
+    let change = entity::member_status_history::ActiveModel {
+        organization_id: Set(data.organization_id),
+        member_id: Set(data.member_id),
+        from_status: Set(Some(data.from_status)),
+        to_status: Set(data.to_status),
+        effective_date: Set(data.effective_date),
+        reason: Set(data.reason),
+        changed_by: Set(Some(data.changed_by)),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    txn.commit().await.map_err(|err| {
+        eprintln!("Database transaction error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
 
-    ActiveModelTrait::update(model, state.pg_db.get_ref())
+    Ok(Some(change))
+}
+
+// Most recent change first.
+pub async fn get_status_history(
+    member_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::member_status_history::Model>, DbErr> {
+    entity::member_status_history::Entity::find()
+        .filter(entity::member_status_history::Column::MemberId.eq(member_id))
+        .order_by_desc(entity::member_status_history::Column::EffectiveDate)
+        .order_by_desc(entity::member_status_history::Column::CreatedAt)
+        .all(state.pg_db.get_ref())
         .await
         .map_err(|err| {
-            eprintln!("Database update error: {}", err);
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })
+}
+
+// The member's login account, if any, goes with it through the users foreign key.
+pub async fn delete_member(
+    id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
+    db: &impl ConnectionTrait,
+) -> Result<bool, DbErr> {
+    let result = entity::members::Entity::delete_many()
+        .filter(entity::members::Column::Id.eq(id))
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::members::Column::OrganizationId.eq(org))
+        })
+        .exec(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database delete error: {}", err);
             DbErr::Custom(err.to_string())
         })?;
 
-    Ok(())
+    Ok(result.rows_affected > 0)
 }
 
 pub async fn update_member(
     id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
     member: UpdateMemberDto,
     state: &web::Data<AppState>,
 ) -> Result<entity::members::ActiveModel, DbErr> {
     let exists = entity::members::Entity::find_by_id(id)
         .filter(entity::members::Column::IsBlocked.eq(false))
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::members::Column::OrganizationId.eq(org))
+        })
         .one(state.pg_db.get_ref())
         .await?;
 
@@ -135,9 +871,6 @@ pub async fn update_member(
         contact: phone,
         residential_address: address,
         gender: gender,
-        department: department,
-        aux_department: aux_department,
-        sub_department: sub_department,
         member_type: member_type
     );
 
@@ -147,8 +880,99 @@ pub async fn update_member(
         .await
         .map_err(|err| {
             eprintln!("Database update error: {}", err);
-            DbErr::Custom(err.to_string())
+            err
         })?;
 
     Ok(updated_member.into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{save_member, search_members};
+    use crate::{
+        app::members::models::model::AddMemberDto,
+        middlewares::role::ADMIN,
+        utils::testing::{random_phone, seed_tenant, test_state, Tenant},
+        AppState,
+    };
+
+    async fn add(
+        tenant: &Tenant,
+        first_name: &str,
+        last_name: &str,
+        email: Option<&str>,
+        state: &actix_web::web::Data<AppState>,
+    ) -> entity::members::Model {
+        save_member(
+            AddMemberDto {
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+                email: email.map(String::from),
+                phone: random_phone(),
+                organization_id: tenant.organization.id,
+                address: "Test Address".to_string(),
+                gender: "male".to_string(),
+                date_joined: None,
+                date_of_birth: None,
+                added_by: None,
+                status: "member".to_string(),
+            },
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn search(
+        tenant: &Tenant,
+        q: &str,
+        state: &actix_web::web::Data<AppState>,
+    ) -> Vec<uuid::Uuid> {
+        let terms = q.split_whitespace().map(String::from).collect();
+
+        search_members(Some(tenant.organization.id), terms, 20, state)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|member| member.id)
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn partial_terms_find_the_closest_member_first() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+        let other = seed_tenant(ADMIN, &state).await;
+
+        let kwame = add(&tenant, "Kwame", "Mensah", None, &state).await;
+        let kwamena = add(&tenant, "Kwamena", "Amensah-Boateng", None, &state).await;
+        add(&tenant, "Kwame", "Owusu", None, &state).await;
+        add(&tenant, "Ama", "Mensah", None, &state).await;
+        add(&other, "Kwame", "Mensah", None, &state).await;
+
+        // every term has to match, and only within the tenant
+        assert_eq!(
+            search(&tenant, "kwam mens", &state).await,
+            [kwame.id, kwamena.id]
+        );
+    }
+
+    #[actix_web::test]
+    async fn like_wildcards_in_terms_match_literally() {
+        let state = test_state().await;
+        let tenant = seed_tenant(ADMIN, &state).await;
+
+        let kofi = add(
+            &tenant,
+            "Kofi",
+            "Boateng",
+            Some("kofi_b@example.com"),
+            &state,
+        )
+        .await;
+        add(&tenant, "Kofi", "Badu", Some("kofib@example.com"), &state).await;
+
+        assert_eq!(search(&tenant, "kofi_b", &state).await, [kofi.id]);
+        assert!(search(&tenant, "%", &state).await.is_empty());
+    }
+}
@@ -1,5 +1,30 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::app::{
+    departments::models::model::MemberDepartmentModel,
+    households::models::model::HouseholdDetailModel,
+};
+
+// Mirror the gender, member_type and status CHECK constraints on the members table.
+pub const GENDERS: [&str; 2] = ["male", "female"];
+pub const MEMBER_TYPES: [&str; 3] = ["member", "pastor", "not_selected"];
+pub const MEMBER_STATUSES: [&str; 6] = [
+    "visitor",
+    "convert",
+    "member",
+    "inactive",
+    "transferred",
+    "deceased",
+];
+// Statuses a member can be added with, and the ones still counted on rosters.
+pub const INITIAL_STATUSES: [&str; 3] = ["visitor", "convert", "member"];
+pub const ACTIVE_STATUSES: [&str; 3] = ["visitor", "convert", "member"];
+// Fields the member list can be sorted on, and the directions.
+pub const MEMBER_SORTS: [&str; 3] = ["name", "date_joined", "date_of_birth"];
+pub const SORT_ORDERS: [&str; 2] = ["asc", "desc"];
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AddMemberModel {
     pub first_name: String,
@@ -10,6 +35,7 @@ pub struct AddMemberModel {
     pub gender: String,
     pub date_joined: Option<chrono::NaiveDate>,
     pub date_of_birth: Option<chrono::NaiveDate>,
+    pub status: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +49,19 @@ pub struct AddMemberDto {
     pub gender: String,
     pub date_joined: Option<chrono::NaiveDate>,
     pub date_of_birth: Option<chrono::NaiveDate>,
+    pub added_by: Option<uuid::Uuid>,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateMemberModel {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub gender: Option<String>,
+    pub member_type: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,8 +72,171 @@ pub struct UpdateMemberDto {
     pub phone: Option<String>,
     pub address: Option<String>,
     pub gender: Option<String>,
-    pub department: Option<String>,
-    pub aux_department: Option<String>,
-    pub sub_department: Option<String>,
     pub member_type: Option<String>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberQueryModel {
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub department_id: Option<String>,
+    pub member_type: Option<String>,
+    pub gender: Option<String>,
+    pub status: Option<String>,
+    pub joined_from: Option<chrono::NaiveDate>,
+    pub joined_to: Option<chrono::NaiveDate>,
+    pub born_from: Option<chrono::NaiveDate>,
+    pub born_to: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MemberFilterDto {
+    pub department_id: Option<uuid::Uuid>,
+    pub member_type: Option<String>,
+    pub gender: Option<String>,
+    pub status: Option<String>,
+    pub joined_from: Option<chrono::NaiveDate>,
+    pub joined_to: Option<chrono::NaiveDate>,
+    pub born_from: Option<chrono::NaiveDate>,
+    pub born_to: Option<chrono::NaiveDate>,
+}
+
+// The last row of a page in terms of the sort it was fetched with; the values are the
+// sort keys of that row, in order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberCursorModel {
+    pub sort: String,
+    pub order: String,
+    pub values: Vec<String>,
+    pub id: uuid::Uuid,
+}
+
+pub struct MemberPageDto {
+    pub sort: String,
+    pub descending: bool,
+    pub limit: u64,
+    pub offset: u64,
+    pub after: Option<(Vec<sea_orm::Value>, uuid::Uuid)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberSearchModel {
+    pub q: Option<String>,
+    pub limit: Option<u64>,
+}
+
+pub const IMPORT_FORMATS: [&str; 2] = ["csv", "xlsx"];
+pub const IMPORT_FIELDS: [&str; 11] = [
+    "first_name",
+    "last_name",
+    "email",
+    "phone",
+    "address",
+    "gender",
+    "date_of_birth",
+    "date_joined",
+    "alias",
+    "departments",
+    "member_type",
+];
+pub const IMPORT_REQUIRED_FIELDS: [&str; 6] = [
+    "first_name",
+    "last_name",
+    "phone",
+    "address",
+    "gender",
+    "date_of_birth",
+];
+
+// The file is base64 encoded; mapping takes each member field to the header of the
+// column it is read from.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportMembersModel {
+    pub format: String,
+    pub data: String,
+    pub mapping: HashMap<String, String>,
+    pub dry_run: Option<bool>,
+}
+
+pub struct ImportMemberDto {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub phone: String,
+    pub address: String,
+    pub gender: String,
+    pub date_of_birth: chrono::NaiveDate,
+    pub date_joined: Option<chrono::NaiveDate>,
+    pub alias: Option<String>,
+    pub department_ids: Vec<uuid::Uuid>,
+    pub member_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportRowErrorModel {
+    pub row: usize,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportReportModel {
+    pub dry_run: bool,
+    pub rows: usize,
+    pub created: usize,
+    pub existing: usize,
+    pub errors: Vec<ImportRowErrorModel>,
+}
+
+// A relationship row reads "related member is the member's <relationship>".
+pub const RELATIONSHIPS: [&str; 6] = ["spouse", "parent", "child", "guardian", "ward", "sibling"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddRelationshipModel {
+    pub related_member_id: String,
+    pub relationship: String,
+}
+
+pub struct SaveRelationshipDto {
+    pub organization_id: uuid::Uuid,
+    pub member_id: uuid::Uuid,
+    pub related_member_id: uuid::Uuid,
+    pub relationship: String,
+    pub reciprocal: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelationshipModel {
+    pub member_id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub relationship: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberDetailModel {
+    #[serde(flatten)]
+    pub member: entity::members::Model,
+    pub household: Option<HouseholdDetailModel>,
+    pub departments: Vec<MemberDepartmentModel>,
+    pub relationships: Vec<RelationshipModel>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeStatusModel {
+    pub status: String,
+    pub effective_date: Option<chrono::NaiveDate>,
+    pub reason: String,
+}
+
+pub struct SaveStatusChangeDto {
+    pub organization_id: uuid::Uuid,
+    pub member_id: uuid::Uuid,
+    pub from_status: String,
+    pub to_status: String,
+    pub effective_date: chrono::NaiveDate,
+    pub reason: String,
+    pub changed_by: uuid::Uuid,
+}
@@ -1,15 +1,124 @@
 use actix_web::web;
 
 use crate::{
-    app::members::controllers::controller::{add_member, get_all},
-    middlewares::auth::JwtAuthMiddleware,
+    app::members::controllers::controller::{
+        add_member, add_relationship, block_member, change_status, get_all, get_member,
+        import_members, list_relationships, patch_member, remove_member, remove_relationship,
+        search, status_history, unblock_member,
+    },
+    middlewares::{
+        auth::{ApiKeyAuth, JwtAuthMiddleware},
+        role::{RequireRole, ADMIN, API_KEY, SECRETARY, SUPER_ADMIN},
+    },
     AppState,
 };
 
 pub fn all_routes(cfg: &mut web::ServiceConfig, state: web::Data<AppState>) {
     cfg.service(
         web::scope("/api/v1/members")
-            .route("/add", web::post().to(add_member).wrap(JwtAuthMiddleware))
-            .route("/get", web::get().to(get_all).wrap(JwtAuthMiddleware)),
+            .route(
+                "/add",
+                web::post()
+                    .to(add_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN, API_KEY]))
+                    .wrap(ApiKeyAuth("members:write")),
+            )
+            .route(
+                "/get",
+                web::get()
+                    .to(get_all)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN, API_KEY]))
+                    .wrap(ApiKeyAuth("members:read")),
+            )
+            .route(
+                "/search",
+                web::get()
+                    .to(search)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN, API_KEY]))
+                    .wrap(ApiKeyAuth("members:read")),
+            )
+            .service(
+                web::resource("/import")
+                    .app_data(
+                        web::JsonConfig::default()
+                            .limit(state.config.get::<usize>("import.max_size").unwrap()),
+                    )
+                    .route(
+                        web::post()
+                            .to(import_members)
+                            .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                            .wrap(JwtAuthMiddleware),
+                    ),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(get_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN, API_KEY]))
+                    .wrap(ApiKeyAuth("members:read")),
+            )
+            .route(
+                "/{id}",
+                web::patch()
+                    .to(patch_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN, API_KEY]))
+                    .wrap(ApiKeyAuth("members:write")),
+            )
+            .route(
+                "/{id}",
+                web::delete()
+                    .to(remove_member)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/status",
+                web::post()
+                    .to(change_status)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/status-history",
+                web::get()
+                    .to(status_history)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN, API_KEY]))
+                    .wrap(ApiKeyAuth("members:read")),
+            )
+            .route(
+                "/{id}/relationships",
+                web::get()
+                    .to(list_relationships)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN, API_KEY]))
+                    .wrap(ApiKeyAuth("members:read")),
+            )
+            .route(
+                "/{id}/relationships",
+                web::post()
+                    .to(add_relationship)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/relationships/{related_member_id}",
+                web::delete()
+                    .to(remove_relationship)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/block",
+                web::post()
+                    .to(block_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/unblock",
+                web::post()
+                    .to(unblock_member)
+                    .wrap(RequireRole::any([ADMIN, SECRETARY, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
     );
 }
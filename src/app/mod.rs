@@ -1,3 +1,9 @@
+pub mod auth;
+pub mod departments;
+pub mod exports;
 pub mod health;
+pub mod households;
+pub mod invitations;
+pub mod members;
 pub mod organization;
-pub mod members;
\ No newline at end of file
+pub mod users;
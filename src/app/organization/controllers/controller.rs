@@ -1,21 +1,41 @@
+use std::collections::HashMap;
+
 use actix_web::{web, HttpRequest, HttpResponse};
 use base64::{engine::general_purpose, Engine as _};
+use sea_orm::TransactionTrait;
 use serde_json::json;
 
 use crate::{
-    app::organization::{
-        dto::dtos::{
-            get_organization_by_id, get_organization_by_phone, get_organizations, save_organization,
+    app::{
+        auth::dto::dtos::revoke_user_sessions,
+        members::models::model::GENDERS,
+        organization::{
+            dto::dtos::{
+                find_organization_by_id, get_identity_provider, get_member_counts,
+                get_organization_by_id, get_organization_by_phone, get_organization_logos,
+                get_organization_settings, get_organizations, save_identity_provider,
+                save_organization, save_organization_settings, set_organization_blocked,
+                update_organization,
+            },
+            models::model::{
+                AddOrganizationDto, AddOrganizationModel, CreatedResponseModel,
+                IdentityProviderModel, OrganizationResponseModel, OrganizationSettingsModel,
+                SaveIdentityProviderDto, SaveIdentityProviderModel, UpdateOrganizationDto,
+                UpdateOrganizationModel, UpdateOrganizationSettingsDto,
+                UpdateOrganizationSettingsModel, UploadImgModel,
+            },
         },
-        models::model::{
-            AddOrganizationDto, AddOrganizationModel, CreatedResponseModel, UploadImgModel,
+        users::{
+            controllers::controller::provision_account, dto::dtos::get_users_by_organization,
+            models::model::ProvisionedAccountModel,
         },
     },
     libs::{error, validator},
+    middlewares::{auth::AuthContext, role::ADMIN},
     utils::{
         file_methods::save_file,
         models::{HttpClientResponse, SaveMediaDto, SaveMemberOrgDto},
-        shared::{save_media_meta, save_member_from_org},
+        shared::save_media_meta,
     },
     AppState,
 };
@@ -37,7 +57,7 @@ pub async fn add_organization(
     )?;
     let member_phone = validator::mobile(&payload.member_phone, "Member Phone")?;
     let member_address = validator::required_str(&payload.member_address, "Member Address")?;
-    let gender = validator::required_str(&payload.gender, "Gender")?;
+    let gender = validator::one_of(&payload.gender, &GENDERS, "Gender")?;
     let date_joined = validator::date(
         payload
             .date_joined
@@ -53,15 +73,15 @@ pub async fn add_organization(
             .as_deref()
             .unwrap_or(""),
         "Date of Birth",
-    )?;
+    )
+    .and_then(|date| validator::date_of_birth(date, "Date of Birth"))?;
 
     if let Ok(_) = get_organization_by_phone(&mobile, &state).await {
-        return Ok(HttpResponse::Forbidden().json(HttpClientResponse {
-            code: 2001,
-            status: false,
-            message: format!("Organization With Contact {} Exists", mobile),
-            data: json!({}),
-        }));
+        return Err(error::new_error(
+            1006,
+            &format!("Organization With Contact {} Exists", mobile),
+            409,
+        ));
     }
 
     let organization = AddOrganizationDto {
@@ -71,46 +91,65 @@ pub async fn add_organization(
         address,
     };
 
-    let result = save_organization(organization, &state).await;
-
-    match result {
-        Ok(res) => {
-            let data = SaveMemberOrgDto {
-                first_name,
-                last_name,
-                email: Some(member_email),
-                phone: member_phone,
-                address: member_address,
-                gender,
-                date_joined: Some(date_joined),
-                date_of_birth: Some(date_of_birth),
-            };
-
-            let save_member = save_member_from_org(res.last_insert_id, data, &state).await;
-
-            if let Err(e) = save_member {
-                return Err(error::Error::from_db_err(e));
-            };
-
-            Ok(HttpResponse::Created().json(HttpClientResponse {
-                code: 2000,
-                status: true,
-                message: "Organization Added Successfully".to_string(),
-                data: json!(CreatedResponseModel {
-                    organization: res.last_insert_id.to_string(),
-                    member: save_member.unwrap().last_insert_id.to_string(),
-                }),
-            }))
-        }
-        Err(e) => Ok(
-            HttpResponse::InternalServerError().json(HttpClientResponse {
-                code: 2001,
-                status: false,
-                message: format!("Error Adding Organization: {}", e),
-                data: json!({}),
-            }),
-        ),
-    }
+    let member = SaveMemberOrgDto {
+        first_name,
+        last_name,
+        email: Some(member_email),
+        phone: member_phone,
+        address: member_address,
+        gender,
+        date_joined: Some(date_joined),
+        date_of_birth: Some(date_of_birth),
+    };
+
+    let (organization, member, account) =
+        create_organization(organization, member, ADMIN, &state).await?;
+
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Organization Added Successfully".to_string(),
+        data: json!(CreatedResponseModel {
+            organization: organization.id.to_string(),
+            member: member.id.to_string(),
+            account,
+        }),
+    }))
+}
+
+// Creates the organization, its founding member, that member's account and default
+// settings in one transaction, so a failure at any step leaves nothing behind and
+// signup can simply be retried.
+pub async fn create_organization(
+    organization: AddOrganizationDto,
+    member: SaveMemberOrgDto,
+    role: &str,
+    state: &web::Data<AppState>,
+) -> Result<
+    (
+        entity::organization::Model,
+        entity::members::Model,
+        ProvisionedAccountModel,
+    ),
+    error::Error,
+> {
+    let txn = state
+        .pg_db
+        .begin()
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let conflict = format!("Organization With Contact {} Exists", organization.phone);
+
+    let (organization, member) = save_organization(organization, member, &txn)
+        .await
+        .map_err(|err| error::Error::from_db_err_or_conflict(err, &conflict))?;
+
+    let account = provision_account(&member, role, &txn, state).await?;
+
+    txn.commit().await.map_err(error::Error::from_db_err)?;
+
+    Ok((organization, member, account))
 }
 
 pub async fn get_all(
@@ -124,7 +163,7 @@ pub async fn get_all(
             code: 2000,
             status: true,
             message: "Organizations Fetched Successfully".to_string(),
-            data: json!(res),
+            data: json!(organization_responses(res, None, &state).await?),
         })),
         Err(e) => Ok(
             HttpResponse::InternalServerError().json(HttpClientResponse {
@@ -139,11 +178,16 @@ pub async fn get_all(
 
 pub async fn upload_img(
     _req: HttpRequest,
+    auth: AuthContext,
     data: web::Json<UploadImgModel>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, error::Error> {
     let id = validator::uuid(&data.id, "ID")?;
 
+    if !auth.can_access(id) {
+        return Err(error::new_error(1003, "Forbidden", 403));
+    }
+
     let data = match general_purpose::STANDARD.decode(&data.data) {
         Ok(data) => data,
         Err(e) => {
@@ -167,7 +211,7 @@ pub async fn upload_img(
     let media = SaveMediaDto {
         file_path: "uploads/".to_string(),
         mime_type: "image/png".to_string(),
-        file_size: 0,
+        file_size: data.len() as i64,
         file_name: format!("{}-{}", owner, uuid::Uuid::new_v4()),
         media_type: "image".to_string(),
         width: Some(0),
@@ -176,18 +220,11 @@ pub async fn upload_img(
     };
 
     let mime_type = media.mime_type.clone();
-    let media_file_name = media.file_name.clone();
-
-    let result = save_media_meta(owner, media, &state).await;
-
-    if let Err(e) = result {
-        return Err(error::Error::from_db_err(e));
-    };
-
-    let file_name = format!("{}-{}", result.unwrap().last_insert_id, media_file_name);
+    let file_name = media.file_name.clone();
     let ext = mime_type.split('/').nth(1).unwrap();
 
-    if let Err(err) = save_file(&file_name, &ext, &data).await {
+    // the file has to be on disk before its metadata can be recorded
+    if let Err(err) = save_file(&file_name, ext, &data).await {
         return Ok(
             HttpResponse::InternalServerError().json(HttpClientResponse {
                 code: 2001,
@@ -198,6 +235,12 @@ pub async fn upload_img(
         );
     };
 
+    let result = save_media_meta(owner, owner, media, &state).await;
+
+    if let Err(e) = result {
+        return Err(error::Error::from_db_err(e));
+    };
+
     Ok(HttpResponse::Ok().json(HttpClientResponse {
         code: 2000,
         status: true,
@@ -205,3 +248,582 @@ pub async fn upload_img(
         data: json!({}),
     }))
 }
+
+// The caller's own organization, for the profile page of any signed-in user.
+pub async fn get_profile(
+    _req: HttpRequest,
+    auth: AuthContext,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let organization = get_organization_by_id(auth.organization_id, &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Organization Not Found", 404))?;
+
+    let mut profile =
+        organization_responses(vec![organization], Some(auth.organization_id), &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Organization Fetched Successfully".to_string(),
+        data: json!(profile.remove(0)),
+    }))
+}
+
+pub async fn get_organization(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "ID")?;
+
+    if !auth.can_access(id) {
+        return Err(error::new_error(1003, "Forbidden", 403));
+    }
+
+    let organization = find_organization_by_id(id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .ok_or_else(|| error::new_error(1004, "Organization Not Found", 404))?;
+
+    let mut organization = organization_responses(vec![organization], Some(id), &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Organization Fetched Successfully".to_string(),
+        data: json!(organization.remove(0)),
+    }))
+}
+
+pub async fn patch_organization(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<UpdateOrganizationModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "ID")?;
+
+    if !auth.can_access(id) {
+        return Err(error::new_error(1003, "Forbidden", 403));
+    }
+
+    let data = UpdateOrganizationDto {
+        name: payload
+            .name
+            .as_deref()
+            .map(|v| validator::required_str(v, "Name"))
+            .transpose()?,
+        email: payload
+            .email
+            .as_deref()
+            .map(|v| validator::email(v, "Email"))
+            .transpose()?,
+        phone: payload
+            .phone
+            .as_deref()
+            .map(|v| validator::mobile(v, "Phone"))
+            .transpose()?,
+        address: payload
+            .address
+            .as_deref()
+            .map(|v| validator::required_str(v, "Address"))
+            .transpose()?,
+    };
+
+    find_organization_by_id(id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .ok_or_else(|| error::new_error(1004, "Organization Not Found", 404))?;
+
+    if let Some(phone) = &data.phone {
+        let existing = get_organization_by_phone(phone, &state).await;
+
+        if existing.is_ok_and(|other| other.id != id) {
+            return Err(error::new_error(
+                1006,
+                &format!("Organization With Contact {} Exists", phone),
+                409,
+            ));
+        }
+    }
+
+    update_organization(id, data, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let organization = find_organization_by_id(id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .ok_or_else(|| error::new_error(1004, "Organization Not Found", 404))?;
+
+    let mut organization = organization_responses(vec![organization], Some(id), &state).await?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Organization Updated Successfully".to_string(),
+        data: json!(organization.remove(0)),
+    }))
+}
+
+pub async fn block_organization(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    set_blocked(auth, id, true, state).await
+}
+
+pub async fn unblock_organization(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    set_blocked(auth, id, false, state).await
+}
+
+pub async fn get_settings(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "ID")?;
+
+    if !auth.can_access(id) {
+        return Err(error::new_error(1003, "Forbidden", 403));
+    }
+
+    let settings = get_organization_settings(id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    // organizations without a row yet are on the defaults
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Settings Fetched Successfully".to_string(),
+        data: json!(OrganizationSettingsModel {
+            organization_id: id.to_string(),
+            require_two_factor: settings.map(|s| s.require_two_factor).unwrap_or(false),
+        }),
+    }))
+}
+
+pub async fn update_settings(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<UpdateOrganizationSettingsModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "ID")?;
+
+    if !auth.can_access(id) {
+        return Err(error::new_error(1003, "Forbidden", 403));
+    }
+
+    get_organization_by_id(id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    save_organization_settings(
+        id,
+        UpdateOrganizationSettingsDto {
+            require_two_factor: payload.require_two_factor,
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Settings Updated Successfully".to_string(),
+        data: json!(OrganizationSettingsModel {
+            organization_id: id.to_string(),
+            require_two_factor: payload.require_two_factor,
+        }),
+    }))
+}
+
+pub async fn get_provider(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "ID")?;
+
+    if !auth.can_access(id) {
+        return Err(error::new_error(1003, "Forbidden", 403));
+    }
+
+    let provider = match get_identity_provider(id, &state).await {
+        Ok(provider) => provider,
+        Err(_) => {
+            return Err(error::new_error(
+                1004,
+                "Identity Provider Not Configured",
+                404,
+            ))
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Identity Provider Fetched Successfully".to_string(),
+        data: json!(identity_provider_response(provider)),
+    }))
+}
+
+pub async fn save_provider(
+    _req: HttpRequest,
+    auth: AuthContext,
+    id: web::Path<String>,
+    payload: web::Json<SaveIdentityProviderModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "ID")?;
+
+    if !auth.can_access(id) {
+        return Err(error::new_error(1003, "Forbidden", 403));
+    }
+
+    let loopback = state
+        .config
+        .get::<bool>("oidc.allow_insecure_loopback")
+        .unwrap_or(false);
+
+    let issuer = validator::url(&payload.issuer, "Issuer", loopback)?;
+    let client_id = validator::required_str(&payload.client_id, "Client ID")?;
+    let authorization_endpoint = validator::url(
+        &payload.authorization_endpoint,
+        "Authorization Endpoint",
+        loopback,
+    )?;
+    let token_endpoint = validator::url(&payload.token_endpoint, "Token Endpoint", loopback)?;
+    let jwks_uri = payload
+        .jwks_uri
+        .as_deref()
+        .filter(|uri| !uri.is_empty())
+        .map(|uri| validator::url(uri, "JWKS URI", loopback))
+        .transpose()?;
+    let redirect_uri = validator::url(&payload.redirect_uri, "Redirect URI", loopback)?;
+    let scopes = payload
+        .scopes
+        .clone()
+        .unwrap_or("openid email profile".to_string());
+
+    if !scopes.split_whitespace().any(|scope| scope == "openid") {
+        return Err(error::new_error(1002, "Scopes must include openid", 422));
+    }
+
+    get_organization_by_id(id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let provider = save_identity_provider(
+        id,
+        SaveIdentityProviderDto {
+            issuer,
+            client_id,
+            client_secret: payload.client_secret.clone().filter(|s| !s.is_empty()),
+            authorization_endpoint,
+            token_endpoint,
+            jwks_uri,
+            redirect_uri,
+            scopes,
+            is_enabled: payload.is_enabled.unwrap_or(true),
+        },
+        &state,
+    )
+    .await
+    .map_err(error::Error::from_db_err)?;
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "Identity Provider Saved Successfully".to_string(),
+        data: json!(identity_provider_response(provider)),
+    }))
+}
+
+// The client secret is write-only and never leaves the server.
+fn identity_provider_response(
+    provider: entity::identity_providers::Model,
+) -> IdentityProviderModel {
+    IdentityProviderModel {
+        organization_id: provider.organization_id.to_string(),
+        issuer: provider.issuer,
+        client_id: provider.client_id,
+        has_client_secret: provider.client_secret.is_some(),
+        authorization_endpoint: provider.authorization_endpoint,
+        token_endpoint: provider.token_endpoint,
+        jwks_uri: provider.jwks_uri,
+        redirect_uri: provider.redirect_uri,
+        scopes: provider.scopes,
+        is_enabled: provider.is_enabled,
+    }
+}
+
+// Blocking shuts every user of the organization out, including those signed in.
+async fn set_blocked(
+    auth: AuthContext,
+    id: web::Path<String>,
+    is_blocked: bool,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let id = validator::uuid(&id, "ID")?;
+
+    if is_blocked && id == auth.organization_id {
+        return Err(error::new_error(
+            1003,
+            "You Cannot Block Your Own Organization",
+            403,
+        ));
+    }
+
+    let found = set_organization_blocked(id, is_blocked, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    if !found {
+        return Err(error::new_error(1004, "Organization Not Found", 404));
+    }
+
+    if is_blocked {
+        let users = get_users_by_organization(id, &state)
+            .await
+            .map_err(error::Error::from_db_err)?;
+
+        for user in users {
+            revoke_user_sessions(user.id, &state)
+                .await
+                .map_err(error::Error::from_db_err)?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: if is_blocked {
+            "Organization Blocked Successfully".to_string()
+        } else {
+            "Organization Unblocked Successfully".to_string()
+        },
+        data: json!({}),
+    }))
+}
+
+// Decorates organizations with their member counts and the URL of their latest logo.
+async fn organization_responses(
+    organizations: Vec<entity::organization::Model>,
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<OrganizationResponseModel>, error::Error> {
+    let counts: HashMap<uuid::Uuid, i64> = get_member_counts(organization_id, state)
+        .await
+        .map_err(error::Error::from_db_err)?
+        .into_iter()
+        .collect();
+
+    let ids: Vec<uuid::Uuid> = organizations
+        .iter()
+        .map(|organization| organization.id)
+        .collect();
+    let mut logos: HashMap<uuid::Uuid, String> = HashMap::new();
+
+    for logo in get_organization_logos(&ids, state)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        logos
+            .entry(logo.owner_id)
+            .or_insert(format!("/api/v1/media/{}", logo.id));
+    }
+
+    Ok(organizations
+        .into_iter()
+        .map(|organization| OrganizationResponseModel {
+            id: organization.id.to_string(),
+            member_count: counts.get(&organization.id).copied().unwrap_or(0),
+            logo_url: logos.get(&organization.id).cloned(),
+            name: organization.name,
+            email: organization.email,
+            contact: organization.contact,
+            address: organization.address,
+            is_blocked: organization.is_blocked,
+            created_at: organization.created_at.to_rfc3339(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    use actix_web::{http::StatusCode, test};
+
+    use super::create_organization;
+    use crate::{
+        app::organization::models::model::AddOrganizationDto,
+        libs::code::gen_numeric_code,
+        middlewares::role::ADMIN,
+        utils::{
+            models::SaveMemberOrgDto,
+            testing::{random_phone, seed_tenant, test_app, test_state},
+        },
+        AppState,
+    };
+
+    fn signup(phone: &str, gender: &str, date_of_birth: &str) -> actix_http::Request {
+        test::TestRequest::post()
+            .uri("/api/v1/organization/add")
+            .set_json(serde_json::json!({
+                "name": format!("Signup Organization {}", gen_numeric_code(8)),
+                "email": format!("org{}@example.com", gen_numeric_code(8)),
+                "phone": phone,
+                "address": "Test Address",
+                "first_name": "Test",
+                "last_name": "Founder",
+                "member_email": format!("founder{}@example.com", gen_numeric_code(8)),
+                "member_phone": random_phone(),
+                "member_address": "Test Address",
+                "gender": gender,
+                "date_joined": "2020-01-01",
+                "date_of_birth": date_of_birth,
+            }))
+            .to_request()
+    }
+
+    #[actix_web::test]
+    async fn signup_with_a_taken_contact_is_a_conflict() {
+        let state = test_state().await;
+        let existing = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        assert_eq!(
+            test::call_service(
+                &app,
+                signup(&existing.organization.contact, "female", "1990-05-17")
+            )
+            .await
+            .status(),
+            StatusCode::CONFLICT
+        );
+    }
+
+    #[actix_web::test]
+    async fn signup_checks_the_founder_before_saving() {
+        let state = test_state().await;
+        let app = test_app(&state).await;
+
+        for req in [
+            signup(&random_phone(), "Female", "1990-05-17"),
+            signup(&random_phone(), "female", "1899-12-31"),
+        ] {
+            assert_eq!(
+                test::call_service(&app, req).await.status(),
+                StatusCode::UNPROCESSABLE_ENTITY
+            );
+        }
+
+        assert_eq!(
+            test::call_service(&app, signup(&random_phone(), "female", "1990-05-17"))
+                .await
+                .status(),
+            StatusCode::CREATED
+        );
+    }
+
+    #[actix_web::test]
+    async fn signup_racing_past_the_contact_check_is_a_conflict() {
+        let state = test_state().await;
+        let existing = seed_tenant(ADMIN, &state).await;
+
+        let saved = create_organization(
+            AddOrganizationDto {
+                name: format!("Racing Organization {}", gen_numeric_code(8)),
+                email: format!("org{}@example.com", gen_numeric_code(8)),
+                phone: existing.organization.contact.clone(),
+                address: "Test Address".to_string(),
+            },
+            SaveMemberOrgDto {
+                first_name: "Test".to_string(),
+                last_name: "Founder".to_string(),
+                email: None,
+                phone: random_phone(),
+                address: "Test Address".to_string(),
+                gender: "female".to_string(),
+                date_joined: None,
+                date_of_birth: None,
+            },
+            ADMIN,
+            &state,
+        )
+        .await;
+
+        assert_eq!(saved.err().map(|err| err.status), Some(409));
+    }
+
+    #[actix_web::test]
+    async fn signup_leaves_no_organization_when_the_account_fails() {
+        let state = test_state().await;
+        let name = format!("Rolled Back Organization {}", gen_numeric_code(8));
+
+        // Argon2 rejects a zero memory cost, so hashing the founder's password, and with it
+        // provisioning their account, fails after the organization and member are saved
+        let state = actix_web::web::Data::new(AppState {
+            config: config::Config::builder()
+                .add_source(state.config.clone())
+                .set_override("argon2.memory_cost", 0)
+                .and_then(|builder| builder.build())
+                .unwrap(),
+            pg_db: state.pg_db.clone(),
+            sessions: state.sessions.clone(),
+            notifier: state.notifier.clone(),
+            throttle: state.throttle.clone(),
+            common_passwords: state.common_passwords.clone(),
+        });
+
+        let saved = create_organization(
+            AddOrganizationDto {
+                name: name.clone(),
+                email: format!("org{}@example.com", gen_numeric_code(8)),
+                phone: random_phone(),
+                address: "Test Address".to_string(),
+            },
+            SaveMemberOrgDto {
+                first_name: "Test".to_string(),
+                last_name: "Founder".to_string(),
+                email: None,
+                phone: random_phone(),
+                address: "Test Address".to_string(),
+                gender: "female".to_string(),
+                date_joined: None,
+                date_of_birth: None,
+            },
+            ADMIN,
+            &state,
+        )
+        .await;
+
+        assert_eq!(saved.err().map(|err| err.status), Some(500));
+
+        let organizations = entity::organization::Entity::find()
+            .filter(entity::organization::Column::Name.eq(name))
+            .all(state.pg_db.get_ref())
+            .await
+            .unwrap();
+
+        assert!(organizations.is_empty());
+    }
+}
@@ -1,35 +1,78 @@
 use actix_web::web;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DbErr, EntityTrait, InsertResult,
-    QueryFilter, Set,
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
 };
 
 use crate::{
-    app::organization::models::model::{AddOrganizationDto, UpdateOrganizationDto},
-    apply_update_wrap, AppState,
+    app::{
+        members::dto::dtos::save_initial_status,
+        organization::models::model::{
+            AddOrganizationDto, SaveIdentityProviderDto, UpdateOrganizationDto,
+            UpdateOrganizationSettingsDto,
+        },
+    },
+    apply_update_wrap,
+    utils::models::SaveMemberOrgDto,
+    AppState,
 };
 
+// Creates the organization with its founding member and default settings on `db`. The
+// caller runs it in the transaction that also creates the founder's account.
 pub async fn save_organization(
     organization: AddOrganizationDto,
-    state: &web::Data<AppState>,
-) -> Result<InsertResult<entity::organization::ActiveModel>, DbErr> {
-    let data = entity::organization::ActiveModel {
+    member: SaveMemberOrgDto,
+    db: &impl ConnectionTrait,
+) -> Result<(entity::organization::Model, entity::members::Model), DbErr> {
+    let saved_organization = entity::organization::ActiveModel {
         name: Set(organization.name),
         email: Set(Some(organization.email)),
         contact: Set(organization.phone),
         address: Set(organization.address),
         ..Default::default()
-    };
+    }
+    .insert(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        err
+    })?;
 
-    let insertion = entity::organization::Entity::insert(data)
-        .exec(state.pg_db.get_ref())
-        .await
-        .map_err(|err| {
-            eprintln!("Database insert error: {}", err);
-            DbErr::Custom(err.to_string())
-        })?;
+    let saved_member = entity::members::ActiveModel {
+        first_name: Set(member.first_name),
+        last_name: Set(member.last_name),
+        email: Set(member.email),
+        contact: Set(member.phone),
+        residential_address: Set(member.address),
+        organization_id: Set(saved_organization.id),
+        gender: Set(member.gender),
+        date_joined: Set(member.date_joined),
+        date_of_birth: Set(member.date_of_birth.unwrap_or_default()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        err
+    })?;
 
-    Ok(insertion)
+    save_initial_status(&saved_member, db).await?;
+
+    entity::organization_settings::ActiveModel {
+        organization_id: Set(saved_organization.id),
+        require_two_factor: Set(false),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        DbErr::Custom(err.to_string())
+    })?;
+
+    Ok((saved_organization, saved_member))
 }
 
 pub async fn get_organizations(
@@ -128,29 +171,210 @@ pub async fn update_organization(
     Ok(updated_org.into())
 }
 
-pub async fn toggle_block(id: uuid::Uuid, state: &web::Data<AppState>) -> Result<(), DbErr> {
-    let exists = entity::organization::Entity::find_by_id(id)
+// Idempotent like member blocking. Returns false only when no such organization exists.
+pub async fn set_organization_blocked(
+    id: uuid::Uuid,
+    is_blocked: bool,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    let result = entity::organization::Entity::update_many()
+        .col_expr(
+            entity::organization::Column::IsBlocked,
+            Expr::value(is_blocked),
+        )
+        .col_expr(
+            entity::organization::Column::UpdatedAt,
+            Expr::current_timestamp().into(),
+        )
+        .filter(entity::organization::Column::Id.eq(id))
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database update error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(result.rows_affected > 0)
+}
+
+// Unlike get_organization_by_id this also finds blocked organizations.
+pub async fn find_organization_by_id(
+    id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::organization::Model>, DbErr> {
+    let organization = entity::organization::Entity::find_by_id(id)
         .one(state.pg_db.get_ref())
-        .await?;
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
 
-    let exists = match exists {
-        Some(m) => m,
-        None => return Err(DbErr::Custom("Organization not found".to_string())),
+    Ok(organization)
+}
+
+pub async fn get_member_counts(
+    organization_id: Option<uuid::Uuid>,
+    state: &web::Data<AppState>,
+) -> Result<Vec<(uuid::Uuid, i64)>, DbErr> {
+    let counts = entity::members::Entity::find()
+        .select_only()
+        .column(entity::members::Column::OrganizationId)
+        .column_as(entity::members::Column::Id.count(), "member_count")
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::members::Column::OrganizationId.eq(org))
+        })
+        .group_by(entity::members::Column::OrganizationId)
+        .into_tuple()
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(counts)
+}
+
+// Logos are the images an organization uploaded for itself, newest first. Only the
+// given organizations' logos are loaded, not every image in the table.
+pub async fn get_organization_logos(
+    organization_ids: &[uuid::Uuid],
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::media::Model>, DbErr> {
+    let logos = entity::media::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::media::Column::OwnerId.is_in(organization_ids.iter().copied()))
+                .add(
+                    Expr::col(entity::media::Column::OwnerId)
+                        .equals(entity::media::Column::OrganizationId),
+                )
+                .add(entity::media::Column::MediaType.eq("image"))
+                .add(entity::media::Column::IsDeleted.eq(false)),
+        )
+        .order_by_desc(entity::media::Column::CreatedAt)
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(logos)
+}
+
+pub async fn get_organization_settings(
+    organization_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Option<entity::organization_settings::Model>, DbErr> {
+    let settings = entity::organization_settings::Entity::find()
+        .filter(entity::organization_settings::Column::OrganizationId.eq(organization_id))
+        .one(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(settings)
+}
+
+pub async fn save_organization_settings(
+    organization_id: uuid::Uuid,
+    settings: UpdateOrganizationSettingsDto,
+    state: &web::Data<AppState>,
+) -> Result<(), DbErr> {
+    let data = entity::organization_settings::ActiveModel {
+        organization_id: Set(organization_id),
+        require_two_factor: Set(settings.require_two_factor),
+        ..Default::default()
     };
 
-    let mut model: entity::organization::ActiveModel = exists.into();
+    entity::organization_settings::Entity::insert(data)
+        .on_conflict(
+            OnConflict::column(entity::organization_settings::Column::OrganizationId)
+                .update_column(entity::organization_settings::Column::RequireTwoFactor)
+                .value(
+                    entity::organization_settings::Column::UpdatedAt,
+                    Expr::current_timestamp(),
+                )
+                .to_owned(),
+        )
+        .exec(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database insert error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(())
+}
+
+pub async fn get_identity_provider(
+    organization_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<entity::identity_providers::Model, DbErr> {
+    let provider = entity::identity_providers::Entity::find()
+        .filter(entity::identity_providers::Column::OrganizationId.eq(organization_id))
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("Identity provider not found".into()));
 
-    if let ActiveValue::Set(is_blocked) = model.is_blocked {
-        model.is_blocked = ActiveValue::Set(!is_blocked);
+    provider
+}
+
+// A missing client secret on update keeps the stored one, so it never has to be re-sent.
+pub async fn save_identity_provider(
+    organization_id: uuid::Uuid,
+    provider: SaveIdentityProviderDto,
+    state: &web::Data<AppState>,
+) -> Result<entity::identity_providers::Model, DbErr> {
+    let mut update_columns = vec![
+        entity::identity_providers::Column::Issuer,
+        entity::identity_providers::Column::ClientId,
+        entity::identity_providers::Column::AuthorizationEndpoint,
+        entity::identity_providers::Column::TokenEndpoint,
+        entity::identity_providers::Column::JwksUri,
+        entity::identity_providers::Column::RedirectUri,
+        entity::identity_providers::Column::Scopes,
+        entity::identity_providers::Column::IsEnabled,
+    ];
+
+    if provider.client_secret.is_some() {
+        update_columns.push(entity::identity_providers::Column::ClientSecret);
     }
-    model.updated_at = ActiveValue::Set(chrono::Utc::now().into());
 
-    ActiveModelTrait::update(model, state.pg_db.get_ref())
+    let data = entity::identity_providers::ActiveModel {
+        organization_id: Set(organization_id),
+        issuer: Set(provider.issuer),
+        client_id: Set(provider.client_id),
+        client_secret: Set(provider.client_secret),
+        authorization_endpoint: Set(provider.authorization_endpoint),
+        token_endpoint: Set(provider.token_endpoint),
+        jwks_uri: Set(provider.jwks_uri),
+        redirect_uri: Set(provider.redirect_uri),
+        scopes: Set(provider.scopes),
+        is_enabled: Set(provider.is_enabled),
+        ..Default::default()
+    };
+
+    entity::identity_providers::Entity::insert(data)
+        .on_conflict(
+            OnConflict::column(entity::identity_providers::Column::OrganizationId)
+                .update_columns(update_columns)
+                .value(
+                    entity::identity_providers::Column::UpdatedAt,
+                    Expr::current_timestamp(),
+                )
+                .to_owned(),
+        )
+        .exec(state.pg_db.get_ref())
         .await
         .map_err(|err| {
-            eprintln!("Database update error: {}", err);
+            eprintln!("Database insert error: {}", err);
             DbErr::Custom(err.to_string())
         })?;
 
-    Ok(())
+    get_identity_provider(organization_id, state).await
 }
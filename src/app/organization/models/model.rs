@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::app::users::models::model::ProvisionedAccountModel;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AddOrganizationModel {
     pub name: String,
@@ -32,14 +34,90 @@ pub struct UpdateOrganizationDto {
     pub address: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateOrganizationModel {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationResponseModel {
+    pub id: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub contact: String,
+    pub address: String,
+    pub is_blocked: bool,
+    pub member_count: i64,
+    pub logo_url: Option<String>,
+    pub created_at: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreatedResponseModel {
     pub organization: String,
     pub member: String,
+    pub account: ProvisionedAccountModel,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UploadImgModel {
     pub id: String,
     pub data: String,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateOrganizationSettingsModel {
+    pub require_two_factor: bool,
+}
+
+pub struct UpdateOrganizationSettingsDto {
+    pub require_two_factor: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationSettingsModel {
+    pub organization_id: String,
+    pub require_two_factor: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveIdentityProviderModel {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: Option<String>,
+    pub redirect_uri: String,
+    pub scopes: Option<String>,
+    pub is_enabled: Option<bool>,
+}
+
+pub struct SaveIdentityProviderDto {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: Option<String>,
+    pub redirect_uri: String,
+    pub scopes: String,
+    pub is_enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdentityProviderModel {
+    pub organization_id: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub has_client_secret: bool,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: Option<String>,
+    pub redirect_uri: String,
+    pub scopes: String,
+    pub is_enabled: bool,
+}
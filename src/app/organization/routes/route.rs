@@ -1,8 +1,15 @@
 use actix_web::web;
 
 use crate::{
-    app::organization::controllers::controller::{add_organization, get_all, upload_img},
-    middlewares::auth::JwtAuthMiddleware,
+    app::organization::controllers::controller::{
+        add_organization, block_organization, get_all, get_organization, get_profile, get_provider,
+        get_settings, patch_organization, save_provider, unblock_organization, update_settings,
+        upload_img,
+    },
+    middlewares::{
+        auth::JwtAuthMiddleware,
+        role::{RequireRole, ADMIN, SUPER_ADMIN},
+    },
     AppState,
 };
 
@@ -10,7 +17,79 @@ pub fn all_routes(cfg: &mut web::ServiceConfig, state: web::Data<AppState>) {
     cfg.service(
         web::scope("/api/v1/organization")
             .route("/add", web::post().to(add_organization))
-            .route("/get", web::get().to(get_all).wrap(JwtAuthMiddleware))
-            .route("/upload", web::post().to(upload_img).wrap(JwtAuthMiddleware)),
+            .route(
+                "/get",
+                web::get()
+                    .to(get_all)
+                    .wrap(RequireRole::any([SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/upload",
+                web::post()
+                    .to(upload_img)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/profile",
+                web::get().to(get_profile).wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::get()
+                    .to(get_organization)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}",
+                web::patch()
+                    .to(patch_organization)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/block",
+                web::post()
+                    .to(block_organization)
+                    .wrap(RequireRole::any([SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/unblock",
+                web::post()
+                    .to(unblock_organization)
+                    .wrap(RequireRole::any([SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/settings",
+                web::get()
+                    .to(get_settings)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/settings",
+                web::put()
+                    .to(update_settings)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/identity-provider",
+                web::get()
+                    .to(get_provider)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/{id}/identity-provider",
+                web::put()
+                    .to(save_provider)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
     );
 }
@@ -0,0 +1,240 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use sea_orm::ConnectionTrait;
+use serde_json::json;
+
+use crate::{
+    app::{
+        departments::dto::dtos::get_department_by_id,
+        members::dto::dtos::{get_member_by_id, get_members_by_department},
+        users::{
+            dto::dtos::{get_existing_account, save_user},
+            models::model::{
+                BulkProvisionResponseModel, ProvisionDepartmentModel, ProvisionUserModel,
+                ProvisionedAccountModel, SaveUserDto, SkippedMemberModel,
+            },
+        },
+    },
+    libs::{error, jwt::gen_string, pword::hash_password, validator},
+    middlewares::{auth::AuthContext, role::ORGANIZATION_ROLES},
+    utils::models::HttpClientResponse,
+    AppState,
+};
+
+pub async fn provision_user(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<ProvisionUserModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let member_id = validator::uuid(&payload.member_id, "Member ID")?;
+    let role = validator::one_of(&payload.role, &ORGANIZATION_ROLES, "Role")?;
+
+    let member = match get_member_by_id(member_id, auth.scope(), &state).await {
+        Ok(member) => member,
+        Err(_) => {
+            return Ok(HttpResponse::NotFound().json(HttpClientResponse {
+                code: 2001,
+                status: false,
+                message: "Member Not Found".to_string(),
+                data: json!({}),
+            }))
+        }
+    };
+
+    let account = provision_account(&member, &role, state.pg_db.get_ref(), &state).await?;
+
+    Ok(HttpResponse::Created().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: "User Provisioned Successfully".to_string(),
+        data: json!(account),
+    }))
+}
+
+pub async fn provision_department(
+    _req: HttpRequest,
+    auth: AuthContext,
+    payload: web::Json<ProvisionDepartmentModel>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, error::Error> {
+    let department_id = validator::uuid(&payload.department_id, "Department ID")?;
+    let role = validator::one_of(&payload.role, &ORGANIZATION_ROLES, "Role")?;
+
+    let department = get_department_by_id(department_id, auth.scope(), &state)
+        .await
+        .map_err(|_| error::new_error(1004, "Department Not Found", 404))?;
+
+    let members = get_members_by_department(department.organization_id, department.id, &state)
+        .await
+        .map_err(error::Error::from_db_err)?;
+
+    let mut result = BulkProvisionResponseModel {
+        provisioned: vec![],
+        skipped: vec![],
+    };
+
+    for member in members {
+        match provision_account(&member, &role, state.pg_db.get_ref(), &state).await {
+            Ok(account) => result.provisioned.push(account),
+            Err(e) => result.skipped.push(SkippedMemberModel {
+                member_id: member.id.to_string(),
+                reason: e.message,
+            }),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(HttpClientResponse {
+        code: 2000,
+        status: true,
+        message: format!(
+            "Provisioned {} Users, Skipped {}",
+            result.provisioned.len(),
+            result.skipped.len()
+        ),
+        data: json!(result),
+    }))
+}
+
+// Creates a login for the member with a generated password that must be changed on
+// first sign-in. The password is only ever returned here, never stored in clear. The
+// checks and the insert run on `db`, so callers creating the member in a transaction
+// pass it.
+pub async fn provision_account(
+    member: &entity::members::Model,
+    role: &str,
+    db: &impl ConnectionTrait,
+    state: &web::Data<AppState>,
+) -> Result<ProvisionedAccountModel, error::Error> {
+    let contact_taken = error::new_error(
+        1006,
+        &format!("An Account With Contact {} Exists", member.contact),
+        409,
+    );
+
+    if let Some(user) = get_existing_account(member.id, member.organization_id, &member.contact, db)
+        .await
+        .map_err(error::Error::from_db_err)?
+    {
+        if user.member_id == member.id {
+            return Err(error::new_error(1006, "Member Already Has an Account", 409));
+        }
+
+        return Err(contact_taken);
+    }
+
+    let temporary_password = gen_string(12);
+    let password = hash_password(&temporary_password, &state.config)?;
+
+    let user = save_user(
+        SaveUserDto {
+            member_id: member.id,
+            organization_id: member.organization_id,
+            email: member.email.clone(),
+            contact: member.contact.clone(),
+            password,
+            role: role.to_string(),
+        },
+        db,
+    )
+    .await
+    .map_err(|err| error::Error::from_db_err_or_conflict(err, &contact_taken.message))?;
+
+    Ok(ProvisionedAccountModel {
+        user_id: user.id.to_string(),
+        member_id: member.id.to_string(),
+        username: member.contact.clone(),
+        role: role.to_string(),
+        temporary_password,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test};
+
+    use crate::{
+        app::members::{dto::dtos::save_member, models::model::AddMemberDto},
+        middlewares::role::ADMIN,
+        utils::testing::{bearer, seed_tenant, test_app, test_state, TENANT_PASSWORD},
+    };
+
+    #[actix_web::test]
+    async fn admin_cannot_provision_other_organization_member() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/provision")
+            .insert_header(bearer(&org_a.token))
+            .set_json(serde_json::json!({
+                "member_id": org_b.member.id.to_string(),
+                "role": ADMIN,
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn contact_can_hold_an_account_in_each_organization() {
+        let state = test_state().await;
+        let org_a = seed_tenant(ADMIN, &state).await;
+        let org_b = seed_tenant(ADMIN, &state).await;
+        let app = test_app(&state).await;
+
+        // org A's founder also worships with org B
+        let member = save_member(
+            AddMemberDto {
+                first_name: "Shared".to_string(),
+                last_name: "Member".to_string(),
+                email: None,
+                phone: org_a.user.contact.clone(),
+                organization_id: org_b.organization.id,
+                address: "Test Address".to_string(),
+                gender: "female".to_string(),
+                date_joined: None,
+                date_of_birth: None,
+                added_by: None,
+                status: "member".to_string(),
+            },
+            state.pg_db.get_ref(),
+        )
+        .await
+        .unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/provision")
+            .insert_header(bearer(&org_b.token))
+            .set_json(serde_json::json!({
+                "member_id": member.id.to_string(),
+                "role": ADMIN,
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // the contact alone no longer names one account, the organization picks it
+        let login = |organization_id: Option<String>| {
+            test::TestRequest::post()
+                .uri("/api/v1/auth/login")
+                .set_json(serde_json::json!({
+                    "username": org_a.user.contact,
+                    "password": TENANT_PASSWORD,
+                    "organization_id": organization_id,
+                }))
+                .to_request()
+        };
+
+        let resp = test::call_service(&app, login(None)).await;
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = test::call_service(&app, login(Some(org_a.organization.id.to_string()))).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,104 @@
+use actix_web::web;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, QueryFilter, Set,
+};
+
+use crate::{app::users::models::model::SaveUserDto, AppState};
+
+// Takes the connection to insert on, so the account can be created inside a caller's
+// transaction.
+pub async fn save_user(
+    data: SaveUserDto,
+    db: &impl ConnectionTrait,
+) -> Result<entity::users::Model, DbErr> {
+    let user = entity::users::ActiveModel {
+        member_id: Set(data.member_id),
+        organization_id: Set(data.organization_id),
+        email: Set(data.email),
+        contact: Set(data.contact),
+        password: Set(data.password),
+        role: Set(data.role),
+        is_password_changed: Set(false),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database insert error: {}", err);
+        err
+    })?;
+
+    Ok(user)
+}
+
+// An account already held by the member or by their contact within the organization.
+// Reads through `db`, so inside the transaction creating the member it sees that
+// transaction's rows.
+pub async fn get_existing_account(
+    member_id: uuid::Uuid,
+    organization_id: uuid::Uuid,
+    contact: &str,
+    db: &impl ConnectionTrait,
+) -> Result<Option<entity::users::Model>, DbErr> {
+    entity::users::Entity::find()
+        .filter(
+            Condition::any()
+                .add(entity::users::Column::MemberId.eq(member_id))
+                .add(
+                    Condition::all()
+                        .add(entity::users::Column::OrganizationId.eq(organization_id))
+                        .add(entity::users::Column::Contact.eq(contact)),
+                ),
+        )
+        .one(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })
+}
+
+pub async fn get_user_by_member_id(
+    member_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<entity::users::Model, DbErr> {
+    let user = entity::users::Entity::find()
+        .filter(entity::users::Column::MemberId.eq(member_id))
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("User not found".into()));
+
+    user
+}
+
+pub async fn get_user_by_contact(
+    contact: &String,
+    organization_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<entity::users::Model, DbErr> {
+    let user = entity::users::Entity::find()
+        .filter(entity::users::Column::Contact.eq(contact))
+        .filter(entity::users::Column::OrganizationId.eq(organization_id))
+        .one(state.pg_db.get_ref())
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("User not found".into()));
+
+    user
+}
+
+pub async fn get_users_by_organization(
+    organization_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::users::Model>, DbErr> {
+    let users = entity::users::Entity::find()
+        .inner_join(entity::members::Entity)
+        .filter(entity::members::Column::OrganizationId.eq(organization_id))
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(users)
+}
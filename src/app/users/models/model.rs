@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvisionUserModel {
+    pub member_id: String,
+    pub role: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvisionDepartmentModel {
+    pub department_id: String,
+    pub role: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveUserDto {
+    pub member_id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    pub email: Option<String>,
+    pub contact: String,
+    pub password: String,
+    pub role: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvisionedAccountModel {
+    pub user_id: String,
+    pub member_id: String,
+    pub username: String,
+    pub role: String,
+    pub temporary_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedMemberModel {
+    pub member_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkProvisionResponseModel {
+    pub provisioned: Vec<ProvisionedAccountModel>,
+    pub skipped: Vec<SkippedMemberModel>,
+}
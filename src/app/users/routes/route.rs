@@ -0,0 +1,30 @@
+use actix_web::web;
+
+use crate::{
+    app::users::controllers::controller::{provision_department, provision_user},
+    middlewares::{
+        auth::JwtAuthMiddleware,
+        role::{RequireRole, ADMIN, SUPER_ADMIN},
+    },
+    AppState,
+};
+
+pub fn all_routes(cfg: &mut web::ServiceConfig, _state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/api/v1/users")
+            .route(
+                "/provision",
+                web::post()
+                    .to(provision_user)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            )
+            .route(
+                "/provision/department",
+                web::post()
+                    .to(provision_department)
+                    .wrap(RequireRole::any([ADMIN, SUPER_ADMIN]))
+                    .wrap(JwtAuthMiddleware),
+            ),
+    );
+}
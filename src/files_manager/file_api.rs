@@ -1,7 +1,8 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 
 use crate::{
-    libs::{error, pword::parse_uuid},
+    libs::{error, validator},
+    middlewares::auth::{AuthContext, JwtAuthMiddleware},
     utils::{
         file_methods::{file_exists, read_file},
         shared::get_media_by_id,
@@ -11,12 +12,13 @@ use crate::{
 
 async fn req_read_file(
     _req: HttpRequest,
+    auth: AuthContext,
     id: web::Path<String>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, error::Error> {
-    let parsed_id = parse_uuid(&id);
+    let parsed_id = validator::uuid(&id, "ID")?;
 
-    let media = get_media_by_id(parsed_id, &state).await;
+    let media = get_media_by_id(parsed_id, auth.scope(), &state).await;
 
     match media {
         Ok(m) => {
@@ -39,6 +41,6 @@ async fn req_read_file(
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::resource("api/v1/media/{id}")
-        .route(web::get().to(req_read_file))
+            .route(web::get().to(req_read_file).wrap(JwtAuthMiddleware)),
     );
 }
@@ -0,0 +1,80 @@
+use actix_web::{web, HttpRequest};
+use sea_orm::{sea_query::Expr, ColumnTrait, Condition, DbErr, EntityTrait, QueryFilter};
+
+use crate::{
+    libs::jwt::{gen_string, hash_token},
+    AppState,
+};
+
+pub struct GeneratedApiKey {
+    pub key: String,
+    pub prefix: String,
+    pub hash: String,
+}
+
+// The prefix is kept in clear so admins can tell keys apart without the secret part.
+pub fn generate_api_key(state: &web::Data<AppState>) -> GeneratedApiKey {
+    let name = state.config.get::<String>("app.name").unwrap();
+    let key = format!("{}_{}", name, gen_string(40));
+
+    GeneratedApiKey {
+        prefix: key[..name.len() + 9].to_string(),
+        hash: hash_token(&key),
+        key,
+    }
+}
+
+pub fn read_api_key(req: &HttpRequest) -> Option<String> {
+    let header = req.headers().get("Authorization")?.to_str().ok()?;
+
+    header
+        .strip_prefix("ApiKey ")
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+// Returns the key together with the user who created it, as long as it is neither
+// revoked nor expired. A key acts on its creator's behalf, so once they are deleted it
+// stays listed for the organization to replace but no longer signs anything in.
+pub async fn resolve_api_key(
+    key: &str,
+    state: &web::Data<AppState>,
+) -> Result<Option<(entity::api_keys::Model, entity::users::Model)>, DbErr> {
+    let found = entity::api_keys::Entity::find()
+        .find_also_related(entity::users::Entity)
+        .filter(
+            Condition::all()
+                .add(entity::api_keys::Column::KeyHash.eq(hash_token(key)))
+                .add(entity::api_keys::Column::IsRevoked.eq(false))
+                .add(
+                    Condition::any()
+                        .add(entity::api_keys::Column::ExpiresAt.is_null())
+                        .add(entity::api_keys::Column::ExpiresAt.gt(chrono::Utc::now())),
+                ),
+        )
+        .one(state.pg_db.get_ref())
+        .await?;
+
+    let (api_key, user) = match found {
+        Some((api_key, Some(user))) => (api_key, user),
+        _ => return Ok(None),
+    };
+
+    // kiosks poll constantly, so last use is only written about once a minute
+    let stale = api_key
+        .last_used_at
+        .is_none_or(|at| chrono::Utc::now() - at.to_utc() > chrono::Duration::seconds(60));
+
+    if stale {
+        entity::api_keys::Entity::update_many()
+            .col_expr(
+                entity::api_keys::Column::LastUsedAt,
+                Expr::current_timestamp().into(),
+            )
+            .filter(entity::api_keys::Column::Id.eq(api_key.id))
+            .exec(state.pg_db.get_ref())
+            .await?;
+    }
+
+    Ok(Some((api_key, user)))
+}
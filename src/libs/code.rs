@@ -0,0 +1,17 @@
+use rand::{rng, Rng};
+
+// Digits only, so the code can be read out of an SMS and typed on any keypad.
+pub fn gen_numeric_code(size: usize) -> String {
+    (0..size)
+        .map(|_| char::from(b'0' + rng().random_range(0..10u8)))
+        .collect()
+}
+
+// Compares secrets without returning early, so timing does not reveal how much matched.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
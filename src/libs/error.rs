@@ -1,10 +1,10 @@
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use actix_http::body::{BoxBody, EitherBody};
 use actix_http::StatusCode;
 use actix_web::{dev, http, middleware::ErrorHandlerResponse, HttpResponse, ResponseError, Result};
-use sea_orm::DbErr;
-use serde_json::{json, to_string_pretty};
+use sea_orm::{DbErr, SqlErr};
 use serde::Serialize;
-use actix_http::body::{EitherBody, BoxBody};
+use serde_json::{json, to_string_pretty};
+use std::fmt::{Display, Formatter, Result as FmtResult};
 
 #[derive(Debug, Serialize)]
 pub struct Error {
@@ -21,6 +21,17 @@ impl Error {
             status: 500,
         }
     }
+
+    // A unique index rejecting a write means a concurrent request claimed the value
+    // after our own check passed, which is a conflict rather than a server error.
+    // DTOs whose errors are read this way return the DbErr as is rather than wrapping it
+    // in DbErr::Custom, which would lose the constraint that failed.
+    pub fn from_db_err_or_conflict(e: DbErr, message: &str) -> Self {
+        match e.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => new_error(1006, message, 409),
+            _ => Error::from_db_err(e),
+        }
+    }
 }
 
 impl Display for Error {
@@ -51,7 +62,9 @@ pub fn render_404<B>(mut res: dev::ServiceResponse<B>) -> Result<ErrorHandlerRes
     );
 
     let new_res = res.map_body(|_, _| {
-        EitherBody::left(BoxBody::new("{\"code\": 404, \"message\": \"Not Found\", \"status\": 2002 }"))
+        EitherBody::left(BoxBody::new(
+            "{\"code\": 404, \"message\": \"Not Found\", \"status\": 2002 }",
+        ))
     });
     Ok(ErrorHandlerResponse::Response(new_res))
 }
@@ -63,7 +76,9 @@ pub fn render_405<B>(mut res: dev::ServiceResponse<B>) -> Result<ErrorHandlerRes
     );
 
     let new_res = res.map_body(|_, _| {
-        EitherBody::left(BoxBody::new("{\"code\": 405, \"message\": \"Method Not Allowed\", \"status\": 2002}"))
+        EitherBody::left(BoxBody::new(
+            "{\"code\": 405, \"message\": \"Method Not Allowed\", \"status\": 2002}",
+        ))
     });
     Ok(ErrorHandlerResponse::Response(new_res))
 }
@@ -75,7 +90,9 @@ pub fn render_500<B>(mut res: dev::ServiceResponse<B>) -> Result<ErrorHandlerRes
     );
 
     let new_res = res.map_body(|_, _| {
-        EitherBody::left(BoxBody::new("{\"code\": 500, \"message\": \"Internal Server Error\", \"status\": 2002}"))
+        EitherBody::left(BoxBody::new(
+            "{\"code\": 500, \"message\": \"Internal Server Error\", \"status\": 2002}",
+        ))
     });
     Ok(ErrorHandlerResponse::Response(new_res))
 }
@@ -87,7 +104,9 @@ pub fn render_400<B>(mut res: dev::ServiceResponse<B>) -> Result<ErrorHandlerRes
     );
 
     let new_res = res.map_body(|_, _| {
-        EitherBody::left(BoxBody::new("{\"code\": 400, \"message\": \"Bad Request\", \"status\": 2002}"))
+        EitherBody::left(BoxBody::new(
+            "{\"code\": 400, \"message\": \"Bad Request\", \"status\": 2002}",
+        ))
     });
     Ok(ErrorHandlerResponse::Response(new_res))
-}
\ No newline at end of file
+}
@@ -0,0 +1,332 @@
+use std::io::Cursor;
+
+use printpdf::{image_crate, Image, ImageTransform, Mm, PdfDocument};
+use rust_xlsxwriter::{Format, Workbook};
+use ttf_parser::Face;
+
+use crate::libs::error;
+
+// A4 portrait, in millimetres.
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const HEADER_HEIGHT: f32 = 12.0;
+const ENTRY_HEIGHT: f32 = 28.0;
+const PHOTO_SIZE: f32 = 22.0;
+const NAME_SIZE: f32 = 11.0;
+const DETAIL_SIZE: f32 = 9.0;
+const DETAIL_LINES: usize = 4;
+
+// The builtin PDF fonts only cover Latin-1, so a Unicode font is embedded instead.
+const REGULAR_FONT: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("fonts/DejaVuSans-Bold.ttf");
+
+pub struct DirectoryEntry {
+    pub name: String,
+    pub details: Vec<String>,
+    pub photo: Option<Vec<u8>>,
+}
+
+pub fn to_csv(headers: &[&str], rows: &[Vec<String>]) -> Result<Vec<u8>, error::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    writer.write_record(headers).map_err(|_| render_error())?;
+
+    for row in rows {
+        writer
+            .write_record(row.iter().map(|value| csv_cell(value)))
+            .map_err(|_| render_error())?;
+    }
+
+    writer.into_inner().map_err(|_| render_error())
+}
+
+// Spreadsheet apps run a CSV cell starting with one of these as a formula, so member
+// supplied text is prefixed with a quote to keep it a plain value.
+fn csv_cell(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+// Every cell is written as text so contacts keep their leading zeros.
+pub fn to_xlsx(headers: &[&str], rows: &[Vec<String>]) -> Result<Vec<u8>, error::Error> {
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+    let sheet = workbook.add_worksheet();
+
+    for (col, header) in headers.iter().enumerate() {
+        sheet
+            .write_string_with_format(0, col as u16, *header, &bold)
+            .map_err(|_| render_error())?;
+    }
+
+    for (row, values) in rows.iter().enumerate() {
+        for (col, value) in values.iter().enumerate() {
+            sheet
+                .write_string(row as u32 + 1, col as u16, value)
+                .map_err(|_| render_error())?;
+        }
+    }
+
+    sheet.set_freeze_panes(1, 0).map_err(|_| render_error())?;
+
+    workbook.save_to_buffer().map_err(|_| render_error())
+}
+
+// Lays entries out one under another with a title and page numbers on every page.
+// When any entry has a photo, text is indented on all of them so the columns line up.
+pub fn to_pdf(title: &str, entries: &[DirectoryEntry]) -> Result<Vec<u8>, error::Error> {
+    let per_page = ((PAGE_HEIGHT - 2.0 * MARGIN - HEADER_HEIGHT) / ENTRY_HEIGHT) as usize;
+    let pages: Vec<&[DirectoryEntry]> = if entries.is_empty() {
+        vec![&[]]
+    } else {
+        entries.chunks(per_page).collect()
+    };
+    let indent = if entries.iter().any(|entry| entry.photo.is_some()) {
+        PHOTO_SIZE + 5.0
+    } else {
+        0.0
+    };
+
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Directory");
+    let font = doc
+        .add_external_font(Cursor::new(REGULAR_FONT))
+        .map_err(|_| render_error())?;
+    let bold = doc
+        .add_external_font(Cursor::new(BOLD_FONT))
+        .map_err(|_| render_error())?;
+    let regular_face = Face::parse(REGULAR_FONT, 0).map_err(|_| render_error())?;
+    let bold_face = Face::parse(BOLD_FONT, 0).map_err(|_| render_error())?;
+    let text_width = PAGE_WIDTH - 2.0 * MARGIN - indent;
+
+    for (number, page) in pages.iter().enumerate() {
+        let layer = if number == 0 {
+            doc.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Directory");
+            doc.get_page(page).get_layer(layer)
+        };
+
+        layer.use_text(title, 16.0, Mm(MARGIN), Mm(PAGE_HEIGHT - MARGIN), &bold);
+        layer.use_text(
+            format!("Page {} of {}", number + 1, pages.len()),
+            8.0,
+            Mm(MARGIN),
+            Mm(MARGIN / 2.0),
+            &font,
+        );
+
+        let mut top = PAGE_HEIGHT - MARGIN - HEADER_HEIGHT;
+
+        for entry in page.iter() {
+            if let Some(photo) = entry
+                .photo
+                .as_ref()
+                .and_then(|bytes| image_crate::load_from_memory(bytes).ok())
+            {
+                // printpdf can't embed alpha channels, and a thumbnail keeps the file small
+                let thumbnail = photo.thumbnail(300, 300).to_rgb8();
+                let size = thumbnail.width().max(thumbnail.height()) as f32;
+
+                Image::from_dynamic_image(&image_crate::DynamicImage::ImageRgb8(thumbnail))
+                    .add_to_layer(
+                        layer.clone(),
+                        ImageTransform {
+                            translate_x: Some(Mm(MARGIN)),
+                            translate_y: Some(Mm(top - PHOTO_SIZE)),
+                            dpi: Some(size * 25.4 / PHOTO_SIZE),
+                            ..Default::default()
+                        },
+                    );
+            }
+
+            layer.use_text(
+                truncate(&bold_face, &entry.name, NAME_SIZE, text_width),
+                NAME_SIZE,
+                Mm(MARGIN + indent),
+                Mm(top - 4.0),
+                &bold,
+            );
+
+            let lines = wrap(&regular_face, &entry.details, DETAIL_SIZE, text_width);
+
+            for (line, detail) in lines.iter().enumerate() {
+                layer.use_text(
+                    detail,
+                    DETAIL_SIZE,
+                    Mm(MARGIN + indent),
+                    Mm(top - 9.0 - line as f32 * 4.5),
+                    &font,
+                );
+            }
+
+            top -= ENTRY_HEIGHT;
+        }
+    }
+
+    doc.save_to_bytes().map_err(|_| render_error())
+}
+
+// Breaks the details into lines that fit the width, splitting words too long for a
+// line of their own. Whatever doesn't fit in the entry is cut off with an ellipsis.
+fn wrap(face: &Face, details: &[String], size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for detail in details {
+        let mut line = String::new();
+
+        for word in detail.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+
+            if text_width(face, &candidate, size) <= width {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            for c in word.chars() {
+                line.push(c);
+
+                if text_width(face, &line, size) > width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+
+        lines.push(line);
+    }
+
+    if lines.len() > DETAIL_LINES {
+        lines.truncate(DETAIL_LINES);
+        lines[DETAIL_LINES - 1] = ellipsize(face, &lines[DETAIL_LINES - 1], size, width);
+    }
+
+    lines
+}
+
+fn truncate(face: &Face, text: &str, size: f32, width: f32) -> String {
+    if text_width(face, text, size) <= width {
+        text.to_string()
+    } else {
+        ellipsize(face, text, size, width)
+    }
+}
+
+fn ellipsize(face: &Face, text: &str, size: f32, width: f32) -> String {
+    let mut line = text.to_string();
+
+    while !line.is_empty() && text_width(face, &format!("{}\u{2026}", line), size) > width {
+        line.pop();
+    }
+
+    format!("{}\u{2026}", line.trim_end())
+}
+
+// Width in millimetres of the text set at the given point size.
+fn text_width(face: &Face, text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            face.glyph_index(c)
+                .and_then(|glyph| face.glyph_hor_advance(glyph))
+                .unwrap_or(0) as u32
+        })
+        .sum();
+
+    units as f32 / face.units_per_em() as f32 * size * 25.4 / 72.0
+}
+
+fn render_error() -> error::Error {
+    error::new_error(2001, "Export could not be generated", 500)
+}
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::Face;
+
+    use super::{text_width, to_csv, to_pdf, wrap, DirectoryEntry, DETAIL_LINES, REGULAR_FONT};
+
+    #[test]
+    fn csv_neutralizes_formula_cells() {
+        let rows = vec![vec![
+            "=HYPERLINK(\"http://evil\")".to_string(),
+            "+233241234567".to_string(),
+            "-1".to_string(),
+            "@SUM(A1)".to_string(),
+            "\tTab".to_string(),
+            "\rReturn".to_string(),
+            "Kwame".to_string(),
+        ]];
+
+        let csv = String::from_utf8(to_csv(&["a", "b", "c", "d", "e", "f", "g"], &rows).unwrap())
+            .unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let record = reader.records().next().unwrap().unwrap();
+
+        assert_eq!(
+            record.iter().collect::<Vec<_>>(),
+            vec![
+                "'=HYPERLINK(\"http://evil\")",
+                "'+233241234567",
+                "'-1",
+                "'@SUM(A1)",
+                "'\tTab",
+                "'\rReturn",
+                "Kwame",
+            ]
+        );
+    }
+
+    #[test]
+    fn pdf_details_wrap_to_the_page_width() {
+        let face = Face::parse(REGULAR_FONT, 0).unwrap();
+        let address = "Plot 14, Nii Okaiman Street, off the Achimota Forest Road, ".repeat(3);
+
+        let lines = wrap(&face, std::slice::from_ref(&address), 9.0, 80.0);
+
+        assert!(lines.len() > 1);
+        assert!(lines
+            .iter()
+            .all(|line| text_width(&face, line, 9.0) <= 80.0));
+        assert_eq!(lines.join(" "), address.trim_end());
+
+        let lines = wrap(&face, &[address.repeat(4)], 9.0, 80.0);
+
+        assert_eq!(lines.len(), DETAIL_LINES);
+        assert!(lines.last().unwrap().ends_with('\u{2026}'));
+        assert!(text_width(&face, lines.last().unwrap(), 9.0) <= 80.0);
+    }
+
+    #[test]
+    fn pdf_renders_names_outside_latin_1() {
+        let face = Face::parse(REGULAR_FONT, 0).unwrap();
+        assert!("Ɔkɔmfoɔ Ŋɔnɔ Σοφία"
+            .chars()
+            .all(|c| c == ' ' || face.glyph_index(c).is_some()));
+
+        let pdf = to_pdf(
+            "Directory",
+            &[DirectoryEntry {
+                name: "Ɔkɔmfoɔ Ŋɔnɔ Σοφία".to_string(),
+                details: vec!["Dzorwulu, Accra".to_string()],
+                photo: None,
+            }],
+        )
+        .unwrap();
+
+        assert!(pdf.starts_with(b"%PDF"));
+        assert!(pdf.windows(8).any(|window| window == b"FontFile"));
+    }
+}
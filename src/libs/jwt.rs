@@ -5,24 +5,50 @@ use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header,
 use rand::distr::Alphanumeric;
 use rand::{rng, Rng};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::libs::error;
 use crate::AppState;
 
+pub const SCOPE_FULL: &str = "full";
+pub const SCOPE_PASSWORD_CHANGE: &str = "password_change";
+pub const SCOPE_MFA: &str = "mfa";
+pub const SCOPE_MFA_ENROLL: &str = "mfa_enroll";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub iat: usize,
     pub exp: usize,
     pub jid: String,
     pub id: String,
-    pub _id: String,
+    pub member_id: String,
+    pub organization_id: String,
+    pub role: String,
+    pub scope: String,
+}
+
+pub struct JwtDto {
+    pub user_id: uuid::Uuid,
+    pub member_id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    pub role: String,
+    pub session_id: uuid::Uuid,
+    pub scope: String,
 }
 
 pub struct Token {
     pub token: String,
+    pub expires_in: i64,
 }
 
-fn gen_string(size: usize) -> String {
+pub struct RefreshToken {
+    pub token: String,
+    pub hash: String,
+    pub expires_in: i64,
+    pub expires_at: chrono::DateTime<Utc>,
+}
+
+pub fn gen_string(size: usize) -> String {
     rng()
         .sample_iter(&Alphanumeric)
         .take(size)
@@ -30,23 +56,28 @@ fn gen_string(size: usize) -> String {
         .collect()
 }
 
-pub async fn create_jwt(user_id: String, state: &web::Data<AppState>) -> Token {
+pub async fn create_jwt(data: JwtDto, state: &web::Data<AppState>) -> Token {
     dotenv().unwrap();
 
-    let expire = state.config.get::<i64>("jwt.access_expire").unwrap();
+    let expire = match data.scope.as_str() {
+        SCOPE_FULL => state.config.get::<i64>("jwt.access_expire").unwrap(),
+        _ => state.config.get::<i64>("jwt.restricted_expire").unwrap(),
+    };
 
     let jwt_key = std::env::var("secret_key").unwrap();
 
     let created = Utc::now();
     let expiry = Utc::now() + Duration::seconds(expire);
-    let jid = uuid::Uuid::new_v4();
 
     let claim = Claims {
         iat: created.timestamp() as usize,
         exp: expiry.timestamp() as usize,
-        jid: jid.to_string(),
-        id: user_id,
-        _id: gen_string(32),
+        jid: data.session_id.to_string(),
+        id: data.user_id.to_string(),
+        member_id: data.member_id.to_string(),
+        organization_id: data.organization_id.to_string(),
+        role: data.role,
+        scope: data.scope,
     };
 
     let token = encode(
@@ -56,7 +87,27 @@ pub async fn create_jwt(user_id: String, state: &web::Data<AppState>) -> Token {
     )
     .unwrap();
 
-    Token { token }
+    Token {
+        token,
+        expires_in: expire,
+    }
+}
+
+pub fn create_refresh_token(state: &web::Data<AppState>) -> RefreshToken {
+    let expire = state.config.get::<i64>("jwt.refresh_expire").unwrap();
+
+    let token = gen_string(64);
+
+    RefreshToken {
+        hash: hash_token(&token),
+        token,
+        expires_in: expire,
+        expires_at: Utc::now() + Duration::seconds(expire),
+    }
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
 }
 
 pub fn parse_token(token: &str) -> Result<Claims, error::Error> {
@@ -76,9 +127,7 @@ pub fn parse_token(token: &str) -> Result<Claims, error::Error> {
     Ok(claims)
 }
 
-pub async fn verify_jwt(
-    req: &HttpRequest
-) -> Result<Claims, error::Error> {
+pub async fn verify_jwt(req: &HttpRequest) -> Result<Claims, error::Error> {
     let token = match req.headers().get("Authorization") {
         None => return Err(error::new_error(1001, "Authentication failure", 401)),
         Some(v) => {
@@ -1,5 +1,15 @@
+pub mod api_key;
+pub mod code;
 pub mod error;
+pub mod export;
 pub mod ip;
-pub mod validator;
 pub mod jwt;
-pub mod pword;
\ No newline at end of file
+pub mod notifier;
+pub mod oidc;
+pub mod pagination;
+pub mod pword;
+pub mod session;
+pub mod spreadsheet;
+pub mod throttle;
+pub mod totp;
+pub mod validator;
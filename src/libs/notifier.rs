@@ -0,0 +1,49 @@
+use std::{fs::OpenOptions, io::Write, sync::Arc};
+
+use config::Config;
+
+pub trait Notifier: Send + Sync {
+    fn send(&self, recipient: &str, subject: &str, body: &str) -> Result<(), std::io::Error>;
+}
+
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn send(&self, recipient: &str, subject: &str, body: &str) -> Result<(), std::io::Error> {
+        log::info!("notification to {} [{}]: {}", recipient, subject, body);
+        Ok(())
+    }
+}
+
+pub struct FileNotifier {
+    path: String,
+}
+
+impl Notifier for FileNotifier {
+    fn send(&self, recipient: &str, subject: &str, body: &str) -> Result<(), std::io::Error> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(
+            file,
+            "{}\t{}\t{}\t{}",
+            chrono::Utc::now().to_rfc3339(),
+            recipient,
+            subject,
+            body
+        )
+    }
+}
+
+pub fn build_notifier(settings: &Config) -> Arc<dyn Notifier> {
+    let kind = settings.get::<String>("notifier.kind").unwrap();
+
+    match kind.as_str() {
+        "file" => Arc::new(FileNotifier {
+            path: settings.get::<String>("notifier.file_path").unwrap(),
+        }),
+        _ => Arc::new(LogNotifier),
+    }
+}
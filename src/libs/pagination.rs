@@ -0,0 +1,31 @@
+use actix_web::web;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{libs::error, AppState};
+
+// Clamps a requested page size to the configured maximum.
+pub fn page_limit(limit: Option<u64>, state: &web::Data<AppState>) -> Result<u64, error::Error> {
+    let default_limit = state.config.get::<u64>("pagination.default_limit").unwrap();
+    let max_limit = state.config.get::<u64>("pagination.max_limit").unwrap();
+
+    match limit {
+        Some(0) => Err(error::new_error(1002, "Limit must be positive", 422)),
+        Some(limit) => Ok(limit.min(max_limit)),
+        None => Ok(default_limit),
+    }
+}
+
+// Cursors are opaque to clients: the position of the last row seen, serialized and
+// base64 encoded so it can travel in a query string.
+pub fn encode_cursor<T: Serialize>(position: &T) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(position).unwrap())
+}
+
+pub fn decode_cursor<T: DeserializeOwned>(cursor: &str) -> Result<T, error::Error> {
+    general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| error::new_error(1002, "Cursor is invalid", 422))
+}
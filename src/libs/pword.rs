@@ -1,27 +1,232 @@
+use std::collections::HashSet;
+
+use actix_web::web;
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use config::Config;
+use rand::{rng, Rng};
 use sha2::{Digest, Sha512};
 
-pub fn salt() -> uuid::Uuid {
-    uuid::Uuid::new_v4()
+use crate::{
+    libs::{code::constant_time_eq, error},
+    AppState,
+};
+
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+
+// The bundled list only holds the most notorious passwords. Deployments should point
+// `password.common_list` at a full breach list (one password per line, e.g. the top
+// 10k), which then replaces it.
+pub struct CommonPasswords(HashSet<String>);
+
+impl CommonPasswords {
+    pub fn from_config(settings: &Config) -> Self {
+        let list = match settings.get::<String>("password.common_list") {
+            Ok(path) if !path.is_empty() => std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("Failed to read common password list {}: {}", path, e)),
+            _ => COMMON_PASSWORDS.to_string(),
+        };
+
+        CommonPasswords(
+            list.lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect(),
+        )
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.0.contains(&password.to_lowercase())
+    }
+}
+
+pub struct PasswordCheck {
+    pub valid: bool,
+    pub needs_rehash: bool,
+}
+
+fn argon2_hasher(settings: &Config) -> Result<Argon2<'static>, error::Error> {
+    let memory_cost = settings.get::<u32>("argon2.memory_cost").unwrap();
+    let time_cost = settings.get::<u32>("argon2.time_cost").unwrap();
+    let parallelism = settings.get::<u32>("argon2.parallelism").unwrap();
+
+    let params = Params::new(memory_cost, time_cost, parallelism, None)
+        .map_err(|e| error::new_error(2001, &e.to_string(), 500))?;
+
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+pub fn hash_password(password: &str, settings: &Config) -> Result<String, error::Error> {
+    let salt = SaltString::encode_b64(&rng().random::<[u8; 16]>())
+        .map_err(|e| error::new_error(2001, &e.to_string(), 500))?;
+
+    let hash = argon2_hasher(settings)?
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| error::new_error(2001, &e.to_string(), 500))?;
+
+    Ok(hash.to_string())
+}
+
+// Accounts created before Argon2 carry the md5+sha512 hash that the old
+// `encrypt_password(password, salt)` produced. It took a uuid salt, but the users table
+// never had a column for one, so the only uuid stored with the row, its id, is what the
+// fallback can use. Rows salted with anything else cannot be verified and need a
+// password reset. Legacy hashes that verify are flagged so the caller can upgrade them.
+pub fn verify_password(
+    password: &str,
+    legacy_salt: &uuid::Uuid,
+    hash: &str,
+    settings: &Config,
+) -> PasswordCheck {
+    let parsed = match PasswordHash::new(hash) {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            return PasswordCheck {
+                valid: constant_time_eq(
+                    legacy_hash(password, legacy_salt).as_bytes(),
+                    hash.as_bytes(),
+                ),
+                needs_rehash: true,
+            }
+        }
+    };
+
+    let hasher = match argon2_hasher(settings) {
+        Ok(hasher) => hasher,
+        Err(_) => {
+            return PasswordCheck {
+                valid: false,
+                needs_rehash: false,
+            }
+        }
+    };
+
+    let valid = hasher.verify_password(password.as_bytes(), &parsed).is_ok();
+
+    let needs_rehash = match Params::try_from(&parsed) {
+        Ok(params) => {
+            parsed.algorithm != Algorithm::Argon2id.ident()
+                || params.m_cost() != hasher.params().m_cost()
+                || params.t_cost() != hasher.params().t_cost()
+                || params.p_cost() != hasher.params().p_cost()
+        }
+        Err(_) => true,
+    };
+
+    PasswordCheck {
+        valid,
+        needs_rehash,
+    }
 }
 
-pub fn parse_uuid(id: &str) -> uuid::Uuid {
-    uuid::Uuid::parse_str(id).expect("Invalid UUID string")
+pub fn check_password_policy(
+    password: &str,
+    state: &web::Data<AppState>,
+) -> Result<(), error::Error> {
+    let min_length = state.config.get::<usize>("password.min_length").unwrap();
+
+    if password.chars().count() < min_length {
+        return Err(error::new_error(
+            1002,
+            &format!("Password must be at least {} characters", min_length),
+            422,
+        ));
+    }
+
+    if state.common_passwords.contains(password) {
+        return Err(error::new_error(1002, "Password is too common", 422));
+    }
+
+    Ok(())
 }
 
-pub fn encrypt_password(password: &str, _salt: &uuid::Uuid) -> String {
-    let pwd = format!("{}{}", password, _salt.to_string());
+pub fn legacy_hash(password: &str, _salt: &uuid::Uuid) -> String {
+    let pwd = format!("{}{}", password, _salt);
 
     let pwd = md5::compute(pwd);
 
-    let pwd = format!("{:?}{}{}", pwd, password, _salt.to_string());
+    let pwd = format!("{:?}{}{}", pwd, password, _salt);
 
     let pwd = Sha512::new().chain_update(pwd).finalize();
 
     format!("{:x}", pwd)
 }
 
-pub fn validate_password(password: &str, salt: &uuid::Uuid, hash: &str) -> bool {
-    let encrypted = encrypt_password(password, salt);
+#[cfg(test)]
+mod tests {
+    use super::{hash_password, legacy_hash, verify_password, CommonPasswords};
+
+    fn settings() -> config::Config {
+        config::Config::builder()
+            .set_override("argon2.memory_cost", 19456)
+            .unwrap()
+            .set_override("argon2.time_cost", 2)
+            .unwrap()
+            .set_override("argon2.parallelism", 1)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn legacy_hash_verifies_and_needs_rehash() {
+        let settings = settings();
+        let user_id = uuid::Uuid::new_v4();
+        let hash = legacy_hash("Kente#Weaver7", &user_id);
+
+        let check = verify_password("Kente#Weaver7", &user_id, &hash, &settings);
+
+        assert!(check.valid);
+        assert!(check.needs_rehash);
+
+        assert!(!verify_password("kente#weaver7", &user_id, &hash, &settings).valid);
+        assert!(!verify_password("Kente#Weaver7", &uuid::Uuid::new_v4(), &hash, &settings).valid);
+    }
 
-    encrypted == hash
-}
\ No newline at end of file
+    #[test]
+    fn current_hash_does_not_need_rehash() {
+        let settings = settings();
+        let hash = hash_password("Kente#Weaver7", &settings).unwrap();
+
+        let check = verify_password("Kente#Weaver7", &uuid::Uuid::new_v4(), &hash, &settings);
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(check.valid);
+        assert!(!check.needs_rehash);
+    }
+
+    #[test]
+    fn bundled_list_is_used_without_a_configured_path() {
+        let settings = config::Config::builder()
+            .set_override("password.common_list", "")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let common = CommonPasswords::from_config(&settings);
+
+        assert!(common.contains("Password"));
+        assert!(!common.contains("correct horse battery staple"));
+    }
+
+    #[test]
+    fn configured_list_replaces_the_bundled_one() {
+        let path = std::env::temp_dir().join(format!("common-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "Tr0ub4dor&3\n\n  hunter2  \n").unwrap();
+
+        let settings = config::Config::builder()
+            .set_override("password.common_list", path.to_str().unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let common = CommonPasswords::from_config(&settings);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(common.contains("tr0ub4dor&3"));
+        assert!(common.contains("HUNTER2"));
+        assert!(!common.contains("password"));
+    }
+}
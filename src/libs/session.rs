@@ -0,0 +1,71 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use actix_web::web;
+use sea_orm::{ColumnTrait, Condition, DbErr, EntityTrait, PaginatorTrait, QueryFilter};
+
+use crate::AppState;
+
+pub struct SessionCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<uuid::Uuid, (bool, Instant)>>,
+}
+
+impl SessionCache {
+    pub fn new(ttl: Duration) -> Self {
+        SessionCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, session_id: &uuid::Uuid) -> Option<bool> {
+        let entries = self.entries.lock().unwrap();
+
+        match entries.get(session_id) {
+            Some((active, checked_at)) if checked_at.elapsed() < self.ttl => Some(*active),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&self, session_id: uuid::Uuid, active: bool) {
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.retain(|_, (_, checked_at)| checked_at.elapsed() < self.ttl);
+        entries.insert(session_id, (active, Instant::now()));
+    }
+
+    pub fn invalidate(&self, session_id: &uuid::Uuid) {
+        self.entries.lock().unwrap().remove(session_id);
+    }
+}
+
+// A session stays alive for as long as its refresh token family has a live token,
+// so logout, reuse detection and admin revocation all end it the same way.
+pub async fn is_session_active(
+    session_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<bool, DbErr> {
+    if let Some(active) = state.sessions.get(&session_id) {
+        return Ok(active);
+    }
+
+    let live_tokens = entity::refresh_tokens::Entity::find()
+        .filter(
+            Condition::all()
+                .add(entity::refresh_tokens::Column::FamilyId.eq(session_id))
+                .add(entity::refresh_tokens::Column::IsRevoked.eq(false))
+                .add(entity::refresh_tokens::Column::ExpiresAt.gt(chrono::Utc::now())),
+        )
+        .count(state.pg_db.get_ref())
+        .await?;
+
+    let active = live_tokens > 0;
+
+    state.sessions.insert(session_id, active);
+
+    Ok(active)
+}
@@ -0,0 +1,99 @@
+use std::io::Cursor;
+
+use calamine::{open_workbook_from_rs, Data, DataType, Reader, Xlsx};
+
+use crate::libs::error;
+
+// Reads the first sheet of an uploaded file into rows of trimmed cell text, header row
+// included. Dates come out as YYYY-MM-DD whichever format they were stored in.
+pub fn read_rows(format: &str, data: &[u8]) -> Result<Vec<Vec<String>>, error::Error> {
+    match format {
+        "xlsx" => read_xlsx(data),
+        _ => read_csv(data),
+    }
+}
+
+fn read_csv(data: &[u8]) -> Result<Vec<Vec<String>>, error::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(String::from).collect())
+                .map_err(|_| error::new_error(1002, "File is not valid CSV", 422))
+        })
+        .collect()
+}
+
+fn read_xlsx(data: &[u8]) -> Result<Vec<Vec<String>>, error::Error> {
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(data))
+        .map_err(|_| error::new_error(1002, "File is not a valid XLSX workbook", 422))?;
+
+    let sheet = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| error::new_error(1002, "Workbook has no sheets", 422))?
+        .map_err(|_| error::new_error(1002, "File is not a valid XLSX workbook", 422))?;
+
+    Ok(sheet
+        .rows()
+        .map(|row| row.iter().map(cell_text).collect())
+        .collect())
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(_) | Data::DateTimeIso(_) => cell
+            .as_date()
+            .map(|date| date.to_string())
+            .unwrap_or_default(),
+        // spreadsheets store typed numbers as floats, which would otherwise print as 1.0
+        Data::Float(value) if value.fract() == 0.0 => format!("{}", *value as i64),
+        _ => cell.to_string().trim().to_string(),
+    }
+}
+
+// A phone number typed into a numeric cell loses its leading zero, both in the workbook
+// and in any CSV saved from it, and comes back one digit short. Anything else is left
+// for validation to judge.
+pub fn restore_leading_zero(value: &str, width: usize) -> String {
+    if value.len() + 1 == width && value.bytes().all(|b| b.is_ascii_digit()) {
+        format!("0{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_xlsxwriter::Workbook;
+
+    use super::{read_rows, restore_leading_zero};
+
+    #[test]
+    fn numeric_phone_cells_get_their_leading_zero_back() {
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.write_string(0, 0, "Phone").unwrap();
+        sheet.write_number(1, 0, 241234567.0).unwrap();
+        sheet.write_string(2, 0, "0241234567").unwrap();
+
+        let rows = read_rows("xlsx", &workbook.save_to_buffer().unwrap()).unwrap();
+        let phones: Vec<String> = rows[1..]
+            .iter()
+            .map(|row| restore_leading_zero(&row[0], 10))
+            .collect();
+
+        assert_eq!(phones, vec!["0241234567", "0241234567"]);
+    }
+
+    #[test]
+    fn only_one_missing_digit_is_restored() {
+        assert_eq!(restore_leading_zero("12345", 10), "12345");
+        assert_eq!(restore_leading_zero("24123456x", 10), "24123456x");
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use config::Config;
+
+// Failed attempts are remembered per client address and per account (by user id, so
+// every identifier for it shares a window) over a sliding window. Past `free_attempts`
+// every further failure doubles the wait before the next try, and a key that reaches its
+// maximum is shut out until the window slides on. Password resets keep their own address
+// and account buckets, so they never hold anyone back from signing in.
+pub struct LoginThrottle {
+    window: Duration,
+    max_per_ip: usize,
+    max_per_account: usize,
+    free_attempts: usize,
+    base_delay: Duration,
+    max_delay: Duration,
+    failures: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl LoginThrottle {
+    pub fn from_config(settings: &Config) -> Self {
+        LoginThrottle {
+            window: Duration::from_secs(settings.get::<u64>("login_throttle.window").unwrap()),
+            max_per_ip: settings.get::<usize>("login_throttle.max_per_ip").unwrap(),
+            max_per_account: settings
+                .get::<usize>("login_throttle.max_per_account")
+                .unwrap(),
+            free_attempts: settings
+                .get::<usize>("login_throttle.free_attempts")
+                .unwrap(),
+            base_delay: Duration::from_secs(
+                settings.get::<u64>("login_throttle.base_delay").unwrap(),
+            ),
+            max_delay: Duration::from_secs(
+                settings.get::<u64>("login_throttle.max_delay").unwrap(),
+            ),
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Seconds to wait before another attempt is allowed, if either key is throttled.
+    pub fn retry_after(&self, ip: &str, account: &str) -> Option<u64> {
+        self.retry_after_keys(&[
+            (ip_key(ip), self.max_per_ip),
+            (account_key(account), self.max_per_account),
+        ])
+    }
+
+    pub fn record_failure(&self, ip: &str, account: &str) {
+        self.record(&[ip_key(ip), account_key(account)]);
+    }
+
+    // For requests anyone can make about any account, which must never count against
+    // that account's own bucket.
+    pub fn retry_after_ip(&self, ip: &str) -> Option<u64> {
+        self.retry_after_keys(&[(ip_key(ip), self.max_per_ip)])
+    }
+
+    pub fn record_ip_failure(&self, ip: &str) {
+        self.record(&[ip_key(ip)]);
+    }
+
+    // Reset requests count against a separate address bucket, so a run of them from an
+    // address shared behind NAT cannot shut everyone behind it out of login.
+    pub fn retry_after_reset_ip(&self, ip: &str) -> Option<u64> {
+        self.retry_after_keys(&[(reset_ip_key(ip), self.max_per_ip)])
+    }
+
+    pub fn record_reset_ip_failure(&self, ip: &str) {
+        self.record(&[reset_ip_key(ip)]);
+    }
+
+    // Reset codes are guessed against their own per-account bucket, so wrong codes
+    // submitted for someone's username cannot lock them out of login.
+    pub fn retry_after_reset(&self, ip: &str, account: &str) -> Option<u64> {
+        self.retry_after_keys(&[
+            (reset_ip_key(ip), self.max_per_ip),
+            (reset_key(account), self.max_per_account),
+        ])
+    }
+
+    pub fn record_reset_failure(&self, ip: &str, account: &str) {
+        self.record(&[reset_ip_key(ip), reset_key(account)]);
+    }
+
+    pub fn reset_account(&self, account: &str) {
+        let mut failures = self.failures.lock().unwrap();
+
+        failures.remove(&account_key(account));
+        failures.remove(&reset_key(account));
+    }
+
+    fn retry_after_keys(&self, keys: &[(String, usize)]) -> Option<u64> {
+        let mut failures = self.failures.lock().unwrap();
+        let now = Instant::now();
+
+        keys.iter()
+            .filter_map(|(key, max_failures)| self.wait(&mut failures, key, *max_failures, now))
+            .max()
+            .map(|wait| wait.as_secs() + u64::from(wait.subsec_nanos() > 0))
+    }
+
+    fn record(&self, keys: &[String]) {
+        let mut failures = self.failures.lock().unwrap();
+        let now = Instant::now();
+
+        failures.retain(|_, attempts| {
+            attempts
+                .back()
+                .is_some_and(|at| now.duration_since(*at) < self.window)
+        });
+
+        for key in keys {
+            failures.entry(key.clone()).or_default().push_back(now);
+        }
+    }
+
+    fn wait(
+        &self,
+        failures: &mut HashMap<String, VecDeque<Instant>>,
+        key: &str,
+        max_failures: usize,
+        now: Instant,
+    ) -> Option<Duration> {
+        let attempts = failures.get_mut(key)?;
+
+        while attempts
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= self.window)
+        {
+            attempts.pop_front();
+        }
+
+        let (oldest, latest) = (*attempts.front()?, *attempts.back()?);
+
+        if attempts.len() >= max_failures {
+            return Some(self.window.saturating_sub(now.duration_since(oldest)));
+        }
+
+        if attempts.len() < self.free_attempts {
+            return None;
+        }
+
+        let exponent = (attempts.len() - self.free_attempts) as u32;
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_delay);
+
+        delay
+            .checked_sub(now.duration_since(latest))
+            .filter(|wait| !wait.is_zero())
+    }
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+fn account_key(account: &str) -> String {
+    format!("account:{}", account.trim().to_lowercase())
+}
+
+fn reset_ip_key(ip: &str) -> String {
+    format!("reset-ip:{}", ip)
+}
+
+fn reset_key(account: &str) -> String {
+    format!("reset:{}", account.trim().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoginThrottle;
+
+    fn throttle() -> LoginThrottle {
+        let settings = config::Config::builder()
+            .set_override("login_throttle.window", 900)
+            .unwrap()
+            .set_override("login_throttle.max_per_ip", 50)
+            .unwrap()
+            .set_override("login_throttle.max_per_account", 3)
+            .unwrap()
+            .set_override("login_throttle.free_attempts", 3)
+            .unwrap()
+            .set_override("login_throttle.base_delay", 1)
+            .unwrap()
+            .set_override("login_throttle.max_delay", 60)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        LoginThrottle::from_config(&settings)
+    }
+
+    #[test]
+    fn account_is_shut_out_after_max_failures() {
+        let throttle = throttle();
+
+        for _ in 0..3 {
+            assert_eq!(throttle.retry_after("10.0.0.1", "0241234567"), None);
+            throttle.record_failure("10.0.0.1", "0241234567");
+        }
+
+        assert!(throttle.retry_after("10.0.0.2", "0241234567").is_some());
+    }
+
+    #[test]
+    fn reset_requests_do_not_lock_the_login_account() {
+        let throttle = throttle();
+
+        for _ in 0..10 {
+            throttle.record_reset_ip_failure("10.0.0.1");
+            throttle.record_reset_failure("10.0.0.1", "0241234567");
+        }
+
+        assert!(throttle
+            .retry_after_reset("10.0.0.2", "0241234567")
+            .is_some());
+        assert_eq!(throttle.retry_after("10.0.0.2", "0241234567"), None);
+    }
+
+    #[test]
+    fn reset_requests_do_not_lock_the_address_out_of_login() {
+        let throttle = throttle();
+
+        for _ in 0..60 {
+            throttle.record_reset_ip_failure("10.0.0.1");
+        }
+
+        assert!(throttle.retry_after_reset_ip("10.0.0.1").is_some());
+        assert_eq!(throttle.retry_after_ip("10.0.0.1"), None);
+        assert_eq!(throttle.retry_after("10.0.0.1", "0241234567"), None);
+    }
+
+    #[test]
+    fn reset_account_clears_both_buckets() {
+        let throttle = throttle();
+
+        for _ in 0..3 {
+            throttle.record_failure("10.0.0.1", "0241234567");
+            throttle.record_reset_failure("10.0.0.1", "0241234567");
+        }
+
+        throttle.reset_account("0241234567");
+
+        assert_eq!(throttle.retry_after("10.0.0.2", "0241234567"), None);
+        assert_eq!(throttle.retry_after_reset("10.0.0.2", "0241234567"), None);
+    }
+}
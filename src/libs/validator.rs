@@ -1,46 +1,70 @@
-use regex::Regex;
 use crate::libs::error;
 use chrono::NaiveDate;
+use regex::Regex;
 
 pub fn not_none<T>(v: Option<T>, name: &str) -> Result<(), error::Error> {
-
     if let None = v {
-        return Err(error::new_error(1002, &format!("{} cannot be empty", name)[..], 422));
+        return Err(error::new_error(
+            1002,
+            &format!("{} cannot be empty", name)[..],
+            422,
+        ));
     }
 
     Ok(())
 }
 
 pub fn required_str(v: &str, name: &str) -> Result<String, error::Error> {
-
     let v = v.to_string();
 
     if v.chars().count() == 0 {
-        return Err(error::new_error(1002, &format!("{} is required", name)[..], 422));
+        return Err(error::new_error(
+            1002,
+            &format!("{} is required", name)[..],
+            422,
+        ));
     }
 
     Ok(v)
 }
 
-pub fn email(v: &str, name: &str) -> Result<String, error::Error> {
+pub fn one_of(v: &str, allowed: &[&str], name: &str) -> Result<String, error::Error> {
+    if !allowed.contains(&v) {
+        return Err(error::new_error(
+            1002,
+            &format!("{} must be one of: {}", name, allowed.join(", "))[..],
+            422,
+        ));
+    }
+
+    Ok(v.to_string())
+}
 
+pub fn email(v: &str, name: &str) -> Result<String, error::Error> {
     let res_str = v.to_string();
 
     let re = Regex::new(r"^[a-zA-Z0-9_.+-]+@[a-zA-Z0-9-]+\.[a-zA-Z0-9-.]+$").unwrap();
 
     if !re.is_match(v) {
-        return Err(error::new_error(1002, &format!("{} validation failed", name)[..], 422));
+        return Err(error::new_error(
+            1002,
+            &format!("{} validation failed", name)[..],
+            422,
+        ));
     }
 
     Ok(res_str)
 }
 
 pub fn mobile(v: &str, name: &str) -> Result<String, error::Error> {
-
     let re = Regex::new(r"^[0-9]{10}$").unwrap();
 
     if !re.is_match(v) {
-        return Err(error::new_error(1002, &format!("{} validation failed", name)[..], 422));
+        return Err(error::new_error(
+            1002,
+            &format!("{} validation failed", name)[..],
+            422,
+        ));
     }
 
     let res_str = v.to_string();
@@ -49,14 +73,18 @@ pub fn mobile(v: &str, name: &str) -> Result<String, error::Error> {
 }
 
 pub fn uuid(v: &str, name: &str) -> Result<uuid::Uuid, error::Error> {
-
     let re = Regex::new(r"^[0-9A-F]{8}-[0-9A-F]{4}-[0-9A-F]{4}-[0-9A-F]{4}-[0-9A-F]{12}$").unwrap();
-    
+
     if !re.is_match(&(v.to_uppercase())[..]) {
-        return Err(error::new_error(1002, &format!("{} is invalid", name)[..], 422));
+        return Err(error::new_error(
+            1002,
+            &format!("{} is invalid", name)[..],
+            422,
+        ));
     }
 
-    let res_str = uuid::Uuid::parse_str(v).map_err(|_| error::new_error(1002, &format!("{} is not a valid UUID", name), 422))?;
+    let res_str = uuid::Uuid::parse_str(v)
+        .map_err(|_| error::new_error(1002, &format!("{} is not a valid UUID", name), 422))?;
 
     Ok(res_str)
 }
@@ -76,4 +104,118 @@ pub fn date(v: &str, name: &str) -> Result<NaiveDate, error::Error> {
     };
 
     Ok(parsed_date)
-}
\ No newline at end of file
+}
+
+// The members table only takes births from 1900-01-01 up to today, so anything outside
+// that is turned away here rather than failing on the CHECK constraint later.
+pub fn date_of_birth(v: NaiveDate, name: &str) -> Result<NaiveDate, error::Error> {
+    if v < NaiveDate::from_ymd_opt(1900, 1, 1).unwrap() {
+        return Err(error::new_error(
+            1002,
+            &format!("{} cannot be before 1900-01-01", name),
+            422,
+        ));
+    }
+
+    not_future(v, name)
+}
+
+pub fn not_future(v: NaiveDate, name: &str) -> Result<NaiveDate, error::Error> {
+    if v > chrono::Utc::now().date_naive() {
+        return Err(error::new_error(
+            1002,
+            &format!("{} cannot be in the future", name),
+            422,
+        ));
+    }
+
+    Ok(v)
+}
+
+// Identity provider endpoints carry client secrets and tokens, so only https is
+// accepted. With `allow_loopback` (oidc.allow_insecure_loopback, set only for tests)
+// plain http is also allowed for loopback hosts, where the mock identity provider runs.
+pub fn url(v: &str, name: &str, allow_loopback: bool) -> Result<String, error::Error> {
+    let parsed = reqwest::Url::parse(v)
+        .map_err(|_| error::new_error(1002, &format!("{} is not a valid URL", name), 422))?;
+
+    let loopback = allow_loopback
+        && match parsed.host_str() {
+            Some("localhost") => true,
+            Some(host) => host
+                .trim_matches(|c| c == '[' || c == ']')
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback()),
+            None => false,
+        };
+
+    match parsed.scheme() {
+        "https" => Ok(v.to_string()),
+        "http" if loopback => Ok(v.to_string()),
+        _ => Err(error::new_error(
+            1002,
+            &format!("{} must be an https URL", name),
+            422,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{date_of_birth, not_future, url};
+
+    #[test]
+    fn date_of_birth_is_kept_between_1900_and_today() {
+        let today = chrono::Utc::now().date_naive();
+
+        assert!(date_of_birth(
+            NaiveDate::from_ymd_opt(1900, 1, 1).unwrap(),
+            "Date of Birth"
+        )
+        .is_ok());
+        assert!(date_of_birth(today, "Date of Birth").is_ok());
+        assert!(date_of_birth(
+            NaiveDate::from_ymd_opt(1899, 12, 31).unwrap(),
+            "Date of Birth"
+        )
+        .is_err());
+        assert!(date_of_birth(today.succ_opt().unwrap(), "Date of Birth").is_err());
+    }
+
+    #[test]
+    fn not_future_rejects_tomorrow() {
+        let today = chrono::Utc::now().date_naive();
+
+        assert!(not_future(today, "Date Joined").is_ok());
+        assert!(not_future(today.succ_opt().unwrap(), "Date Joined").is_err());
+    }
+
+    #[test]
+    fn url_accepts_https() {
+        assert!(url("https://idp.example.com/token", "Token Endpoint", false).is_ok());
+    }
+
+    #[test]
+    fn url_rejects_http_unless_loopback_is_allowed() {
+        assert!(url("http://localhost:8080/token", "Token Endpoint", false).is_err());
+        assert!(url("http://127.0.0.1:8080/token", "Token Endpoint", false).is_err());
+        assert!(url("https://localhost:8080/token", "Token Endpoint", false).is_ok());
+    }
+
+    #[test]
+    fn url_accepts_http_only_on_loopback() {
+        assert!(url("http://localhost:8080/token", "Token Endpoint", true).is_ok());
+        assert!(url("http://127.0.0.1:8080/token", "Token Endpoint", true).is_ok());
+        assert!(url("http://[::1]:8080/token", "Token Endpoint", true).is_ok());
+        assert!(url("http://idp.example.com/token", "Token Endpoint", true).is_err());
+        assert!(url("http://10.0.0.5/token", "Token Endpoint", true).is_err());
+    }
+
+    #[test]
+    fn url_rejects_other_schemes() {
+        assert!(url("ftp://idp.example.com/token", "Token Endpoint", false).is_err());
+        assert!(url("not a url", "Token Endpoint", false).is_err());
+    }
+}
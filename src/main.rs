@@ -9,21 +9,31 @@ use actix_web::{
     App, HttpServer,
 };
 use config::{Config as ConfigLoader, ConfigError, File, FileFormat};
-use libs::error;
+use libs::{
+    error,
+    notifier::{build_notifier, Notifier},
+    pword::CommonPasswords,
+    session::SessionCache,
+    throttle::LoginThrottle,
+};
 use sea_orm::DatabaseConnection;
 use setup::db::pg::pg_conn;
 
 mod app;
+mod files_manager;
 mod libs;
+mod middlewares;
 mod setup;
 mod utils;
-mod middlewares;
-mod files_manager;
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: ConfigLoader,
     pub pg_db: Arc<Data<DatabaseConnection>>,
+    pub sessions: Arc<SessionCache>,
+    pub notifier: Arc<dyn Notifier>,
+    pub throttle: Arc<LoginThrottle>,
+    pub common_passwords: Arc<CommonPasswords>,
 }
 
 fn load_config() -> Result<ConfigLoader, ConfigError> {
@@ -35,6 +45,19 @@ fn load_config() -> Result<ConfigLoader, ConfigError> {
     Ok(config)
 }
 
+fn routes(cfg: &mut web::ServiceConfig, state: web::Data<AppState>) {
+    app::auth::routes::route::all_routes(cfg, state.clone());
+    app::organization::routes::route::all_routes(cfg, state.clone());
+    app::members::routes::route::all_routes(cfg, state.clone());
+    app::users::routes::route::all_routes(cfg, state.clone());
+    app::invitations::routes::route::all_routes(cfg, state.clone());
+    app::exports::routes::route::all_routes(cfg, state.clone());
+    app::households::routes::route::all_routes(cfg, state.clone());
+    app::departments::routes::route::all_routes(cfg, state);
+    app::health::routes::route::route(cfg);
+    files_manager::file_api::routes(cfg);
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     if std::env::var_os("RUST_LOG").is_none() {
@@ -48,6 +71,7 @@ async fn main() -> std::io::Result<()> {
 
     let port = settings.get::<String>("app.port").unwrap();
     let host = settings.get::<String>("app.host").unwrap();
+    let session_cache_ttl = settings.get::<u64>("session.cache_ttl").unwrap();
 
     let pg_conn: Arc<Data<DatabaseConnection>> = Arc::new(Data::new(pg_conn(&settings).await));
 
@@ -57,12 +81,23 @@ async fn main() -> std::io::Result<()> {
     let state = web::Data::new(AppState {
         config: settings.clone(),
         pg_db: pg_conn.clone(),
+        sessions: Arc::new(SessionCache::new(std::time::Duration::from_secs(
+            session_cache_ttl,
+        ))),
+        notifier: build_notifier(&settings),
+        throttle: Arc::new(LoginThrottle::from_config(&settings)),
+        common_passwords: Arc::new(CommonPasswords::from_config(&settings)),
     });
 
+    app::exports::controllers::controller::recover_interrupted_exports(&state).await;
+    tokio::spawn(app::exports::controllers::controller::run_export_sweeper(
+        state.clone(),
+    ));
+
     HttpServer::new(move || {
         let cors = Cors::default()
             .allow_any_origin()
-            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
+            .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
             .allowed_headers(vec![
                 header::CONTENT_TYPE,
                 header::AUTHORIZATION,
@@ -86,10 +121,7 @@ async fn main() -> std::io::Result<()> {
             .wrap(Logger::default())
             .wrap(NormalizePath::trim())
             .wrap(cors)
-            .configure(|cfg| app::organization::routes::route::all_routes(cfg, state.clone()))
-            .configure(|cfg| app::members::routes::route::all_routes(cfg, state.clone()))
-            .configure(app::health::routes::route::route)
-            .configure(files_manager::file_api::routes)
+            .configure(|cfg| routes(cfg, state.clone()))
     })
     .bind(format!("{}:{}", _host, _port))?
     .run()
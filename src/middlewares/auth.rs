@@ -1,15 +1,89 @@
 use actix_web::{
-    dev::{Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpMessage,
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    web, Error, FromRequest, HttpMessage, HttpRequest,
 };
-use futures::future::{ok, LocalBoxFuture, Ready};
+use futures::future::{err, ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
 use std::task::{Context, Poll};
-use std::{rc::Rc, sync::Arc};
 
-use crate::libs::jwt::verify_jwt;
+use crate::{
+    libs::{
+        api_key::{read_api_key, resolve_api_key},
+        jwt::{verify_jwt, Claims, SCOPE_FULL},
+        session::is_session_active,
+    },
+    middlewares::role::{API_KEY, SUPER_ADMIN},
+    AppState,
+};
+
+pub const SCOPE_API_KEY: &str = "api_key";
+
+// Permissions an API key can be granted; each route opts in with ApiKeyAuth.
+pub const API_KEY_SCOPES: [&str; 2] = ["members:read", "members:write"];
+
+#[derive(Debug, Clone)]
+pub struct AuthContext {
+    pub user_id: uuid::Uuid,
+    pub member_id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    pub role: String,
+    pub session_id: uuid::Uuid,
+    pub scope: String,
+}
+
+impl AuthContext {
+    pub fn is_super_admin(&self) -> bool {
+        self.role == SUPER_ADMIN
+    }
+
+    // The organization every query must be filtered by; None only for platform super admins.
+    pub fn scope(&self) -> Option<uuid::Uuid> {
+        if self.is_super_admin() {
+            None
+        } else {
+            Some(self.organization_id)
+        }
+    }
 
+    pub fn can_access(&self, organization_id: uuid::Uuid) -> bool {
+        self.is_super_admin() || self.organization_id == organization_id
+    }
+
+    fn from_claims(claims: &Claims) -> Option<Self> {
+        Some(AuthContext {
+            user_id: uuid::Uuid::parse_str(&claims.id).ok()?,
+            member_id: uuid::Uuid::parse_str(&claims.member_id).ok()?,
+            organization_id: uuid::Uuid::parse_str(&claims.organization_id).ok()?,
+            role: claims.role.clone(),
+            session_id: uuid::Uuid::parse_str(&claims.jid).ok()?,
+            scope: claims.scope.clone(),
+        })
+    }
+}
+
+impl FromRequest for AuthContext {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        match req.extensions().get::<AuthContext>() {
+            Some(auth) => ok(auth.clone()),
+            None => err(actix_web::error::ErrorUnauthorized("Unauthorized")),
+        }
+    }
+}
+
+// Only accepts full-access tokens; restricted tokens must go through ScopedJwtAuth.
 pub struct JwtAuthMiddleware;
 
+// Accepts tokens carrying any of the listed scopes, e.g. the restricted token
+// handed out until a provisioned user changes their password.
+pub struct ScopedJwtAuth(pub &'static [&'static str]);
+
+// Accepts full-access tokens as well as `Authorization: ApiKey ...` for keys that were
+// granted the named scope. Role checks behind it must allow the API_KEY role.
+pub struct ApiKeyAuth(pub &'static str);
+
 impl<S, B> Transform<S, ServiceRequest> for JwtAuthMiddleware
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
@@ -22,15 +96,57 @@ where
     type InitError = ();
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
+    fn new_transform(&self, service: S) -> Self::Future {
+        ScopedJwtAuth(&[SCOPE_FULL]).new_transform(service)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ScopedJwtAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = JwtAuthMiddlewareInner<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
     fn new_transform(&self, service: S) -> Self::Future {
         ok(JwtAuthMiddlewareInner {
             service: Rc::new(service),
+            scopes: self.0,
+            api_scope: None,
+        })
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = JwtAuthMiddlewareInner<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(JwtAuthMiddlewareInner {
+            service: Rc::new(service),
+            scopes: &[SCOPE_FULL],
+            api_scope: Some(self.0),
         })
     }
 }
 
 pub struct JwtAuthMiddlewareInner<S> {
     service: Rc<S>,
+    scopes: &'static [&'static str],
+    api_scope: Option<&'static str>,
 }
 
 impl<S, B> Service<ServiceRequest> for JwtAuthMiddlewareInner<S>
@@ -49,17 +165,89 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = self.service.clone();
+        let scopes = self.scopes;
+        let api_scope = self.api_scope;
         let (http_request, payload) = req.into_parts();
 
         Box::pin(async move {
-            match verify_jwt(&http_request).await {
-                Ok(claims) => {
+            if let Some(key) = read_api_key(&http_request) {
+                let required = match api_scope {
+                    Some(required) => required,
+                    None => return Err(actix_web::error::ErrorUnauthorized("Unauthorized")),
+                };
+
+                let state = match http_request.app_data::<web::Data<AppState>>() {
+                    Some(state) => state.clone(),
+                    None => {
+                        return Err(actix_web::error::ErrorInternalServerError("Missing State"))
+                    }
+                };
+
+                let auth = api_key_context(&key, required, &state).await?;
+
+                let req = ServiceRequest::from_parts(http_request, payload);
+                req.extensions_mut().insert(auth);
+                return service.call(req).await;
+            }
+
+            let claims = match verify_jwt(&http_request).await {
+                Ok(claims) => claims,
+                Err(_) => return Err(actix_web::error::ErrorUnauthorized("Unauthorized")),
+            };
+
+            let auth = match AuthContext::from_claims(&claims) {
+                Some(auth) => auth,
+                None => return Err(actix_web::error::ErrorUnauthorized("Unauthorized")),
+            };
+
+            if !scopes.contains(&auth.scope.as_str()) {
+                return Err(actix_web::error::ErrorForbidden("Insufficient Token Scope"));
+            }
+
+            let state = match http_request.app_data::<web::Data<AppState>>() {
+                Some(state) => state.clone(),
+                None => return Err(actix_web::error::ErrorInternalServerError("Missing State")),
+            };
+
+            match is_session_active(auth.session_id, &state).await {
+                Ok(true) => {
                     let req = ServiceRequest::from_parts(http_request, payload);
-                    req.extensions_mut().insert(Arc::new(claims));
+                    req.extensions_mut().insert(auth);
                     service.call(req).await
                 }
-                Err(_) => Err(actix_web::error::ErrorUnauthorized("Unauthorized")),
+                Ok(false) => Err(actix_web::error::ErrorUnauthorized("Session Revoked")),
+                Err(e) => Err(actix_web::error::ErrorInternalServerError(e.to_string())),
             }
         })
     }
 }
+
+async fn api_key_context(
+    key: &str,
+    required: &str,
+    state: &web::Data<AppState>,
+) -> Result<AuthContext, Error> {
+    let (api_key, user) = match resolve_api_key(key, state).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return Err(actix_web::error::ErrorUnauthorized("Unauthorized")),
+        Err(e) => return Err(actix_web::error::ErrorInternalServerError(e.to_string())),
+    };
+
+    if !api_key
+        .scopes
+        .split_whitespace()
+        .any(|scope| scope == required)
+    {
+        return Err(actix_web::error::ErrorForbidden("Insufficient Key Scope"));
+    }
+
+    // the key acts on behalf of the admin who created it, within its organization
+    Ok(AuthContext {
+        user_id: user.id,
+        member_id: user.member_id,
+        organization_id: api_key.organization_id,
+        role: API_KEY.to_string(),
+        session_id: api_key.id,
+        scope: SCOPE_API_KEY.to_string(),
+    })
+}
@@ -0,0 +1,173 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use crate::middlewares::auth::AuthContext;
+
+pub const SUPER_ADMIN: &str = "super_admin";
+pub const ADMIN: &str = "admin";
+pub const SECRETARY: &str = "secretary";
+pub const FINANCE: &str = "finance";
+pub const MEMBER: &str = "member";
+
+// Carried by integrations signing in with an API key instead of a user.
+pub const API_KEY: &str = "api_key";
+
+// Roles an organization admin may hand out; super admins are created out of band.
+pub const ORGANIZATION_ROLES: [&str; 4] = [ADMIN, SECRETARY, FINANCE, MEMBER];
+
+// Roles an organization can force onto two-factor authentication.
+pub const TWO_FACTOR_ROLES: [&str; 2] = [ADMIN, FINANCE];
+
+// Must sit inside JwtAuthMiddleware, i.e. be wrapped before it, so the auth context exists.
+pub struct RequireRole {
+    roles: Rc<Vec<String>>,
+}
+
+impl RequireRole {
+    pub fn any<I, R>(roles: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<String>,
+    {
+        RequireRole {
+            roles: Rc::new(roles.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireRole
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequireRoleInner<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequireRoleInner {
+            service: Rc::new(service),
+            roles: self.roles.clone(),
+        })
+    }
+}
+
+pub struct RequireRoleInner<S> {
+    service: Rc<S>,
+    roles: Rc<Vec<String>>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireRoleInner<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let role = req
+            .extensions()
+            .get::<AuthContext>()
+            .map(|a| a.role.clone());
+        let allowed = match &role {
+            Some(role) => self.roles.iter().any(|r| r == role),
+            None => false,
+        };
+
+        Box::pin(async move {
+            match (role, allowed) {
+                (None, _) => Err(actix_web::error::ErrorUnauthorized("Unauthorized")),
+                (Some(_), false) => Err(actix_web::error::ErrorForbidden("Forbidden")),
+                (Some(_), true) => service.call(req).await,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{dev::Service, http::StatusCode, test, web, App, HttpMessage, HttpResponse};
+
+    use super::{RequireRole, ADMIN, FINANCE, MEMBER};
+    use crate::{libs::jwt::SCOPE_FULL, middlewares::auth::AuthContext};
+
+    fn context(role: &str) -> AuthContext {
+        AuthContext {
+            user_id: uuid::Uuid::new_v4(),
+            member_id: uuid::Uuid::new_v4(),
+            organization_id: uuid::Uuid::new_v4(),
+            role: role.to_string(),
+            session_id: uuid::Uuid::new_v4(),
+            scope: SCOPE_FULL.to_string(),
+        }
+    }
+
+    async fn whoami(auth: AuthContext) -> HttpResponse {
+        HttpResponse::Ok().body(auth.role)
+    }
+
+    // Stands in for JwtAuthMiddleware by attaching `auth`, when given, to every request.
+    async fn call(auth: Option<AuthContext>) -> Result<String, StatusCode> {
+        let app = test::init_service(
+            App::new()
+                .wrap_fn(move |req, srv| {
+                    if let Some(auth) = auth.clone() {
+                        req.extensions_mut().insert(auth);
+                    }
+                    srv.call(req)
+                })
+                .route(
+                    "/",
+                    web::get()
+                        .to(whoami)
+                        .wrap(RequireRole::any([ADMIN, FINANCE])),
+                ),
+        )
+        .await;
+
+        match test::try_call_service(&app, test::TestRequest::get().uri("/").to_request()).await {
+            Ok(resp) => Ok(String::from_utf8(test::read_body(resp).await.to_vec()).unwrap()),
+            Err(err) => Err(err.as_response_error().status_code()),
+        }
+    }
+
+    #[actix_web::test]
+    async fn allowed_role_reaches_the_handler() {
+        assert_eq!(call(Some(context(FINANCE))).await, Ok(FINANCE.to_string()));
+    }
+
+    #[actix_web::test]
+    async fn other_roles_are_forbidden() {
+        assert_eq!(
+            call(Some(context(MEMBER))).await,
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+
+    #[actix_web::test]
+    async fn missing_auth_context_is_unauthorized() {
+        assert_eq!(call(None).await, Err(StatusCode::UNAUTHORIZED));
+
+        // the extractor refuses on its own when no middleware set the context
+        let app = test::init_service(App::new().route("/", web::get().to(whoami))).await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
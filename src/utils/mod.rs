@@ -1,4 +1,6 @@
+pub mod file_methods;
 pub mod macros;
 pub mod models;
 pub mod shared;
-pub mod file_methods;
+#[cfg(test)]
+pub mod testing;
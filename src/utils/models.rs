@@ -1,5 +1,3 @@
-
-
 use serde::Serialize;
 use serde_json::Value;
 
@@ -8,7 +6,19 @@ pub struct HttpClientResponse {
     pub code: u16,
     pub status: bool,
     pub message: String,
-    pub data: Value
+    pub data: Value,
+}
+
+// The data of a paginated listing. next_cursor is set while more rows follow and can be
+// passed back as the cursor to fetch them.
+#[derive(Serialize)]
+pub struct PaginatedModel<T: Serialize> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub limit: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    pub next_cursor: Option<String>,
 }
 
 pub struct SaveMemberOrgDto {
@@ -31,4 +41,4 @@ pub struct SaveMediaDto {
     pub width: Option<i32>,
     pub height: Option<i32>,
     pub duration: Option<i32>,
-}
\ No newline at end of file
+}
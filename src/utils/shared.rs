@@ -1,58 +1,16 @@
 use actix_web::web;
-use sea_orm::{ColumnTrait, Condition, DbErr, EntityTrait, InsertResult, QueryFilter, Set};
-
-use crate::AppState;
-
-use super::{
-    file_methods::file_exists,
-    models::{SaveMediaDto, SaveMemberOrgDto},
+use sea_orm::{
+    ColumnTrait, Condition, DbErr, EntityTrait, InsertResult, QueryFilter, QueryOrder, QueryTrait,
+    Set,
 };
 
-pub async fn save_member_from_org(
-    id: uuid::Uuid,
-    data: SaveMemberOrgDto,
-    state: &web::Data<AppState>,
-) -> Result<InsertResult<entity::members::ActiveModel>, DbErr> {
-    let exists = entity::members::Entity::find()
-        .filter(
-            Condition::all()
-                .add(entity::members::Column::IsBlocked.eq(false))
-                .add(entity::members::Column::Contact.eq(&data.phone))
-                .add(entity::members::Column::OrganizationId.eq(id)),
-        )
-        .one(state.pg_db.get_ref())
-        .await?;
-
-    if let Some(_) = exists {
-        return Err(DbErr::Custom("Member already exists".to_string()));
-    }
-
-    let member = entity::members::ActiveModel {
-        first_name: Set(data.first_name),
-        last_name: Set(data.last_name),
-        email: Set(data.email),
-        contact: Set(data.phone),
-        residential_address: Set(data.address),
-        organization_id: Set(id),
-        gender: Set(data.gender),
-        date_joined: Set(data.date_joined),
-        date_of_birth: Set(data.date_of_birth.unwrap_or_default()),
-        ..Default::default()
-    };
-
-    let insertion = entity::members::Entity::insert(member)
-        .exec(state.pg_db.get_ref())
-        .await
-        .map_err(|err| {
-            eprintln!("Database retrieval error: {}", err);
-            DbErr::Custom(err.to_string())
-        })?;
+use crate::AppState;
 
-    Ok(insertion)
-}
+use super::{file_methods::file_exists, models::SaveMediaDto};
 
 pub async fn save_media_meta(
     owner: uuid::Uuid,
+    organization_id: uuid::Uuid,
     data: SaveMediaDto,
     state: &web::Data<AppState>,
 ) -> Result<InsertResult<entity::media::ActiveModel>, DbErr> {
@@ -64,6 +22,7 @@ pub async fn save_media_meta(
 
     let media_data = entity::media::ActiveModel {
         owner_id: Set(owner),
+        organization_id: Set(Some(organization_id)),
         file_path: Set(Some(data.file_path)),
         mime_type: Set(Some(data.mime_type)),
         file_size: Set(Some(data.file_size)),
@@ -89,9 +48,13 @@ pub async fn save_media_meta(
 //get media by id
 pub async fn get_media_by_id(
     id: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
     state: &web::Data<AppState>,
 ) -> Result<entity::media::Model, DbErr> {
     let media = entity::media::Entity::find_by_id(id)
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::media::Column::OrganizationId.eq(org))
+        })
         .one(state.pg_db.get_ref())
         .await?
         .ok_or_else(|| DbErr::RecordNotFound("Organization not found or is blocked".into()));
@@ -102,13 +65,38 @@ pub async fn get_media_by_id(
 //get media by user
 pub async fn get_media_by_user(
     owner: uuid::Uuid,
+    organization_id: Option<uuid::Uuid>,
     state: &web::Data<AppState>,
 ) -> Result<Vec<entity::media::Model>, DbErr> {
     let medias = entity::media::Entity::find()
+        .filter(Condition::all().add(entity::media::Column::OwnerId.eq(owner)))
+        .apply_if(organization_id, |query, org| {
+            query.filter(entity::media::Column::OrganizationId.eq(org))
+        })
+        .all(state.pg_db.get_ref())
+        .await
+        .map_err(|err| {
+            eprintln!("Database retrieval error: {}", err);
+            DbErr::Custom(err.to_string())
+        })?;
+
+    Ok(medias)
+}
+
+// Images owned by anything in the organization, newest first, so the first one seen for
+// an owner is its current picture.
+pub async fn get_images_by_organization(
+    organization_id: uuid::Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<entity::media::Model>, DbErr> {
+    let images = entity::media::Entity::find()
         .filter(
             Condition::all()
-            .add(entity::media::Column::OwnerId.eq(owner))
+                .add(entity::media::Column::OrganizationId.eq(organization_id))
+                .add(entity::media::Column::MediaType.eq("image"))
+                .add(entity::media::Column::IsDeleted.eq(false)),
         )
+        .order_by_desc(entity::media::Column::CreatedAt)
         .all(state.pg_db.get_ref())
         .await
         .map_err(|err| {
@@ -116,5 +104,5 @@ pub async fn get_media_by_user(
             DbErr::Custom(err.to_string())
         })?;
 
-    Ok(medias)
+    Ok(images)
 }
@@ -0,0 +1,292 @@
+use std::{
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+
+use actix_http::Request;
+use actix_web::{
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    test,
+    web::{self, Data},
+    App, HttpResponse, HttpServer,
+};
+use config::Config as ConfigLoader;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectOptions, Database};
+use tokio::sync::Mutex;
+
+use crate::{
+    app::{
+        auth::{
+            dto::dtos::{change_user_password, save_refresh_token, update_user_session},
+            models::model::SaveRefreshTokenDto,
+        },
+        organization::{
+            controllers::controller::create_organization, models::model::AddOrganizationDto,
+        },
+        users::dto::dtos::get_user_by_member_id,
+    },
+    libs::{
+        code::gen_numeric_code,
+        jwt::{create_jwt, create_refresh_token, JwtDto, SCOPE_FULL},
+        notifier::build_notifier,
+        pword::{hash_password, CommonPasswords},
+        session::SessionCache,
+        throttle::LoginThrottle,
+    },
+    load_config, routes,
+    utils::models::SaveMemberOrgDto,
+    AppState,
+};
+
+// Tests run against the database named by DATABASE_URL in `.env` (see docker-compose.yaml).
+// Fixtures use random names and contacts, so tests can share it and run in parallel.
+static MIGRATED: Mutex<bool> = Mutex::const_new(false);
+
+// Every seeded founder signs in with this password.
+pub const TENANT_PASSWORD: &str = "Kente#Weaver7";
+
+pub struct Tenant {
+    pub organization: entity::organization::Model,
+    pub member: entity::members::Model,
+    pub user: entity::users::Model,
+    pub token: String,
+    pub refresh_token: String,
+}
+
+// Each test gets its own small pool, since every #[actix_web::test] runs its own runtime.
+pub async fn test_state() -> web::Data<AppState> {
+    dotenvy::dotenv().ok();
+
+    // The mock identity provider listens on plain http on 127.0.0.1.
+    let settings = ConfigLoader::builder()
+        .add_source(load_config().expect("Failed to load configuration"))
+        .set_override("oidc.allow_insecure_loopback", true)
+        .and_then(|builder| builder.build())
+        .expect("Failed to load configuration");
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+
+    let mut options = ConnectOptions::new(database_url);
+
+    options
+        .max_connections(5)
+        .min_connections(1)
+        .connect_timeout(Duration::from_secs(8))
+        .sqlx_logging(false);
+
+    let db = Database::connect(options)
+        .await
+        .expect("Failed to connect to the test database");
+
+    let mut migrated = MIGRATED.lock().await;
+
+    if !*migrated {
+        Migrator::up(&db, None)
+            .await
+            .expect("Failed to run migrations");
+        *migrated = true;
+    }
+
+    web::Data::new(AppState {
+        config: settings.clone(),
+        pg_db: Arc::new(Data::new(db)),
+        sessions: Arc::new(SessionCache::new(Duration::from_secs(
+            settings.get::<u64>("session.cache_ttl").unwrap(),
+        ))),
+        notifier: build_notifier(&settings),
+        throttle: Arc::new(LoginThrottle::from_config(&settings)),
+        common_passwords: Arc::new(CommonPasswords::from_config(&settings)),
+    })
+}
+
+pub async fn test_app(
+    state: &web::Data<AppState>,
+) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error> {
+    let state = state.clone();
+
+    test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(|cfg| routes(cfg, state)),
+    )
+    .await
+}
+
+pub fn random_phone() -> String {
+    format!("02{}", gen_numeric_code(8))
+}
+
+// An organization with a founding member holding `role`, signed in with a full-access token.
+pub async fn seed_tenant(role: &str, state: &web::Data<AppState>) -> Tenant {
+    let suffix = gen_numeric_code(8);
+    let phone = random_phone();
+
+    let (organization, member, _) = create_organization(
+        AddOrganizationDto {
+            name: format!("Test Organization {}", suffix),
+            email: format!("org{}@example.com", suffix),
+            phone: random_phone(),
+            address: "Test Address".to_string(),
+        },
+        SaveMemberOrgDto {
+            first_name: "Test".to_string(),
+            last_name: format!("Admin {}", suffix),
+            email: Some(format!("admin{}@example.com", suffix)),
+            phone,
+            address: "Test Address".to_string(),
+            gender: "male".to_string(),
+            date_joined: None,
+            date_of_birth: None,
+        },
+        role,
+        state,
+    )
+    .await
+    .expect("Failed to seed organization");
+
+    // past the first-login password change, as a full-access session would be
+    change_user_password(
+        get_user_by_member_id(member.id, state)
+            .await
+            .expect("Failed to load seeded user")
+            .id,
+        hash_password(TENANT_PASSWORD, &state.config).expect("Failed to hash password"),
+        state,
+    )
+    .await
+    .expect("Failed to set seeded user password");
+
+    let user = get_user_by_member_id(member.id, state)
+        .await
+        .expect("Failed to load seeded user");
+
+    let (token, refresh_token) = sign_in(&user, &member, state).await;
+
+    Tenant {
+        organization,
+        member,
+        user,
+        token,
+        refresh_token,
+    }
+}
+
+// Opens a session for the user the same way login does and returns its access and
+// refresh tokens.
+pub async fn sign_in(
+    user: &entity::users::Model,
+    member: &entity::members::Model,
+    state: &web::Data<AppState>,
+) -> (String, String) {
+    let session_id = uuid::Uuid::new_v4();
+    let refresh_token = create_refresh_token(state);
+
+    save_refresh_token(
+        SaveRefreshTokenDto {
+            user_id: user.id,
+            family_id: session_id,
+            token_hash: refresh_token.hash.clone(),
+            expires_at: refresh_token.expires_at,
+        },
+        state,
+    )
+    .await
+    .expect("Failed to save refresh token");
+
+    update_user_session(user.id, Some(session_id), state)
+        .await
+        .expect("Failed to update session");
+
+    let access_token = create_jwt(
+        JwtDto {
+            user_id: user.id,
+            member_id: member.id,
+            organization_id: member.organization_id,
+            role: user.role.clone(),
+            session_id,
+            scope: SCOPE_FULL.to_string(),
+        },
+        state,
+    )
+    .await
+    .token;
+
+    (access_token, refresh_token.token)
+}
+
+pub fn bearer(token: &str) -> (&'static str, String) {
+    ("Authorization", format!("Bearer {}", token))
+}
+
+// Stands in for an organization's OpenID provider. The token endpoint redeems only
+// MOCK_IDP_CODE, answering with whatever ID token the test queued, and the JWKS
+// endpoint publishes the public half of testdata/oidc_rsa.pem.
+pub struct MockIdentityProvider {
+    pub issuer: String,
+    id_token: Arc<StdMutex<String>>,
+}
+
+pub const MOCK_IDP_CODE: &str = "valid-code";
+pub const MOCK_IDP_CLIENT_ID: &str = "tlms-test";
+pub const MOCK_IDP_KEY_ID: &str = "test-key";
+pub const MOCK_IDP_RSA_KEY: &str = include_str!("testdata/oidc_rsa.pem");
+const MOCK_IDP_JWKS: &str = include_str!("testdata/oidc_jwks.json");
+
+#[derive(serde::Deserialize)]
+struct MockTokenRequest {
+    code: String,
+}
+
+impl MockIdentityProvider {
+    pub async fn start() -> Self {
+        let id_token = Arc::new(StdMutex::new(String::new()));
+        let queued = id_token.clone();
+
+        let server = HttpServer::new(move || {
+            let queued = queued.clone();
+
+            App::new()
+                .route(
+                    "/token",
+                    web::post().to(move |form: web::Form<MockTokenRequest>| {
+                        let id_token = queued.lock().unwrap().clone();
+
+                        async move {
+                            if form.code != MOCK_IDP_CODE {
+                                return HttpResponse::BadRequest()
+                                    .json(serde_json::json!({ "error": "invalid_grant" }));
+                            }
+
+                            HttpResponse::Ok().json(serde_json::json!({
+                                "access_token": "mock-access-token",
+                                "token_type": "Bearer",
+                                "id_token": id_token,
+                            }))
+                        }
+                    }),
+                )
+                .route(
+                    "/jwks",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .content_type("application/json")
+                            .body(MOCK_IDP_JWKS)
+                    }),
+                )
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .expect("Failed to bind mock identity provider");
+
+        let issuer = format!("http://{}", server.addrs()[0]);
+
+        actix_web::rt::spawn(server.run());
+
+        MockIdentityProvider { issuer, id_token }
+    }
+
+    pub fn queue_id_token(&self, id_token: String) {
+        *self.id_token.lock().unwrap() = id_token;
+    }
+}